@@ -0,0 +1,12 @@
+-- Add migration script here
+CREATE TABLE IF NOT exists scouter.api_keys (
+  id integer generated by default as identity primary key,
+  created_at timestamp not null default (timezone('utc', now())),
+  name varchar(256) not null,
+  key_prefix varchar(32) not null,
+  key_hash varchar(64) not null,
+  scopes text[] not null default '{}',
+  repositories text[],
+  revoked_at timestamp,
+  UNIQUE (key_hash)
+);
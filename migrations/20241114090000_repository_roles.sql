@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE IF NOT exists scouter.repository_roles (
+  created_at timestamp not null default (timezone('utc', now())),
+  updated_at timestamp not null default (timezone('utc', now())),
+  subject varchar(256) not null,
+  repository varchar(256) not null,
+  role varchar(32) not null,
+  PRIMARY KEY (subject, repository)
+);
+
+CREATE INDEX ON scouter.repository_roles (repository);
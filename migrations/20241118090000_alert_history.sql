@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE TABLE IF NOT exists scouter.drift_alert_history (
+  id integer generated by default as identity primary key,
+  created_at timestamp not null default (timezone('utc', now())),
+  alert_id integer not null,
+  previous_status varchar(32) not null,
+  status varchar(32) not null,
+  actor varchar(256) not null,
+  subject varchar(256) not null,
+  comment text
+);
+
+CREATE INDEX ON scouter.drift_alert_history (alert_id, created_at);
+
+CREATE INDEX ON scouter.drift_alerts (id);
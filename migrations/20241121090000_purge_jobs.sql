@@ -0,0 +1,15 @@
+-- Add migration script here
+CREATE TABLE IF NOT exists scouter.purge_jobs (
+  id integer generated by default as identity primary key,
+  created_at timestamp not null default (timezone('utc', now())),
+  updated_at timestamp not null default (timezone('utc', now())),
+  name varchar(256) not null,
+  repository varchar(256) not null,
+  version varchar(256) not null,
+  requested_by varchar(256) not null,
+  status varchar(32) not null default 'pending',
+  rows_deleted bigint not null default 0,
+  error text
+);
+
+CREATE INDEX ON scouter.purge_jobs (status);
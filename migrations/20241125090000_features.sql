@@ -0,0 +1,25 @@
+-- Add migration script here
+CREATE TABLE IF NOT exists scouter.features (
+  name varchar(256) not null,
+  repository varchar(256) not null,
+  version varchar(256) not null,
+  feature varchar(256) not null,
+  data_type varchar(32) not null,
+  first_seen timestamp not null,
+  last_seen timestamp not null,
+  record_count bigint not null default 0,
+  PRIMARY KEY (name, repository, version, feature)
+);
+
+-- backfill the catalogue from records ingested before it existed
+INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+SELECT name, repository, version, feature, 'spc', min(created_at), max(created_at), count(*)
+FROM scouter.drift
+GROUP BY name, repository, version, feature
+ON CONFLICT DO NOTHING;
+
+INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+SELECT name, repository, version, feature, 'psi', min(created_at), max(created_at), count(*)
+FROM scouter.observed_bin_count
+GROUP BY name, repository, version, feature
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,19 @@
+-- Add migration script here
+CREATE TABLE IF NOT exists scouter.drift_task_runs (
+  id integer generated by default as identity primary key,
+  created_at timestamp not null default (timezone('utc', now())),
+  name varchar(256) not null,
+  repository varchar(256) not null,
+  version varchar(256) not null,
+  drift_type varchar(256) not null,
+  window_start timestamp not null,
+  window_end timestamp not null,
+  duration_ms bigint not null default 0,
+  -- drift values, bin observations or requests evaluated by the run
+  rows_read bigint not null default 0,
+  alerts_produced integer not null default 0,
+  status varchar(32) not null,
+  error text
+);
+
+CREATE INDEX ON scouter.drift_task_runs (name, repository, version, id DESC);
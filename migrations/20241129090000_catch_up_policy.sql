@@ -0,0 +1,8 @@
+-- Add migration script here
+-- How the drift executor handles scheduled runs that were missed while the server was down
+ALTER TABLE scouter.drift_profile
+add column catch_up varchar(32) not null default 'run_once';
+
+-- Catch-up policy applied by a run that covered missed schedule intervals, null for on-time runs
+ALTER TABLE scouter.drift_task_runs
+add column catch_up varchar(32);
@@ -0,0 +1,5 @@
+-- Add migration script here
+-- records are stamped with the time they were received (or the client timestamp), so metrics
+-- reported by several instances of a service at the same moment are all kept
+ALTER TABLE scouter.observability_metrics
+  DROP CONSTRAINT IF EXISTS observability_metrics_created_at_name_repository_version_key;
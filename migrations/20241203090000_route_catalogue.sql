@@ -0,0 +1,7 @@
+-- Add migration script here
+-- backfill the routes of observability records ingested before routes were catalogued
+INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+SELECT name, repository, version, route_metric->>'route_name', 'route', min(created_at), max(created_at), count(*)
+FROM scouter.observability_metrics, jsonb_array_elements(route_metrics) as route_metric
+GROUP BY name, repository, version, route_metric->>'route_name'
+ON CONFLICT DO NOTHING;
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Add migration script here
+-- Observability profiles share the table with drift profiles, so a service can have both
+ALTER TABLE scouter.drift_profile DROP CONSTRAINT drift_profile_pkey;
+ALTER TABLE scouter.drift_profile ADD PRIMARY KEY (name, repository, version, drift_type);
@@ -0,0 +1,17 @@
+-- Add migration script here
+-- spc, psi and route series may share a name, so each kind is catalogued separately
+ALTER TABLE scouter.features DROP CONSTRAINT features_pkey;
+ALTER TABLE scouter.features ADD PRIMARY KEY (name, repository, version, feature, data_type);
+
+-- backfill the psi features and routes that were folded into a same-named feature
+INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+SELECT name, repository, version, feature, 'psi', min(created_at), max(created_at), count(*)
+FROM scouter.observed_bin_count
+GROUP BY name, repository, version, feature
+ON CONFLICT DO NOTHING;
+
+INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+SELECT name, repository, version, route_metric->>'route_name', 'route', min(created_at), max(created_at), count(*)
+FROM scouter.observability_metrics, jsonb_array_elements(route_metrics) as route_metric
+GROUP BY name, repository, version, route_metric->>'route_name'
+ON CONFLICT DO NOTHING;
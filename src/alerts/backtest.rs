@@ -0,0 +1,168 @@
+use crate::alerts::types::{Drifter, RunOptions};
+use crate::sql::postgres::PostgresClient;
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use tracing::info;
+
+// 20 days of hourly windows
+pub const MAX_BACKTEST_WINDOWS: usize = 480;
+
+// Upper bound on windows x queries per window, spc profiles query each feature in every window
+pub const MAX_BACKTEST_QUERIES: usize = 5000;
+
+/// Alerts a profile would have raised over a single replayed window
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestWindow {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    // records evaluated in the window
+    pub rows_read: i64,
+    pub alerts: Vec<BTreeMap<String, String>>,
+}
+
+/// Split a time range into consecutive fixed-size windows with dispatch disabled
+///
+/// The last window is cut short at `end` when the range isn't a multiple of the window size
+///
+/// # Arguments
+///
+/// * `start` - Start of the range
+/// * `end` - End of the range
+/// * `window_minutes` - Size of each window
+///
+/// # Returns
+///
+/// * `Result<Vec<RunOptions>>` - One set of run options per window
+pub fn split_windows(
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    window_minutes: i32,
+) -> Result<Vec<RunOptions>> {
+    if window_minutes <= 0 {
+        return Err(anyhow::anyhow!("Window size must be positive"));
+    }
+
+    let window = Duration::minutes(window_minutes as i64);
+    let count = (end - start).num_minutes().max(0) as usize / window_minutes as usize;
+
+    if count > MAX_BACKTEST_WINDOWS {
+        return Err(anyhow::anyhow!(
+            "Backtest range covers more than {} windows, use a larger window or a shorter range",
+            MAX_BACKTEST_WINDOWS
+        ));
+    }
+
+    let mut windows = Vec::new();
+    let mut window_start = start;
+
+    while window_start < end {
+        let window_end = (window_start + window).min(end);
+        windows.push(RunOptions {
+            start: window_start,
+            end: window_end,
+            dispatch: false,
+        });
+        window_start = window_end;
+    }
+
+    Ok(windows)
+}
+
+/// Check that replaying a drifter over a number of windows stays within the query budget
+///
+/// # Arguments
+///
+/// * `drifter` - Drifter for the profile being tested
+/// * `windows` - Number of windows to replay
+///
+/// # Returns
+///
+/// * `Result<()>` - Error if the backtest would issue more than `MAX_BACKTEST_QUERIES` queries
+pub fn check_backtest_size(drifter: &Drifter, windows: usize) -> Result<()> {
+    let queries = windows * drifter.queries_per_run();
+
+    if queries > MAX_BACKTEST_QUERIES {
+        return Err(anyhow::anyhow!(
+            "Backtest would run {} queries ({} windows x {} per window), the limit is {}. Use a larger window, a shorter range or fewer features",
+            queries,
+            windows,
+            drifter.queries_per_run(),
+            MAX_BACKTEST_QUERIES
+        ));
+    }
+
+    Ok(())
+}
+
+/// Replay a drifter over historical windows
+///
+/// # Arguments
+///
+/// * `db_client` - Postgres client to read historical records with
+/// * `drifter` - Drifter for the profile being tested
+/// * `windows` - Windows to replay, in order
+///
+/// # Returns
+///
+/// * `Result<Vec<BacktestWindow>>` - Alerts raised in each window
+pub async fn backtest(
+    db_client: &PostgresClient,
+    drifter: &Drifter,
+    windows: &[RunOptions],
+) -> Result<Vec<BacktestWindow>> {
+    let mut results = Vec::with_capacity(windows.len());
+
+    for options in windows.iter() {
+        // alerts must never leave the server during a replay
+        let options = RunOptions {
+            dispatch: false,
+            ..options.clone()
+        };
+        let result = drifter.check_for_alerts(db_client, &options).await?;
+
+        results.push(BacktestWindow {
+            start: options.start,
+            end: options.end,
+            rows_read: result.rows_read,
+            alerts: result.alerts,
+        });
+    }
+
+    info!(
+        "Backtest replayed {} windows, {} raised alerts",
+        results.len(),
+        results
+            .iter()
+            .filter(|window| !window.alerts.is_empty())
+            .count()
+    );
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_split_windows() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        let windows = split_windows(start, start + Duration::minutes(150), 60).unwrap();
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[0].end, windows[1].start);
+        assert_eq!(windows[2].end - windows[2].start, Duration::minutes(30));
+        assert!(windows.iter().all(|window| !window.dispatch));
+
+        assert!(split_windows(start, start, 60).unwrap().is_empty());
+        assert!(split_windows(start, start + Duration::days(20), 60).is_ok());
+        assert!(split_windows(start, start + Duration::days(30), 60).is_err());
+        assert!(split_windows(start, start + Duration::minutes(5), 0).is_err());
+    }
+}
@@ -1,17 +1,26 @@
+use crate::alerts::catch_up::{CatchUpConfig, CatchUpPlan, CatchUpPolicy};
+use crate::alerts::observability::drift::ObservabilityDrifter;
+use crate::alerts::observability::types::{ObservabilityAlertProfile, OBSERVABILITY_DRIFT_TYPE};
+use crate::alerts::psi::drift::PsiDrifter;
 use crate::alerts::spc::drift::SpcDrifter;
-use crate::alerts::types::Drifter;
+use crate::alerts::types::{DriftProfile, DriftRunResult, Drifter, RunOptions};
+use crate::alerts::volume::VolumeConfig;
 use crate::api::schema::ServiceInfo;
 use crate::sql::postgres::PostgresClient;
+use crate::sql::schema::{DriftTaskRun, TaskRequest};
 
-use chrono::NaiveDateTime;
-use scouter::core::drift::base::DriftProfile;
+use chrono::{NaiveDateTime, Utc};
 use scouter::core::drift::base::DriftType;
 use std::collections::BTreeMap;
 use std::result::Result;
 use std::result::Result::Ok;
 use std::str::FromStr;
+use std::time::Instant;
 use tracing::error;
 use tracing::info;
+/// Actor recorded in the alert history when the executor resolves alerts
+const AUTO_RESOLVE_ACTOR: &str = "scouter";
+
 pub trait GetDrifter {
     fn get_drifter(&self) -> Drifter;
 }
@@ -33,40 +42,275 @@ impl GetDrifter for DriftProfile {
             DriftProfile::SpcDriftProfile(profile) => {
                 Drifter::SpcDrifter(SpcDrifter::new(profile.clone()))
             }
+            DriftProfile::PsiDriftProfile(profile) => {
+                Drifter::PsiDrifter(PsiDrifter::new(profile.clone()))
+            }
         }
     }
 }
 
+impl GetDrifter for ObservabilityAlertProfile {
+    /// Get a Drifter for processing observability alert tasks
+    fn get_drifter(&self) -> Drifter {
+        Drifter::ObservabilityDrifter(ObservabilityDrifter::new(self.clone()))
+    }
+}
+
+/// Build the drifter for a scheduled task from its stored profile
+///
+/// # Arguments
+///
+/// * `task` - Task pulled from the drift profile table
+///
+/// # Returns
+///
+/// * `Result<Drifter>` - Drifter for the task's drift type
+pub fn get_task_drifter(task: &TaskRequest) -> Result<Drifter, anyhow::Error> {
+    if task.drift_type == OBSERVABILITY_DRIFT_TYPE {
+        let profile = ObservabilityAlertProfile::from_str(&task.profile)?;
+        return Ok(profile.get_drifter());
+    }
+
+    let drift_type = DriftType::from_str(&task.drift_type)
+        .map_err(|e| anyhow::anyhow!("Error converting drift type: {:?}", e))?;
+    let profile = DriftProfile::from_str(drift_type, &task.profile)
+        .map_err(|e| anyhow::anyhow!("Error converting drift profile: {:?}", e))?;
+
+    Ok(profile.get_drifter())
+}
+
 pub struct DriftExecutor {
     db_client: PostgresClient,
+    volume_config: VolumeConfig,
+    catch_up_config: CatchUpConfig,
 }
 
 impl DriftExecutor {
     pub fn new(db_client: PostgresClient) -> Self {
-        Self { db_client }
+        Self {
+            db_client,
+            volume_config: VolumeConfig::from_env(),
+            catch_up_config: CatchUpConfig::from_env(),
+        }
     }
 
-    /// Process a single drift computation task
+    /// Insert the alerts raised during a run
+    ///
+    /// Every alert is attempted even if an earlier one fails
     ///
     /// # Arguments
     ///
-    /// * `drift_profile` - Drift profile to compute drift for
-    /// * `previous_run` - Previous run timestamp
-    /// * `schedule` - Schedule for drift computation
-    /// * `transaction` - Postgres transaction
+    /// * `service_info` - Service the drift run was executed for
+    /// * `alerts` - Alerts to insert
     ///
     /// # Returns
     ///
-    pub async fn process_task(
-        &mut self,
-        profile: DriftProfile,
-        previous_run: NaiveDateTime,
-    ) -> Result<Option<Vec<BTreeMap<String, String>>>, anyhow::Error> {
-        // match Drifter enum
-        profile
-            .get_drifter()
-            .check_for_alerts(&self.db_client, previous_run)
+    /// * `Result<()>` - Error if any alert could not be inserted
+    async fn insert_alerts(
+        &self,
+        service_info: &ServiceInfo,
+        alerts: &[BTreeMap<String, String>],
+    ) -> Result<(), anyhow::Error> {
+        let mut failed = 0;
+
+        for alert in alerts.iter() {
+            if let Err(e) = self
+                .db_client
+                .insert_drift_alert(
+                    service_info,
+                    alert.get("feature").unwrap_or(&"NA".to_string()),
+                    alert,
+                )
+                .await
+            {
+                error!("Error inserting drift alerts: {:?}", e);
+                failed += 1;
+            }
+        }
+
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "Failed to insert {} of {} drift alerts",
+                failed,
+                alerts.len()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Resolve open alerts for features that were evaluated in a run and came back clean
+    ///
+    /// # Arguments
+    ///
+    /// * `service_info` - Service the drift run was executed for
+    /// * `result` - Result of the drift run
+    async fn resolve_clean_features(&self, service_info: &ServiceInfo, result: &DriftRunResult) {
+        let clean_features = result.clean_features();
+
+        if clean_features.is_empty() {
+            return;
+        }
+
+        match self
+            .db_client
+            .resolve_drift_alerts(
+                service_info,
+                &clean_features,
+                AUTO_RESOLVE_ACTOR,
+                "Resolved automatically after a clean drift run",
+            )
             .await
+        {
+            Ok(0) => {}
+            Ok(count) => info!(
+                "Auto-resolved {} alerts for {}/{}/{}",
+                count, service_info.repository, service_info.name, service_info.version
+            ),
+            Err(e) => error!("Error auto-resolving drift alerts: {:?}", e),
+        }
+    }
+
+    /// Insert the alerts raised by a run, then resolve open alerts of the features that came back clean
+    ///
+    /// Nothing is resolved unless every alert was persisted, so a run that could not record its
+    /// alerts never closes the alerts of other features
+    ///
+    /// # Arguments
+    ///
+    /// * `service_info` - Service the drift run was executed for
+    /// * `result` - Result of the drift run
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Error if any alert could not be inserted
+    async fn persist_run_alerts(
+        &self,
+        service_info: &ServiceInfo,
+        result: &DriftRunResult,
+    ) -> Result<(), anyhow::Error> {
+        self.insert_alerts(service_info, &result.alerts).await?;
+        self.resolve_clean_features(service_info, result).await;
+
+        Ok(())
+    }
+
+    /// Run a task's drifter over a single window, then insert and resolve alerts
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - Task pulled from the drift profile table
+    /// * `service_info` - Service the task belongs to
+    /// * `start` - Start of the window
+    /// * `end` - End of the window
+    /// * `check_volume` - Also check the record volume of the monitored features since `start`
+    ///
+    /// # Returns
+    ///
+    /// * `DriftTaskRun` - Outcome of the run, failures are recorded rather than returned
+    async fn execute_task(
+        &self,
+        task: &TaskRequest,
+        service_info: &ServiceInfo,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+        check_volume: bool,
+    ) -> DriftTaskRun {
+        let started = Instant::now();
+        let mut run = DriftTaskRun::new(
+            &task.name,
+            &task.repository,
+            &task.version,
+            &task.drift_type,
+            start,
+            end,
+        );
+
+        match get_task_drifter(task) {
+            Ok(drifter) => {
+                run.window_start = drifter.window_start(start, end);
+                let options = RunOptions::new(run.window_start, end);
+
+                // features that stopped reporting never show up in the drift
+                // computation, so their volume is checked separately
+                let volume_alerts = if check_volume {
+                    drifter
+                        .check_feature_volume(&self.db_client, start, &self.volume_config)
+                        .await
+                } else {
+                    Vec::new()
+                };
+
+                let result = match drifter.check_for_alerts(&self.db_client, &options).await {
+                    // check for alerts
+                    Ok(mut result) => {
+                        info!("Drift task processed successfully");
+                        result.alerts.extend(volume_alerts);
+                        run.rows_read = result.rows_read;
+                        result
+                    }
+                    // volume alerts are still recorded, no feature was evaluated so none are resolved
+                    Err(e) => {
+                        error!("Error processing drift task: {:?}", e);
+                        run.fail(&e);
+                        DriftRunResult {
+                            alerts: volume_alerts,
+                            ..Default::default()
+                        }
+                    }
+                };
+
+                run.alerts_produced = result.alerts.len() as i32;
+                if let Err(e) = self.persist_run_alerts(service_info, &result).await {
+                    error!("{:?}", e);
+                    // the drift error is kept when the run already failed
+                    if run.error.is_none() {
+                        run.fail(&e);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("{:?}", e);
+                run.fail(&e);
+            }
+        }
+
+        run.duration_ms = started.elapsed().as_millis() as i64;
+        run
+    }
+
+    /// Run a profile immediately over a given window, outside of its schedule
+    ///
+    /// The profile's run dates and run history are left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `task` - Profile task to run
+    /// * `options` - Window to evaluate and whether to dispatch alerts
+    /// * `persist` - Insert the alerts raised by the run
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DriftRunResult>` - Computed drift and any alerts raised
+    pub async fn run_profile(
+        &self,
+        task: &TaskRequest,
+        options: &RunOptions,
+        persist: bool,
+    ) -> Result<DriftRunResult, anyhow::Error> {
+        let drifter = get_task_drifter(task)?;
+        let result = drifter.check_for_alerts(&self.db_client, options).await?;
+
+        if persist {
+            let service_info = ServiceInfo {
+                repository: task.repository.clone(),
+                name: task.name.clone(),
+                version: task.version.clone(),
+            };
+            self.insert_alerts(&service_info, &result.alerts).await?;
+        }
+
+        Ok(result)
     }
 
     /// Execute single drift computation and alerting
@@ -100,59 +344,74 @@ impl DriftExecutor {
             version: task.version.clone(),
         };
 
-        // match drift_type
-        match DriftType::from_str(&task.drift_type) {
-            // match drift_profile
-            Ok(drift_type) => match DriftProfile::from_str(drift_type, task.profile) {
-                // process drift profile task
-                Ok(profile) => match self.process_task(profile, task.previous_run).await {
-                    // check for alerts
-                    Ok(alerts) => {
-                        info!("Drift task processed successfully");
-
-                        if let Some(alerts) = alerts {
-                            // insert each task into db
-                            for alert in alerts {
-                                if let Err(e) = self
-                                    .db_client
-                                    .insert_drift_alert(
-                                        &service_info,
-                                        alert.get("feature").unwrap_or(&"NA".to_string()),
-                                        &alert,
-                                    )
-                                    .await
-                                {
-                                    error!("Error inserting drift alerts: {:?}", e);
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!("Error processing drift task: {:?}", e);
-                    }
-                },
-                Err(e) => {
-                    error!("Error converting drift profile: {:?}", e);
-                }
-            },
-            Err(e) => {
-                error!("Error converting drift type: {:?}", e);
-            }
-        }
+        let window_end = Utc::now().naive_utc();
+        let policy = CatchUpPolicy::from_str(&task.catch_up).unwrap_or_default();
+        let plan = CatchUpPlan::new(
+            policy,
+            &task.schedule,
+            task.previous_run,
+            window_end,
+            &self.catch_up_config,
+        )
+        .unwrap_or_else(|e| {
+            error!("Error planning catch-up runs: {:?}", e);
+            CatchUpPlan::single(task.previous_run, window_end)
+        });
+        let catch_up = plan.catch_up.map(|policy| policy.as_str().to_string());
 
+        // the task is claimed by moving its next run forward, so the row lock is released
+        // before any drift is computed or alerts are dispatched
         if let Err(e) = PostgresClient::update_drift_profile_run_dates(
             &mut transaction,
             &service_info,
+            &task.drift_type,
             &task.schedule,
+            plan.last_scheduled,
         )
         .await
         {
             error!("Error updating drift profile run dates: {:?}", e);
-        } else {
-            info!("Drift profile run dates updated successfully");
+            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
+            return Ok(());
         }
 
         transaction.commit().await?;
+        info!("Drift profile run dates updated successfully");
+
+        if let Some((start, end)) = plan.skipped {
+            info!(
+                "Skipping missed runs for {}/{}/{} between {} and {}",
+                task.repository, task.name, task.version, start, end
+            );
+            let mut run = DriftTaskRun::new(
+                &task.name,
+                &task.repository,
+                &task.version,
+                &task.drift_type,
+                start,
+                end,
+            );
+            run.skip();
+            run.catch_up = catch_up.clone();
+
+            if let Err(e) = self.db_client.insert_drift_task_run(&run).await {
+                error!("Error recording drift task run: {:?}", e);
+            }
+        }
+
+        let window_count = plan.windows.len();
+        for (index, (start, end)) in plan.windows.into_iter().enumerate() {
+            // volume is compared against a trailing average, so it is only checked once, for the latest window
+            let check_volume = index + 1 == window_count;
+            let mut run = self
+                .execute_task(&task, &service_info, start, end, check_volume)
+                .await;
+            run.catch_up = catch_up.clone();
+
+            if let Err(e) = self.db_client.insert_drift_task_run(&run).await {
+                error!("Error recording drift task run: {:?}", e);
+            }
+        }
 
         Ok(())
     }
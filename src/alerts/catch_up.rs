@@ -0,0 +1,230 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{NaiveDateTime, TimeZone, Utc};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+// Upper bound on the windows a single catch-up evaluates. Every window runs the drift queries
+// inside one poll, so a long outage would otherwise hold up the executor and flood the database
+const DEFAULT_MAX_CATCH_UP_RUNS: usize = 48;
+
+/// How the drift executor handles scheduled runs that were missed, e.g. while the server was down
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUpPolicy {
+    // only evaluate the most recent interval, the rest of the gap is recorded as skipped
+    Skip,
+    // evaluate the whole gap in a single run
+    #[default]
+    RunOnce,
+    // evaluate every missed interval in its own run
+    RunEach,
+}
+
+impl CatchUpPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CatchUpPolicy::Skip => "skip",
+            CatchUpPolicy::RunOnce => "run_once",
+            CatchUpPolicy::RunEach => "run_each",
+        }
+    }
+}
+
+impl FromStr for CatchUpPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(policy: &str) -> Result<Self, Self::Err> {
+        match policy {
+            "skip" => Ok(CatchUpPolicy::Skip),
+            "run_once" => Ok(CatchUpPolicy::RunOnce),
+            "run_each" => Ok(CatchUpPolicy::RunEach),
+            _ => Err(anyhow!(
+                "Unknown catch-up policy: {}. Expected one of skip, run_once, run_each",
+                policy
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchUpConfig {
+    // most windows a run_each catch-up evaluates, older intervals are folded into the first one
+    pub max_runs: usize,
+}
+
+impl CatchUpConfig {
+    // Load catch-up settings from SCOUTER_MAX_CATCH_UP_RUNS
+    pub fn from_env() -> Self {
+        let max_runs = std::env::var("SCOUTER_MAX_CATCH_UP_RUNS")
+            .ok()
+            .and_then(|runs| runs.parse::<usize>().ok())
+            .filter(|runs| *runs > 0)
+            .unwrap_or(DEFAULT_MAX_CATCH_UP_RUNS);
+
+        Self { max_runs }
+    }
+}
+
+/// Windows a scheduled task evaluates once its catch-up policy is applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatchUpPlan {
+    // (start, end] of each window to evaluate, oldest first
+    pub windows: Vec<(NaiveDateTime, NaiveDateTime)>,
+    // part of the gap that is not evaluated under the skip policy
+    pub skipped: Option<(NaiveDateTime, NaiveDateTime)>,
+    // latest scheduled time covered by the plan, recorded as the profile's previous run
+    pub last_scheduled: Option<NaiveDateTime>,
+    // policy applied, None if no scheduled runs were missed
+    pub catch_up: Option<CatchUpPolicy>,
+}
+
+impl CatchUpPlan {
+    /// Plan that evaluates everything since the previous run in a single window
+    pub fn single(previous_run: NaiveDateTime, now: NaiveDateTime) -> Self {
+        Self {
+            windows: vec![(previous_run, now)],
+            skipped: None,
+            last_scheduled: None,
+            catch_up: None,
+        }
+    }
+
+    /// Work out the windows a task evaluates
+    ///
+    /// Every scheduled time between the previous run and now beyond the first one is a missed run
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Catch-up policy of the profile
+    /// * `schedule` - Cron schedule of the profile
+    /// * `previous_run` - Previous run timestamp
+    /// * `now` - End of the evaluated range
+    /// * `config` - Catch-up settings
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CatchUpPlan>` - Windows to evaluate
+    pub fn new(
+        policy: CatchUpPolicy,
+        schedule: &str,
+        previous_run: NaiveDateTime,
+        now: NaiveDateTime,
+        config: &CatchUpConfig,
+    ) -> Result<Self> {
+        let schedule = Schedule::from_str(schedule)
+            .with_context(|| format!("Failed to parse cron expression: {}", schedule))?;
+
+        // only the most recent scheduled times are needed, however long the gap
+        let mut scheduled = VecDeque::with_capacity(config.max_runs + 1);
+        let mut scheduled_count = 0;
+
+        for time in schedule
+            .after(&Utc.from_utc_datetime(&previous_run))
+            .map(|time| time.naive_utc())
+            .take_while(|time| *time <= now)
+        {
+            if scheduled.len() > config.max_runs {
+                scheduled.pop_front();
+            }
+            scheduled.push_back(time);
+            scheduled_count += 1;
+        }
+
+        let mut plan = Self::single(previous_run, now);
+        plan.last_scheduled = scheduled.back().copied();
+
+        if scheduled_count <= 1 {
+            return Ok(plan);
+        }
+
+        plan.catch_up = Some(policy);
+
+        // start of the most recent interval
+        let latest = scheduled[scheduled.len() - 2];
+
+        match policy {
+            CatchUpPolicy::RunOnce => {}
+            CatchUpPolicy::Skip => {
+                plan.windows = vec![(latest, now)];
+                plan.skipped = Some((previous_run, latest));
+            }
+            CatchUpPolicy::RunEach => {
+                // the last scheduled time is the run being serviced, so it ends at now instead
+                scheduled.pop_back();
+                while scheduled.len() >= config.max_runs {
+                    scheduled.pop_front();
+                }
+
+                let boundaries = std::iter::once(previous_run)
+                    .chain(scheduled)
+                    .chain(std::iter::once(now))
+                    .collect::<Vec<_>>();
+
+                plan.windows = boundaries
+                    .windows(2)
+                    .map(|window| (window[0], window[1]))
+                    .collect();
+            }
+        }
+
+        Ok(plan)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+
+    #[test]
+    fn test_catch_up_plan() {
+        let hour = |hour: u32| {
+            NaiveDate::from_ymd_opt(2024, 1, 1)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+        };
+        let schedule = "0 0 * * * *";
+        let now = hour(5) + Duration::seconds(30);
+        let config = CatchUpConfig { max_runs: 3 };
+
+        // on time run
+        let plan =
+            CatchUpPlan::new(CatchUpPolicy::RunEach, schedule, hour(4), now, &config).unwrap();
+        assert_eq!(plan.windows, vec![(hour(4), now)]);
+        assert_eq!(plan.last_scheduled, Some(hour(5)));
+        assert_eq!(plan.catch_up, None);
+
+        let plan =
+            CatchUpPlan::new(CatchUpPolicy::RunOnce, schedule, hour(2), now, &config).unwrap();
+        assert_eq!(plan.windows, vec![(hour(2), now)]);
+        assert_eq!(plan.last_scheduled, Some(hour(5)));
+        assert_eq!(plan.catch_up, Some(CatchUpPolicy::RunOnce));
+
+        let plan = CatchUpPlan::new(CatchUpPolicy::Skip, schedule, hour(2), now, &config).unwrap();
+        assert_eq!(plan.windows, vec![(hour(4), now)]);
+        assert_eq!(plan.skipped, Some((hour(2), hour(4))));
+
+        let plan =
+            CatchUpPlan::new(CatchUpPolicy::RunEach, schedule, hour(3), now, &config).unwrap();
+        assert_eq!(plan.windows, vec![(hour(3), hour(4)), (hour(4), now)]);
+
+        // intervals beyond the limit are folded into the first window
+        let plan =
+            CatchUpPlan::new(CatchUpPolicy::RunEach, schedule, hour(0), now, &config).unwrap();
+        assert_eq!(
+            plan.windows,
+            vec![(hour(0), hour(3)), (hour(3), hour(4)), (hour(4), now)]
+        );
+        assert_eq!(plan.last_scheduled, Some(hour(5)));
+
+        assert!(CatchUpPlan::new(CatchUpPolicy::RunOnce, "hourly", hour(0), now, &config).is_err());
+        assert_eq!(
+            CatchUpPolicy::from_str("run_each").unwrap(),
+            CatchUpPolicy::RunEach
+        );
+        assert!(CatchUpPolicy::from_str("each").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::api::schema::ServiceInfo;
+use anyhow::Result;
+use scouter::core::dispatch::dispatcher::dispatcher_logic::AlertDispatcher;
+use scouter::core::dispatch::types::AlertDispatchType;
+use scouter::core::drift::base::DriftType;
+use scouter::core::drift::spc::types::{
+    SpcAlert, SpcAlertConfig, SpcAlertRule, SpcDriftConfig, SpcFeatureAlert, SpcFeatureAlerts,
+};
+use std::collections::{BTreeMap, HashMap};
+use tracing::error;
+
+// Alert keys that are sent as the alert itself rather than as its details
+const ALERT_KEYS: [&str; 2] = ["feature", "kind"];
+
+/// Group alert maps by feature into the format expected by the scouter dispatchers
+///
+/// Details other than the feature and kind (e.g. the observed value and threshold)
+/// are sent in the zone of each alert
+///
+/// # Arguments
+///
+/// * `alerts` - One entry per alert, each with at least a feature and kind
+///
+/// # Returns
+///
+/// * `SpcFeatureAlerts` - Alerts grouped by feature
+pub fn to_feature_alerts(alerts: &[BTreeMap<String, String>]) -> SpcFeatureAlerts {
+    let mut feature_alerts = SpcFeatureAlerts::new(!alerts.is_empty());
+
+    for alert in alerts.iter() {
+        let feature = alert.get("feature").cloned().unwrap_or_default();
+        let details = alert
+            .iter()
+            .filter(|(key, _)| !ALERT_KEYS.contains(&key.as_str()))
+            .map(|(key, value)| format!("{}: {}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        feature_alerts
+            .features
+            .entry(feature.clone())
+            .or_insert_with(|| SpcFeatureAlert {
+                feature,
+                alerts: Vec::new(),
+            })
+            .alerts
+            .push(SpcAlert {
+                kind: alert.get("kind").cloned().unwrap_or_default(),
+                zone: details,
+            });
+    }
+
+    feature_alerts
+}
+
+/// Send alerts that were not produced by an spc rule through the scouter alert dispatchers
+///
+/// # Arguments
+///
+/// * `service_info` - Service the alerts were raised for
+/// * `dispatch_type` - Dispatcher to send the alerts with
+/// * `dispatch_kwargs` - Dispatcher settings from the profile (e.g. slack channel, opsgenie team)
+/// * `alerts` - Alerts raised during the run
+///
+/// # Returns
+///
+/// * `Result<()>` - Error if the dispatcher could not be created or the alerts were not sent
+pub async fn dispatch_alerts(
+    service_info: &ServiceInfo,
+    dispatch_type: &AlertDispatchType,
+    dispatch_kwargs: &HashMap<String, String>,
+    alerts: &[BTreeMap<String, String>],
+) -> Result<()> {
+    if alerts.is_empty() {
+        return Ok(());
+    }
+
+    // dispatchers only read the service, dispatch type and kwargs from the config
+    let config = SpcDriftConfig {
+        sample_size: 0,
+        sample: false,
+        name: service_info.name.clone(),
+        repository: service_info.repository.clone(),
+        version: service_info.version.clone(),
+        targets: Vec::new(),
+        feature_map: None,
+        alert_config: SpcAlertConfig {
+            rule: SpcAlertRule {
+                rule: String::new(),
+                zones_to_monitor: Vec::new(),
+            },
+            dispatch_type: dispatch_type.clone(),
+            schedule: String::new(),
+            features_to_monitor: Vec::new(),
+            dispatch_kwargs: dispatch_kwargs.clone(),
+        },
+        drift_type: DriftType::SPC,
+    };
+
+    let alert_dispatcher = AlertDispatcher::new(&config).map_err(|e| {
+        error!(
+            "Error creating alert dispatcher for {}/{}/{}: {}",
+            service_info.repository, service_info.name, service_info.version, e
+        );
+        anyhow::anyhow!("Error creating alert dispatcher")
+    })?;
+
+    alert_dispatcher
+        .process_alerts(&to_feature_alerts(alerts))
+        .await
+        .map_err(|e| {
+            error!(
+                "Error processing alerts for {}/{}/{}: {}",
+                service_info.repository, service_info.name, service_info.version, e
+            );
+            anyhow::anyhow!("Error processing alerts")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_feature_alerts() {
+        let alert = |feature: &str, kind: &str| {
+            BTreeMap::from([
+                ("feature".to_string(), feature.to_string()),
+                ("kind".to_string(), kind.to_string()),
+                ("psi".to_string(), "0.3000".to_string()),
+                ("threshold".to_string(), "0.25".to_string()),
+            ])
+        };
+
+        let alerts = to_feature_alerts(&[
+            alert("feature_1", "PSI Threshold"),
+            alert("feature_1", "missing_data"),
+            alert("feature_2", "PSI Threshold"),
+        ]);
+
+        assert!(alerts.has_alerts);
+        assert_eq!(alerts.features.len(), 2);
+
+        let feature_1 = &alerts.features["feature_1"];
+        assert_eq!(feature_1.alerts.len(), 2);
+        assert_eq!(feature_1.alerts[0].kind, "PSI Threshold");
+        assert_eq!(feature_1.alerts[0].zone, "psi: 0.3000, threshold: 0.25");
+
+        assert!(!to_feature_alerts(&[]).has_alerts);
+    }
+}
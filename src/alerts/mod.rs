@@ -1,3 +1,9 @@
+pub mod backtest;
 pub mod base;
+pub mod catch_up;
+pub mod dispatch;
+pub mod observability;
+pub mod psi;
 pub mod spc;
 pub mod types;
+pub mod volume;
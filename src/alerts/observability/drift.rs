@@ -0,0 +1,297 @@
+use crate::alerts::dispatch::dispatch_alerts;
+use crate::alerts::observability::types::{ObservabilityAlertProfile, RouteThresholds, ALL_ROUTES};
+use crate::alerts::types::{DriftRunResult, RunOptions};
+use crate::api::schema::ServiceInfo;
+use crate::sql::postgres::PostgresClient;
+use crate::sql::schema::RouteSummary;
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use std::collections::BTreeMap;
+use tracing::info;
+
+pub const P95_LATENCY_KIND: &str = "p95_latency";
+pub const P99_LATENCY_KIND: &str = "p99_latency";
+pub const ERROR_RATE_KIND: &str = "error_rate";
+pub const REQUEST_COUNT_KIND: &str = "request_count";
+
+// Defines the ObservabilityDrifter struct
+// This is used to process threshold alerts for observability alert profiles
+pub struct ObservabilityDrifter {
+    service_info: ServiceInfo,
+    profile: ObservabilityAlertProfile,
+}
+
+fn threshold_alert(
+    route: &str,
+    kind: &str,
+    value: String,
+    threshold: String,
+) -> BTreeMap<String, String> {
+    let mut alert_map = BTreeMap::new();
+    alert_map.insert("feature".to_string(), route.to_string());
+    alert_map.insert("kind".to_string(), kind.to_string());
+    alert_map.insert("value".to_string(), value);
+    alert_map.insert("threshold".to_string(), threshold);
+    alert_map
+}
+
+impl ObservabilityDrifter {
+    pub fn new(profile: ObservabilityAlertProfile) -> Self {
+        Self {
+            service_info: ServiceInfo {
+                name: profile.name.clone(),
+                repository: profile.repository.clone(),
+                version: profile.version.clone(),
+            },
+            profile,
+        }
+    }
+
+    /// Service the drifter evaluates
+    pub fn service_info(&self) -> &ServiceInfo {
+        &self.service_info
+    }
+
+    /// Start of the window evaluated by a run
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_run` - Previous run timestamp, used when the profile has no fixed window
+    /// * `window_end` - End of the window, a fixed window is measured back from it
+    pub fn window_start(
+        &self,
+        previous_run: NaiveDateTime,
+        window_end: NaiveDateTime,
+    ) -> NaiveDateTime {
+        match self.profile.window_minutes() {
+            Some(minutes) => window_end - Duration::minutes(minutes as i64),
+            None => previous_run,
+        }
+    }
+
+    /// Check a single route summary against its thresholds
+    fn evaluate_route(
+        summary: &RouteSummary,
+        thresholds: &RouteThresholds,
+    ) -> Vec<BTreeMap<String, String>> {
+        let mut alerts = Vec::new();
+        let route = &summary.route_name;
+
+        let latencies = [
+            (P95_LATENCY_KIND, summary.p95, thresholds.p95_ceiling),
+            (P99_LATENCY_KIND, summary.p99, thresholds.p99_ceiling),
+        ];
+
+        for (kind, value, ceiling) in latencies {
+            if let (Some(value), Some(ceiling)) = (value, ceiling) {
+                if value > ceiling {
+                    alerts.push(threshold_alert(
+                        route,
+                        kind,
+                        format!("{:.4}", value),
+                        ceiling.to_string(),
+                    ));
+                }
+            }
+        }
+
+        if let Some(ceiling) = thresholds.error_rate_ceiling {
+            if summary.request_count > 0 {
+                let error_rate = summary.error_count as f64 / summary.request_count as f64;
+
+                if error_rate > ceiling {
+                    alerts.push(threshold_alert(
+                        route,
+                        ERROR_RATE_KIND,
+                        format!("{:.4}", error_rate),
+                        ceiling.to_string(),
+                    ));
+                }
+            }
+        }
+
+        if let Some(floor) = thresholds.request_count_floor {
+            if summary.request_count < floor {
+                alerts.push(threshold_alert(
+                    route,
+                    REQUEST_COUNT_KIND,
+                    summary.request_count.to_string(),
+                    floor.to_string(),
+                ));
+            }
+        }
+
+        alerts
+    }
+
+    /// Evaluate the profile thresholds against the route summaries of a window
+    ///
+    /// Routes configured by name take precedence over `*`. Named routes that did not
+    /// report in the window are evaluated as having received no requests
+    ///
+    /// # Arguments
+    ///
+    /// * `summaries` - Aggregated route metrics for the window
+    ///
+    /// # Returns
+    ///
+    /// * `DriftRunResult` - Evaluated routes and any alerts raised
+    pub fn evaluate(&self, summaries: &[RouteSummary]) -> DriftRunResult {
+        let mut result = DriftRunResult::default();
+
+        let wildcard = self
+            .profile
+            .routes
+            .iter()
+            .find(|thresholds| thresholds.route == ALL_ROUTES);
+
+        let named = self
+            .profile
+            .routes
+            .iter()
+            .filter(|thresholds| thresholds.route != ALL_ROUTES);
+
+        for thresholds in named {
+            let summary = summaries
+                .iter()
+                .find(|summary| summary.route_name == thresholds.route)
+                .cloned()
+                .unwrap_or_else(|| RouteSummary {
+                    route_name: thresholds.route.clone(),
+                    ..Default::default()
+                });
+
+            result.features.push(summary.route_name.clone());
+            result
+                .alerts
+                .extend(Self::evaluate_route(&summary, thresholds));
+        }
+
+        if let Some(wildcard) = wildcard {
+            for summary in summaries.iter() {
+                if result.features.contains(&summary.route_name) {
+                    continue;
+                }
+
+                result.features.push(summary.route_name.clone());
+                result
+                    .alerts
+                    .extend(Self::evaluate_route(summary, wildcard));
+            }
+        }
+
+        result
+    }
+
+    /// Process a single observability alert task
+    ///
+    /// # Arguments
+    ///
+    /// * `db_client` - Postgres client to query route metrics with
+    /// * `options` - Window to evaluate and whether to dispatch alerts
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DriftRunResult>` - Evaluated routes and any alerts raised
+    pub async fn check_for_alerts(
+        &self,
+        db_client: &PostgresClient,
+        options: &RunOptions,
+    ) -> Result<DriftRunResult, anyhow::Error> {
+        info!(
+            "Processing observability task for profile: {}/{}/{}",
+            self.service_info.repository, self.service_info.name, self.service_info.version
+        );
+
+        let summaries = db_client
+            .get_route_summaries(&self.service_info, options.start, options.end)
+            .await?;
+
+        let mut result = self.evaluate(&summaries);
+        result.rows_read = summaries.iter().map(|summary| summary.request_count).sum();
+
+        if result.alerts.is_empty() {
+            info!(
+                "No alerts to process for {}/{}/{}",
+                self.service_info.repository, self.service_info.name, self.service_info.version
+            );
+        } else if options.dispatch {
+            // failures are logged by the dispatcher, the alerts are still persisted
+            let _ = dispatch_alerts(
+                &self.service_info,
+                &self.profile.dispatch.dispatch_type(),
+                &self.profile.dispatch.dispatch_kwargs(),
+                &result.alerts,
+            )
+            .await;
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn summary(route: &str, request_count: i64, error_count: i64, p95: f64) -> RouteSummary {
+        RouteSummary {
+            route_name: route.to_string(),
+            request_count,
+            error_count,
+            p95: Some(p95),
+            p99: Some(p95 * 2.0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let profile: ObservabilityAlertProfile = serde_json::from_value(json!({
+            "name": "test_app",
+            "repository": "test",
+            "version": "1.0.0",
+            "schedule": "0 0 * * * *",
+            "routes": [
+                {"route": "*", "p95_ceiling": 0.5, "error_rate_ceiling": 0.1},
+                {"route": "/predict", "p99_ceiling": 1.0, "request_count_floor": 100},
+                {"route": "/health", "request_count_floor": 1}
+            ]
+        }))
+        .unwrap();
+
+        let drifter = ObservabilityDrifter::new(profile);
+        let result = drifter.evaluate(&[
+            summary("/predict", 50, 0, 0.6),
+            summary("/train", 10, 5, 0.6),
+            summary("/docs", 10, 0, 0.1),
+        ]);
+
+        assert_eq!(
+            result.features,
+            vec!["/predict", "/health", "/train", "/docs"]
+        );
+
+        let kinds = result
+            .alerts
+            .iter()
+            .map(|alert| (alert["feature"].as_str(), alert["kind"].as_str()))
+            .collect::<Vec<_>>();
+
+        // /predict only uses its own thresholds, so its p95 is not checked against `*`
+        assert_eq!(
+            kinds,
+            vec![
+                ("/predict", P99_LATENCY_KIND),
+                ("/predict", REQUEST_COUNT_KIND),
+                ("/health", REQUEST_COUNT_KIND),
+                ("/train", P95_LATENCY_KIND),
+                ("/train", ERROR_RATE_KIND),
+            ]
+        );
+        assert_eq!(result.alerts[2]["value"], "0");
+        assert_eq!(result.alerts[4]["value"], "0.5000");
+        assert_eq!(result.clean_features(), vec!["/docs"]);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::types::TimeInterval;
+use anyhow::anyhow;
+use scouter::core::dispatch::types::AlertDispatchType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Value stored in scouter.drift_profile.drift_type for observability alert profiles
+pub const OBSERVABILITY_DRIFT_TYPE: &str = "OBSERVABILITY";
+
+/// Matches every route reported by the service
+pub const ALL_ROUTES: &str = "*";
+
+/// Where observability alerts are sent once they are persisted
+///
+/// Alerts go through the same dispatchers as drift alerts, kwargs are passed through
+/// as the dispatcher settings (e.g. slack channel, opsgenie team)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ObservabilityDispatch {
+    #[default]
+    Console,
+    Slack {
+        #[serde(default)]
+        kwargs: HashMap<String, String>,
+    },
+    OpsGenie {
+        #[serde(default)]
+        kwargs: HashMap<String, String>,
+    },
+}
+
+impl ObservabilityDispatch {
+    /// Dispatcher the alerts are sent with
+    pub fn dispatch_type(&self) -> AlertDispatchType {
+        match self {
+            ObservabilityDispatch::Console => AlertDispatchType::Console,
+            ObservabilityDispatch::Slack { .. } => AlertDispatchType::Slack,
+            ObservabilityDispatch::OpsGenie { .. } => AlertDispatchType::OpsGenie,
+        }
+    }
+
+    /// Settings passed to the dispatcher
+    pub fn dispatch_kwargs(&self) -> HashMap<String, String> {
+        match self {
+            ObservabilityDispatch::Console => HashMap::new(),
+            ObservabilityDispatch::Slack { kwargs }
+            | ObservabilityDispatch::OpsGenie { kwargs } => kwargs.clone(),
+        }
+    }
+}
+
+/// Thresholds evaluated for a single route
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RouteThresholds {
+    // route name as reported in the route metrics, or `*` for every route
+    pub route: String,
+    // seconds
+    pub p95_ceiling: Option<f64>,
+    pub p99_ceiling: Option<f64>,
+    // errors / requests, between 0 and 1
+    pub error_rate_ceiling: Option<f64>,
+    pub request_count_floor: Option<i64>,
+}
+
+/// Alert profile for the request metrics written to scouter.observability_metrics
+///
+/// Stored alongside drift profiles and scheduled by the same drift executor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ObservabilityAlertProfile {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    // cron schedule
+    pub schedule: String,
+    // time window evaluated on each run, e.g. 15minute. Defaults to everything since the previous run
+    pub window: Option<String>,
+    pub routes: Vec<RouteThresholds>,
+    #[serde(default)]
+    pub dispatch: ObservabilityDispatch,
+}
+
+impl ObservabilityAlertProfile {
+    /// Check that the profile can be scheduled and evaluated
+    pub fn validate(&self) -> Result<(), anyhow::Error> {
+        cron::Schedule::from_str(&self.schedule)
+            .map_err(|e| anyhow!("Invalid schedule {}: {}", self.schedule, e))?;
+
+        if let Some(window) = &self.window {
+            TimeInterval::from_str(window)?;
+        }
+
+        if self.routes.is_empty() {
+            return Err(anyhow!("At least one route must be configured"));
+        }
+
+        for route in self.routes.iter() {
+            let ceilings = [
+                route.p95_ceiling,
+                route.p99_ceiling,
+                route.error_rate_ceiling,
+            ];
+
+            if ceilings.iter().flatten().any(|ceiling| *ceiling < 0.0) {
+                return Err(anyhow!("Thresholds for {} must be positive", route.route));
+            }
+
+            if route
+                .error_rate_ceiling
+                .is_some_and(|ceiling| ceiling > 1.0)
+            {
+                return Err(anyhow!(
+                    "error_rate_ceiling for {} must be between 0 and 1",
+                    route.route
+                ));
+            }
+
+            if ceilings.iter().all(Option::is_none) && route.request_count_floor.is_none() {
+                return Err(anyhow!("No thresholds configured for {}", route.route));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Window length in minutes, if the profile uses a fixed window
+    pub fn window_minutes(&self) -> Option<i32> {
+        self.window
+            .as_deref()
+            .and_then(|window| TimeInterval::from_str(window).ok())
+            .map(|window| window.to_minutes())
+    }
+}
+
+impl FromStr for ObservabilityAlertProfile {
+    type Err = anyhow::Error;
+
+    fn from_str(profile: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(profile).map_err(|e| anyhow!("Invalid observability profile: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_profile_validation() {
+        let profile: ObservabilityAlertProfile = serde_json::from_value(json!({
+            "name": "test_app",
+            "repository": "test",
+            "version": "1.0.0",
+            "schedule": "0 0 * * * *",
+            "routes": [{"route": "*", "p95_ceiling": 0.5}]
+        }))
+        .unwrap();
+
+        assert!(profile.validate().is_ok());
+        assert_eq!(profile.dispatch, ObservabilityDispatch::Console);
+        assert_eq!(profile.window_minutes(), None);
+
+        let mut invalid = profile.clone();
+        invalid.schedule = "hourly".to_string();
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = profile.clone();
+        invalid.window = Some("10minute".to_string());
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = profile.clone();
+        invalid.routes[0].p95_ceiling = None;
+        assert!(invalid.validate().is_err());
+
+        let mut invalid = profile.clone();
+        invalid.routes[0].error_rate_ceiling = Some(2.0);
+        assert!(invalid.validate().is_err());
+
+        let dispatch: ObservabilityDispatch =
+            serde_json::from_value(json!({"type": "slack", "kwargs": {"channel": "alerts"}}))
+                .unwrap();
+        assert_eq!(dispatch.dispatch_type(), AlertDispatchType::Slack);
+        assert_eq!(dispatch.dispatch_kwargs()["channel"], "alerts");
+
+        let dispatch: ObservabilityDispatch =
+            serde_json::from_value(json!({"type": "opsgenie"})).unwrap();
+        assert_eq!(dispatch.dispatch_type(), AlertDispatchType::OpsGenie);
+        assert!(dispatch.dispatch_kwargs().is_empty());
+
+        // arbitrary urls are not accepted as a destination
+        assert!(serde_json::from_value::<ObservabilityDispatch>(
+            json!({"type": "webhook", "url": "http://localhost/alerts"})
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use crate::alerts::dispatch::dispatch_alerts;
+use crate::alerts::psi::types::PsiDriftProfile;
+use crate::alerts::types::{DriftRunResult, RunOptions};
+use crate::alerts::volume::{check_feature_volume, VolumeConfig, VolumeDispatch, VolumeSource};
+use crate::api::schema::ServiceInfo;
+use crate::sql::postgres::PostgresClient;
+use crate::sql::schema::FeatureBinCount;
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use std::collections::BTreeMap;
+use tracing::error;
+use tracing::info;
+
+// Smoothing value used for empty bins so the log term stays finite
+const PSI_EPSILON: f64 = 0.0001;
+
+// Defines the PsiDrifter struct
+// This is used to process drift alerts for psi style profiles
+pub struct PsiDrifter {
+    service_info: ServiceInfo,
+    profile: PsiDriftProfile,
+}
+
+impl PsiDrifter {
+    pub fn new(profile: PsiDriftProfile) -> Self {
+        Self {
+            service_info: ServiceInfo {
+                name: profile.config.name.clone(),
+                repository: profile.config.repository.clone(),
+                version: profile.config.version.clone(),
+            },
+            profile,
+        }
+    }
+
+    /// Service the drifter evaluates
+    pub fn service_info(&self) -> &ServiceInfo {
+        &self.service_info
+    }
+
+    /// Check the record volume of the monitored features since the previous run
+    ///
+    /// # Arguments
+    ///
+    /// * `db_client` - Postgres client to count records with
+    /// * `previous_run` - Previous run timestamp
+    /// * `config` - Volume alert settings
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<BTreeMap<String, String>>` - Missing data and low volume alerts
+    pub async fn check_feature_volume(
+        &self,
+        db_client: &PostgresClient,
+        previous_run: NaiveDateTime,
+        config: &VolumeConfig,
+    ) -> Vec<BTreeMap<String, String>> {
+        check_feature_volume(
+            db_client,
+            VolumeSource::Psi,
+            &self.service_info,
+            &self.profile.config.alert_config.features_to_monitor,
+            previous_run,
+            config,
+            VolumeDispatch {
+                dispatch_type: &self.profile.config.alert_config.dispatch_type,
+                dispatch_kwargs: &self.profile.config.alert_config.dispatch_kwargs,
+            },
+        )
+        .await
+    }
+
+    /// Compute the population stability index for a single feature
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - Baseline (bin id, proportion) pairs from the drift profile
+    /// * `observed` - Observed proportions keyed by bin id
+    ///
+    /// # Returns
+    ///
+    /// * `f64` - PSI value
+    pub fn compute_psi(expected: &[(usize, f64)], observed: &BTreeMap<usize, f64>) -> f64 {
+        expected
+            .iter()
+            .map(|(bin_id, expected)| {
+                let expected = expected.max(PSI_EPSILON);
+                let observed = observed
+                    .get(bin_id)
+                    .copied()
+                    .unwrap_or(0.0)
+                    .max(PSI_EPSILON);
+
+                (observed - expected) * (observed / expected).ln()
+            })
+            .sum()
+    }
+
+    /// Get observed bin counts for each monitored feature
+    ///
+    /// # Arguments
+    ///
+    /// * `limit_datetime` - Limit timestamp for drift computation (this is the previous_run timestamp)
+    /// * `end_datetime` - Upper bound on the bin counts, everything up to now if None
+    /// * `db_client` - Postgres client to use for querying bin counts
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<FeatureBinCount>>` - Observed count of each feature bin
+    async fn get_observed_bin_counts(
+        &self,
+        limit_datetime: &NaiveDateTime,
+        end_datetime: Option<NaiveDateTime>,
+        db_client: &PostgresClient,
+    ) -> Result<Vec<FeatureBinCount>> {
+        db_client
+            .get_observed_bin_counts(
+                &self.service_info,
+                limit_datetime,
+                end_datetime,
+                &self.profile.config.alert_config.features_to_monitor,
+            )
+            .await
+            .with_context(|| "error retrieving observed bin counts to compute drift")
+    }
+
+    /// Convert observed bin counts into bin proportions for each feature
+    ///
+    /// # Arguments
+    ///
+    /// * `bin_counts` - Observed count of each feature bin
+    ///
+    /// # Returns
+    ///
+    /// * `BTreeMap<String, BTreeMap<usize, f64>>` - Observed proportions by feature and bin
+    fn observed_proportions(
+        bin_counts: Vec<FeatureBinCount>,
+    ) -> BTreeMap<String, BTreeMap<usize, f64>> {
+        let mut feature_counts: BTreeMap<String, BTreeMap<usize, i64>> = BTreeMap::new();
+        for bin_count in bin_counts {
+            feature_counts
+                .entry(bin_count.feature)
+                .or_default()
+                .insert(bin_count.bin_id as usize, bin_count.bin_count);
+        }
+
+        feature_counts
+            .into_iter()
+            .filter_map(|(feature, counts)| {
+                let total: i64 = counts.values().sum();
+                if total == 0 {
+                    return None;
+                }
+
+                let proportions = counts
+                    .into_iter()
+                    .map(|(bin_id, count)| (bin_id, count as f64 / total as f64))
+                    .collect::<BTreeMap<usize, f64>>();
+
+                Some((feature, proportions))
+            })
+            .collect()
+    }
+
+    /// Compute psi drift from bin counts that have already been retrieved
+    ///
+    /// # Arguments
+    ///
+    /// * `bin_counts` - Observed count of each feature bin
+    ///
+    /// # Returns
+    ///
+    /// * `BTreeMap<String, f64>` - PSI value for each feature with observed data
+    pub fn compute_drift_from_bin_counts(
+        &self,
+        bin_counts: Vec<FeatureBinCount>,
+    ) -> BTreeMap<String, f64> {
+        let observed = Self::observed_proportions(bin_counts);
+
+        observed
+            .iter()
+            .filter_map(|(feature, observed)| {
+                // features without a baseline in the profile can't be compared
+                let feature_profile = self.profile.features.get(feature)?;
+                let expected = feature_profile
+                    .bins
+                    .iter()
+                    .map(|bin| (bin.id, bin.proportion))
+                    .collect::<Vec<_>>();
+
+                Some((feature.clone(), Self::compute_psi(&expected, observed)))
+            })
+            .collect()
+    }
+
+    /// Generate alerts for features whose psi exceeds the configured threshold
+    ///
+    /// # Arguments
+    ///
+    /// * `drift` - PSI value for each feature
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<BTreeMap<String, String>>` - One alert per drifting feature
+    pub fn generate_alerts(&self, drift: &BTreeMap<String, f64>) -> Vec<BTreeMap<String, String>> {
+        let threshold = self.profile.config.alert_config.psi_threshold;
+
+        drift
+            .iter()
+            .filter(|(_, psi)| **psi > threshold)
+            .map(|(feature, psi)| {
+                let mut alert_map = BTreeMap::new();
+                alert_map.insert("feature".to_string(), feature.clone());
+                alert_map.insert("kind".to_string(), "PSI Threshold".to_string());
+                alert_map.insert("psi".to_string(), format!("{:.4}", psi));
+                alert_map.insert("threshold".to_string(), threshold.to_string());
+                alert_map
+            })
+            .collect()
+    }
+
+    /// Process a single psi drift computation task
+    ///
+    /// # Arguments
+    /// * `options` - Window to evaluate and whether to dispatch alerts
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DriftRunResult>` - Evaluated features and any alerts raised
+    pub async fn check_for_alerts(
+        &self,
+        db_client: &PostgresClient,
+        options: &RunOptions,
+    ) -> Result<DriftRunResult, anyhow::Error> {
+        info!(
+            "Processing psi drift task for profile: {}/{}/{}",
+            self.service_info.repository, self.service_info.name, self.service_info.version
+        );
+
+        let bin_counts = self
+            .get_observed_bin_counts(&options.start, Some(options.end), db_client)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Error computing psi drift for {}/{}/{}: {:?}",
+                    self.service_info.repository,
+                    self.service_info.name,
+                    self.service_info.version,
+                    e
+                );
+                anyhow::anyhow!("Error computing psi drift")
+            })?;
+
+        let rows_read = bin_counts.iter().map(|bin_count| bin_count.bin_count).sum();
+        let drift = self.compute_drift_from_bin_counts(bin_counts);
+
+        if drift.is_empty() {
+            info!("No features to process returning early");
+            return Ok(DriftRunResult {
+                rows_read,
+                ..Default::default()
+            });
+        }
+
+        let alerts = self.generate_alerts(&drift);
+
+        if alerts.is_empty() {
+            info!(
+                "No alerts to process for {}/{}/{}",
+                self.service_info.repository, self.service_info.name, self.service_info.version
+            );
+        } else if options.dispatch {
+            let alert_config = &self.profile.config.alert_config;
+            dispatch_alerts(
+                &self.service_info,
+                &alert_config.dispatch_type,
+                &alert_config.dispatch_kwargs,
+                &alerts,
+            )
+            .await
+            .map_err(|e| {
+                error!(
+                    "Error generating alerts for {}/{}/{}: {}",
+                    self.service_info.repository,
+                    self.service_info.name,
+                    self.service_info.version,
+                    e
+                );
+                anyhow::anyhow!("Error generating alerts")
+            })?;
+        }
+
+        Ok(DriftRunResult {
+            features: drift.keys().cloned().collect(),
+            drift: drift
+                .iter()
+                .map(|(feature, psi)| (feature.clone(), vec![*psi]))
+                .collect(),
+            alerts,
+            rows_read,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::psi::types::DEFAULT_PSI_THRESHOLD;
+
+    #[test]
+    fn test_compute_psi() {
+        let expected = vec![(0, 0.25), (1, 0.25), (2, 0.25), (3, 0.25)];
+
+        // identical distributions have no drift
+        let observed: BTreeMap<usize, f64> = expected.iter().cloned().collect();
+        assert!(PsiDrifter::compute_psi(&expected, &observed).abs() < 1e-12);
+
+        // all mass shifted into a single bin is a large drift
+        let observed = BTreeMap::from([(0, 1.0)]);
+        assert!(PsiDrifter::compute_psi(&expected, &observed) > DEFAULT_PSI_THRESHOLD);
+
+        // small shift stays under the threshold
+        let observed = BTreeMap::from([(0, 0.27), (1, 0.23), (2, 0.25), (3, 0.25)]);
+        let psi = PsiDrifter::compute_psi(&expected, &observed);
+        assert!(psi > 0.0 && psi < DEFAULT_PSI_THRESHOLD);
+    }
+}
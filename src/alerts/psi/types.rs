@@ -0,0 +1,167 @@
+use anyhow::anyhow;
+use chrono::NaiveDateTime;
+use scouter::core::dispatch::types::AlertDispatchType;
+use scouter::core::drift::base::{DriftType, ProfileArgs, ProfileBaseArgs, RecordType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// PSI above 0.25 is commonly treated as a significant population shift
+pub const DEFAULT_PSI_THRESHOLD: f64 = 0.25;
+
+fn default_psi_threshold() -> f64 {
+    DEFAULT_PSI_THRESHOLD
+}
+
+/// Observed count of a single feature bin as sent by clients
+///
+/// Wire format of the `PSI` server record, the pinned scouter release only ships spc records
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PsiServerRecord {
+    pub created_at: NaiveDateTime,
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    pub feature: String,
+    pub bin_id: usize,
+    pub bin_count: usize,
+    pub record_type: RecordType,
+}
+
+/// Baseline bin of a feature
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bin {
+    pub id: usize,
+    pub lower_limit: Option<f64>,
+    pub upper_limit: Option<f64>,
+    // share of the baseline records that fell into the bin
+    pub proportion: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PsiFeatureDriftProfile {
+    pub id: String,
+    pub bins: Vec<Bin>,
+    pub timestamp: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PsiAlertConfig {
+    pub dispatch_type: AlertDispatchType,
+    // cron schedule
+    pub schedule: String,
+    pub features_to_monitor: Vec<String>,
+    pub dispatch_kwargs: HashMap<String, String>,
+    // psi value above which a feature raises an alert
+    #[serde(default = "default_psi_threshold")]
+    pub psi_threshold: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PsiDriftConfig {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    pub feature_map: Option<serde_json::Value>,
+    pub targets: Vec<String>,
+    pub alert_config: PsiAlertConfig,
+    pub drift_type: DriftType,
+}
+
+/// Psi drift profile, baseline bins for each feature
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PsiDriftProfile {
+    pub features: HashMap<String, PsiFeatureDriftProfile>,
+    pub config: PsiDriftConfig,
+    pub scouter_version: String,
+}
+
+impl PsiDriftProfile {
+    /// Check the alert settings before the profile is stored
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let threshold = self.config.alert_config.psi_threshold;
+        if !threshold.is_finite() || threshold <= 0.0 {
+            return Err(anyhow!(
+                "psi_threshold must be a positive number, got {}",
+                threshold
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl ProfileBaseArgs for PsiDriftProfile {
+    fn get_base_args(&self) -> ProfileArgs {
+        ProfileArgs {
+            name: self.config.name.clone(),
+            repository: self.config.repository.clone(),
+            version: self.config.version.clone(),
+            schedule: self.config.alert_config.schedule.clone(),
+            scouter_version: self.scouter_version.clone(),
+            drift_type: self.config.drift_type.clone(),
+        }
+    }
+
+    fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::types::DriftProfile;
+    use serde_json::json;
+
+    fn profile(alert_config: serde_json::Value) -> serde_json::Value {
+        json!({
+            "features": {},
+            "config": {
+                "name": "test_app",
+                "repository": "test",
+                "version": "1.0.0",
+                "feature_map": null,
+                "targets": [],
+                "alert_config": alert_config,
+                "drift_type": "PSI"
+            },
+            "scouter_version": "0.3.2"
+        })
+    }
+
+    #[test]
+    fn test_psi_threshold() {
+        let mut alert_config = json!({
+            "dispatch_type": "Console",
+            "schedule": "0 0 0 * * *",
+            "features_to_monitor": [],
+            "dispatch_kwargs": {}
+        });
+
+        let DriftProfile::PsiDriftProfile(loaded) =
+            DriftProfile::from_value(profile(alert_config.clone()), &DriftType::PSI).unwrap()
+        else {
+            panic!("expected a psi profile");
+        };
+        assert_eq!(
+            loaded.config.alert_config.psi_threshold,
+            DEFAULT_PSI_THRESHOLD
+        );
+
+        alert_config["psi_threshold"] = json!(0.1);
+        let DriftProfile::PsiDriftProfile(loaded) =
+            DriftProfile::from_value(profile(alert_config.clone()), &DriftType::PSI).unwrap()
+        else {
+            panic!("expected a psi profile");
+        };
+        assert_eq!(loaded.config.alert_config.psi_threshold, 0.1);
+
+        // invalid thresholds are rejected when the profile is loaded
+        for threshold in [json!(0.0), json!(-0.2), json!("0.1")] {
+            alert_config["psi_threshold"] = threshold;
+            assert!(
+                DriftProfile::from_value(profile(alert_config.clone()), &DriftType::PSI).is_err()
+            );
+        }
+    }
+}
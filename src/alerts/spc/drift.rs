@@ -12,7 +12,8 @@ use std::collections::BTreeMap;
 use tracing::error;
 use tracing::info;
 
-use crate::alerts::types::TaskAlerts;
+use crate::alerts::types::{DriftRunResult, RunOptions, TaskAlerts};
+use crate::alerts::volume::{check_feature_volume, VolumeConfig, VolumeDispatch, VolumeSource};
 use ndarray::Array2;
 
 // Defines the SpcDrifter struct
@@ -34,12 +35,58 @@ impl SpcDrifter {
         }
     }
 
+    /// Service the drifter evaluates
+    pub fn service_info(&self) -> &ServiceInfo {
+        &self.service_info
+    }
+
+    /// Number of features evaluated by each run, each is queried separately
+    pub fn monitored_feature_count(&self) -> usize {
+        match self.profile.config.alert_config.features_to_monitor.len() {
+            0 => self.profile.features.len(),
+            count => count,
+        }
+    }
+
+    /// Check the record volume of the monitored features since the previous run
+    ///
+    /// # Arguments
+    ///
+    /// * `db_client` - Postgres client to count records with
+    /// * `previous_run` - Previous run timestamp
+    /// * `config` - Volume alert settings
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<BTreeMap<String, String>>` - Missing data and low volume alerts
+    pub async fn check_feature_volume(
+        &self,
+        db_client: &PostgresClient,
+        previous_run: NaiveDateTime,
+        config: &VolumeConfig,
+    ) -> Vec<BTreeMap<String, String>> {
+        check_feature_volume(
+            db_client,
+            VolumeSource::Spc,
+            &self.service_info,
+            &self.profile.config.alert_config.features_to_monitor,
+            previous_run,
+            config,
+            VolumeDispatch {
+                dispatch_type: &self.profile.config.alert_config.dispatch_type,
+                dispatch_kwargs: &self.profile.config.alert_config.dispatch_kwargs,
+            },
+        )
+        .await
+    }
+
     /// Get drift features for a given drift profile
     ///
     /// # Arguments
     ///
     /// * `db_client` - Postgres client to use for querying feature data
     /// * `limit_timestamp` - Limit timestamp for drift computation (this is the previous_run timestamp)
+    /// * `end_timestamp` - Upper bound on the feature data, everything up to now if None
     /// * `features_to_monitor` - Features to monitor for drift
     ///
     /// # Returns
@@ -49,70 +96,66 @@ impl SpcDrifter {
         &self,
         db_client: &PostgresClient,
         limit_timestamp: &str,
+        end_timestamp: Option<NaiveDateTime>,
         features_to_monitor: &[String],
     ) -> Result<QueryResult> {
         let records = db_client
-            .get_drift_records(&self.service_info, limit_timestamp, features_to_monitor)
+            .get_drift_records(
+                &self.service_info,
+                limit_timestamp,
+                end_timestamp,
+                features_to_monitor,
+            )
             .await?;
         Ok(records)
     }
 
-    /// Compute drift for a given drift profile
+    /// Compute drift from feature values that have already been retrieved
     ///
     /// # Arguments
     ///
-    /// * `limit_timestamp` - Limit timestamp for drift computation (this is the previous_run timestamp)
-    /// * `db_client` - Postgres client to use for querying feature data
-    ///     
+    /// * `drift_features` - Feature values to compute drift for
+    ///
     /// # Returns
     ///
-    /// * `Result<Array2<f64>>` - Drift array
-    pub async fn compute_drift(
+    /// * `Result<(Array2<f64>, Vec<String>)>` - Drift array and the feature for each column
+    pub fn compute_drift_from_records(
         &self,
-        limit_timestamp: &NaiveDateTime,
-        db_client: &PostgresClient,
+        drift_features: &QueryResult,
     ) -> Result<(Array2<f64>, Vec<String>)> {
-        let drift_features = self
-            .get_drift_features(
-                db_client,
-                &limit_timestamp.to_string(),
-                &self.profile.config.alert_config.features_to_monitor,
-            )
-            .await
-            .with_context(|| "error retrieving raw feature data to compute drift")?;
+        let sample = drift_features
+            .features
+            .iter()
+            .map(|(feature, result)| (feature.clone(), result.values.clone()))
+            .collect::<BTreeMap<String, Vec<f64>>>();
 
-        let feature_keys: Vec<String> = drift_features.features.keys().cloned().collect();
+        self.compute_drift_from_sample(&sample)
+    }
 
-        let feature_values = drift_features
-            .features
-            .values()
-            .cloned()
-            .flat_map(|feature| feature.values.clone())
-            .collect::<Vec<_>>();
+    /// Compute drift from the values of each feature
+    ///
+    /// # Arguments
+    ///
+    /// * `sample` - Values of each feature, every feature must have the same number of values
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(Array2<f64>, Vec<String>)>` - Drift array and the feature for each column
+    pub fn compute_drift_from_sample(
+        &self,
+        sample: &BTreeMap<String, Vec<f64>>,
+    ) -> Result<(Array2<f64>, Vec<String>)> {
+        let feature_keys: Vec<String> = sample.keys().cloned().collect();
 
         // assert all drift features have the same number of values
+        let num_rows = sample.len();
+        let num_cols = sample.values().next().map_or(0, |values| values.len());
 
-        let all_same_len = drift_features.features.iter().all(|(_, feature)| {
-            feature.values.len()
-                == drift_features
-                    .features
-                    .values()
-                    .next()
-                    .unwrap()
-                    .values
-                    .len()
-        });
-
-        if !all_same_len {
+        if sample.values().any(|values| values.len() != num_cols) {
             return Err(anyhow::anyhow!("Feature values have different lengths"));
         }
 
-        let num_rows = drift_features.features.len();
-        let num_cols = if num_rows > 0 {
-            feature_values.len() / num_rows
-        } else {
-            0
-        };
+        let feature_values = sample.values().flatten().copied().collect::<Vec<_>>();
 
         let nd_feature_arr = Array2::from_shape_vec((num_rows, num_cols), feature_values)
             .with_context(|| "Shape error")?;
@@ -126,7 +169,7 @@ impl SpcDrifter {
         Ok((drift, feature_keys))
     }
 
-    /// Generate alerts for a given drift profile
+    /// Evaluate the profile's alert rule against a drift array without dispatching any alerts
     ///
     /// # Arguments
     ///
@@ -135,19 +178,47 @@ impl SpcDrifter {
     ///
     /// # Returns
     ///
-    /// * `Result<Option<TaskAlerts>>` - Task alerts
-    pub async fn generate_alerts<'a>(
+    /// * `Result<Option<TaskAlerts>>` - Task alerts, None if no rule was violated
+    pub fn evaluate_alerts(
         &self,
-        array: &ArrayView2<'a, f64>,
+        array: &ArrayView2<f64>,
         features: &[String],
     ) -> Result<Option<TaskAlerts>, anyhow::Error> {
-        let mut task_alerts = TaskAlerts::default();
-        // Get alerts
         // keys are the feature names that match the order of the drift array columns
         let alert_rule = self.profile.config.alert_config.rule.clone();
         let alerts = generate_alerts(array, features, &alert_rule)
             .with_context(|| "error generating drift alerts")?;
 
+        if !alerts.has_alerts {
+            info!(
+                "No alerts to process for {}/{}/{}",
+                self.service_info.repository, self.service_info.name, self.service_info.version
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(TaskAlerts { alerts }))
+    }
+
+    /// Generate alerts for a given drift profile and send them through the profile's dispatcher
+    ///
+    /// # Arguments
+    ///
+    /// * `array` - Drift array
+    /// * `features` - Features to monitor for drift
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<TaskAlerts>>` - Task alerts
+    pub async fn generate_alerts<'a>(
+        &self,
+        array: &ArrayView2<'a, f64>,
+        features: &[String],
+    ) -> Result<Option<TaskAlerts>, anyhow::Error> {
+        let Some(task_alerts) = self.evaluate_alerts(array, features)? else {
+            return Ok(None);
+        };
+
         // Get dispatcher, will default to console if env vars are not found for 3rd party service
         // TODO: Add ability to pass hashmap of kwargs to dispatcher (from drift profile)
         // This would be for things like opsgenie team, feature priority, slack channel, etc.
@@ -159,30 +230,21 @@ impl SpcDrifter {
             anyhow::anyhow!("Error creating alert dispatcher")
         })?;
 
-        if alerts.has_alerts {
-            alert_dispatcher
-                .process_alerts(&alerts)
-                .await
-                .map_err(|e| {
-                    error!(
-                        "Error processing alerts for {}/{}/{}: {}",
-                        self.service_info.repository,
-                        self.service_info.name,
-                        self.service_info.version,
-                        e
-                    );
-                    anyhow::anyhow!("Error processing alerts")
-                })?;
-            task_alerts.alerts = alerts;
-            return Ok(Some(task_alerts));
-        } else {
-            info!(
-                "No alerts to process for {}/{}/{}",
-                self.service_info.repository, self.service_info.name, self.service_info.version
-            );
-        }
+        alert_dispatcher
+            .process_alerts(&task_alerts.alerts)
+            .await
+            .map_err(|e| {
+                error!(
+                    "Error processing alerts for {}/{}/{}: {}",
+                    self.service_info.repository,
+                    self.service_info.name,
+                    self.service_info.version,
+                    e
+                );
+                anyhow::anyhow!("Error processing alerts")
+            })?;
 
-        Ok(None)
+        Ok(Some(task_alerts))
     }
 
     /// organize alerts so that each alert is mapped to a single entry and feature
@@ -212,53 +274,135 @@ impl SpcDrifter {
         tasks
     }
 
+    /// Split a drift array into the drift of each feature
+    ///
+    /// Each column of the drift array holds the drift of a single feature
+    fn drift_by_feature(drift_array: &Array2<f64>, keys: &[String]) -> BTreeMap<String, Vec<f64>> {
+        keys.iter()
+            .enumerate()
+            .map(|(idx, feature)| (feature.clone(), drift_array.column(idx).to_vec()))
+            .collect()
+    }
+
+    /// Check a sample of feature values against the profile in memory
+    ///
+    /// Nothing is read from or written to the database and alerts are not dispatched
+    ///
+    /// # Arguments
+    ///
+    /// * `sample` - Values of each feature, every feature must have the same number of values
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DriftRunResult>` - Drift of each feature and any rule violations
+    pub fn check_sample(
+        &self,
+        sample: &BTreeMap<String, Vec<f64>>,
+    ) -> Result<DriftRunResult, anyhow::Error> {
+        if sample.is_empty() || sample.values().any(|values| values.is_empty()) {
+            return Err(anyhow::anyhow!(
+                "Sample must contain values for at least one feature"
+            ));
+        }
+
+        if let Some(feature) = sample
+            .keys()
+            .find(|feature| !self.profile.features.contains_key(*feature))
+        {
+            return Err(anyhow::anyhow!(
+                "Feature {} is not part of the drift profile",
+                feature
+            ));
+        }
+
+        let (drift_array, keys) = self.compute_drift_from_sample(sample)?;
+
+        let alerts = match self.evaluate_alerts(&drift_array.view(), &keys)? {
+            Some(alerts) => self.organize_alerts(alerts),
+            None => Vec::new(),
+        };
+
+        Ok(DriftRunResult {
+            drift: Self::drift_by_feature(&drift_array, &keys),
+            features: keys,
+            alerts,
+            rows_read: sample.values().map(|values| values.len() as i64).sum(),
+        })
+    }
+
     /// Process a single drift computation task
     ///
     /// # Arguments
-    /// * `previous_run` - Previous run timestamp
+    /// * `options` - Window to evaluate and whether to dispatch alerts
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DriftRunResult>` - Evaluated features and any alerts raised
     pub async fn check_for_alerts(
         &self,
         db_client: &PostgresClient,
-        previous_run: NaiveDateTime,
-    ) -> Result<Option<Vec<BTreeMap<String, String>>>, anyhow::Error> {
+        options: &RunOptions,
+    ) -> Result<DriftRunResult, anyhow::Error> {
         info!(
             "Processing drift task for profile: {}/{}/{}",
             self.service_info.repository, self.service_info.name, self.service_info.version
         );
 
+        let drift_features = self
+            .get_drift_features(
+                db_client,
+                &options.start.to_string(),
+                Some(options.end),
+                &self.profile.config.alert_config.features_to_monitor,
+            )
+            .await
+            .with_context(|| "error retrieving raw feature data to compute drift")?;
+
+        let rows_read = drift_features
+            .features
+            .values()
+            .map(|feature| feature.values.len() as i64)
+            .sum();
+
         // Compute drift
         let (drift_array, keys) = self
-            .compute_drift(&previous_run, db_client)
-            .await
+            .compute_drift_from_records(&drift_features)
             .with_context(|| "error computing drift")?;
 
         // if drift array is empty, return early
         if drift_array.is_empty() {
             info!("No features to process returning early");
-            return Ok(None);
+            return Ok(DriftRunResult {
+                rows_read,
+                ..Default::default()
+            });
         }
 
         // Generate alerts (if any)
-        let alerts = self
-            .generate_alerts(&drift_array.view(), &keys)
-            .await
-            .map_err(|e| {
-                error!(
-                    "Error generating alerts for {}/{}/{}: {}",
-                    self.service_info.repository,
-                    self.service_info.name,
-                    self.service_info.version,
-                    e
-                );
-                anyhow::anyhow!("Error generating alerts")
-            })?;
+        let alerts = if options.dispatch {
+            self.generate_alerts(&drift_array.view(), &keys).await
+        } else {
+            self.evaluate_alerts(&drift_array.view(), &keys)
+        };
 
-        match alerts {
-            Some(alerts) => {
-                let organized_alerts = self.organize_alerts(alerts);
-                Ok(Some(organized_alerts))
-            }
-            None => Ok(None),
-        }
+        let alerts = alerts.map_err(|e| {
+            error!(
+                "Error generating alerts for {}/{}/{}: {}",
+                self.service_info.repository, self.service_info.name, self.service_info.version, e
+            );
+            anyhow::anyhow!("Error generating alerts")
+        })?;
+
+        let alerts = match alerts {
+            Some(alerts) => self.organize_alerts(alerts),
+            None => Vec::new(),
+        };
+
+        Ok(DriftRunResult {
+            drift: Self::drift_by_feature(&drift_array, &keys),
+            features: keys,
+            alerts,
+            rows_read,
+        })
     }
 }
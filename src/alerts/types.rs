@@ -1,7 +1,15 @@
+use crate::alerts::observability::drift::ObservabilityDrifter;
+use crate::alerts::psi::drift::PsiDrifter;
+use crate::alerts::psi::types::PsiDriftProfile;
 use crate::alerts::spc::drift::SpcDrifter;
+use crate::alerts::volume::VolumeConfig;
+use crate::api::schema::ServiceInfo;
 use crate::sql::postgres::PostgresClient;
+use anyhow::Context;
 use chrono::NaiveDateTime;
-use scouter::core::drift::spc::types::SpcFeatureAlerts;
+use scouter::core::drift::base::{DriftType, ProfileArgs, ProfileBaseArgs};
+use scouter::core::drift::spc::types::{SpcDriftProfile, SpcFeatureAlerts};
+use serde::Serialize;
 use std::collections::BTreeMap;
 pub struct TaskAlerts {
     pub alerts: SpcFeatureAlerts,
@@ -21,18 +29,202 @@ impl Default for TaskAlerts {
     }
 }
 
+/// Time window and side effects of a single drift run
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    // records created after this timestamp are evaluated
+    pub start: NaiveDateTime,
+    // records created at or before this timestamp are evaluated
+    pub end: NaiveDateTime,
+    // send alerts through the profile's dispatcher
+    pub dispatch: bool,
+}
+
+impl RunOptions {
+    pub fn new(start: NaiveDateTime, end: NaiveDateTime) -> Self {
+        Self {
+            start,
+            end,
+            dispatch: true,
+        }
+    }
+}
+
+/// Outcome of a single drift run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DriftRunResult {
+    // features that had data in the run window and were evaluated
+    pub features: Vec<String>,
+    // drift computed for each evaluated feature (spc: one value per sample, psi: a single value)
+    pub drift: BTreeMap<String, Vec<f64>>,
+    // one entry per alert raised during the run
+    pub alerts: Vec<BTreeMap<String, String>>,
+    // records evaluated by the run
+    pub rows_read: i64,
+}
+
+impl DriftRunResult {
+    /// Evaluated features that did not raise any alert
+    pub fn clean_features(&self) -> Vec<String> {
+        self.features
+            .iter()
+            .filter(|feature| {
+                !self
+                    .alerts
+                    .iter()
+                    .any(|alert| alert.get("feature") == Some(*feature))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Drift profile stored in scouter.drift_profile
+///
+/// Spc profiles come from scouter, psi profiles are defined in this crate
+#[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
+pub enum DriftProfile {
+    SpcDriftProfile(SpcDriftProfile),
+    PsiDriftProfile(PsiDriftProfile),
+}
+
+impl DriftProfile {
+    /// Load a stored profile for its drift type
+    ///
+    /// # Arguments
+    ///
+    /// * `drift_type` - Drift type the profile was stored with
+    /// * `profile` - Profile json
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DriftProfile>` - Profile for the drift type
+    pub fn from_str(drift_type: DriftType, profile: &str) -> Result<Self, anyhow::Error> {
+        Self::from_value(serde_json::from_str(profile)?, &drift_type)
+    }
+
+    /// Load a profile sent to the api for its drift type
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - Profile json
+    /// * `drift_type` - Drift type of the profile
+    ///
+    /// # Returns
+    ///
+    /// * `Result<DriftProfile>` - Profile for the drift type
+    pub fn from_value(
+        body: serde_json::Value,
+        drift_type: &DriftType,
+    ) -> Result<Self, anyhow::Error> {
+        match drift_type {
+            DriftType::SPC => Ok(DriftProfile::SpcDriftProfile(
+                serde_json::from_value(body).context("Invalid spc drift profile")?,
+            )),
+            DriftType::PSI => {
+                let profile: PsiDriftProfile =
+                    serde_json::from_value(body).context("Invalid psi drift profile")?;
+                profile.validate()?;
+                Ok(DriftProfile::PsiDriftProfile(profile))
+            }
+        }
+    }
+
+    /// Name, repository, version and schedule of the profile
+    pub fn get_base_args(&self) -> ProfileArgs {
+        match self {
+            DriftProfile::SpcDriftProfile(profile) => profile.get_base_args(),
+            DriftProfile::PsiDriftProfile(profile) => profile.get_base_args(),
+        }
+    }
+
+    pub fn to_value(&self) -> serde_json::Value {
+        match self {
+            DriftProfile::SpcDriftProfile(profile) => profile.to_value(),
+            DriftProfile::PsiDriftProfile(profile) => profile.to_value(),
+        }
+    }
+}
+
+#[allow(clippy::enum_variant_names)]
 pub enum Drifter {
     SpcDrifter(SpcDrifter),
+    PsiDrifter(PsiDrifter),
+    ObservabilityDrifter(ObservabilityDrifter),
 }
 
 impl Drifter {
     pub async fn check_for_alerts(
+        &self,
+        db_client: &PostgresClient,
+        options: &RunOptions,
+    ) -> Result<DriftRunResult, anyhow::Error> {
+        match self {
+            Drifter::SpcDrifter(drifter) => drifter.check_for_alerts(db_client, options).await,
+            Drifter::PsiDrifter(drifter) => drifter.check_for_alerts(db_client, options).await,
+            Drifter::ObservabilityDrifter(drifter) => {
+                drifter.check_for_alerts(db_client, options).await
+            }
+        }
+    }
+
+    /// Service the drifter evaluates
+    pub fn service_info(&self) -> &ServiceInfo {
+        match self {
+            Drifter::SpcDrifter(drifter) => drifter.service_info(),
+            Drifter::PsiDrifter(drifter) => drifter.service_info(),
+            Drifter::ObservabilityDrifter(drifter) => drifter.service_info(),
+        }
+    }
+
+    /// Number of queries a single run issues against the records table
+    ///
+    /// Spc drifters read each monitored feature separately, the others read every feature at once
+    pub fn queries_per_run(&self) -> usize {
+        match self {
+            Drifter::SpcDrifter(drifter) => drifter.monitored_feature_count().max(1),
+            _ => 1,
+        }
+    }
+
+    /// Start of the window a scheduled run evaluates
+    ///
+    /// Observability profiles may evaluate a fixed window instead of everything since the previous run
+    pub fn window_start(
+        &self,
+        previous_run: NaiveDateTime,
+        window_end: NaiveDateTime,
+    ) -> NaiveDateTime {
+        match self {
+            Drifter::ObservabilityDrifter(drifter) => {
+                drifter.window_start(previous_run, window_end)
+            }
+            _ => previous_run,
+        }
+    }
+
+    /// Missing data and low volume alerts for the drifter's monitored features
+    ///
+    /// Observability profiles alert on request counts directly and have no volume check
+    pub async fn check_feature_volume(
         &self,
         db_client: &PostgresClient,
         previous_run: NaiveDateTime,
-    ) -> Result<Option<Vec<BTreeMap<String, String>>>, anyhow::Error> {
+        config: &VolumeConfig,
+    ) -> Vec<BTreeMap<String, String>> {
         match self {
-            Drifter::SpcDrifter(drifter) => drifter.check_for_alerts(db_client, previous_run).await,
+            Drifter::SpcDrifter(drifter) => {
+                drifter
+                    .check_feature_volume(db_client, previous_run, config)
+                    .await
+            }
+            Drifter::PsiDrifter(drifter) => {
+                drifter
+                    .check_feature_volume(db_client, previous_run, config)
+                    .await
+            }
+            Drifter::ObservabilityDrifter(_) => Vec::new(),
         }
     }
 }
@@ -0,0 +1,262 @@
+use crate::alerts::dispatch::dispatch_alerts;
+use crate::api::schema::ServiceInfo;
+use crate::sql::postgres::PostgresClient;
+use crate::sql::schema::FeatureVolume;
+use chrono::NaiveDateTime;
+use scouter::core::dispatch::types::AlertDispatchType;
+use std::collections::{BTreeMap, HashMap};
+use tracing::{error, warn};
+
+// Alert when a run receives less than half of the trailing average
+const DEFAULT_MIN_VOLUME_RATIO: f64 = 0.5;
+
+// Number of preceding run windows the trailing average is computed over
+const DEFAULT_VOLUME_LOOKBACK_RUNS: i32 = 7;
+
+pub const MISSING_DATA_KIND: &str = "missing_data";
+pub const LOW_VOLUME_KIND: &str = "low_volume";
+
+/// Records counted by a volume check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VolumeSource {
+    Spc,
+    Psi,
+}
+
+impl VolumeSource {
+    /// Table the records are stored in
+    pub fn table(&self) -> &'static str {
+        match self {
+            VolumeSource::Spc => "scouter.drift",
+            VolumeSource::Psi => "scouter.observed_bin_count",
+        }
+    }
+
+    /// Data type of the features in the feature catalogue
+    pub fn data_type(&self) -> &'static str {
+        match self {
+            VolumeSource::Spc => "spc",
+            VolumeSource::Psi => "psi",
+        }
+    }
+}
+
+/// Dispatcher settings of the profile a volume check runs for
+pub struct VolumeDispatch<'a> {
+    pub dispatch_type: &'a AlertDispatchType,
+    pub dispatch_kwargs: &'a HashMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VolumeConfig {
+    // fraction of the trailing average below which a low volume alert is raised, 0 disables it
+    pub min_ratio: f64,
+    pub lookback_runs: i32,
+}
+
+impl VolumeConfig {
+    // Load volume alert settings from SCOUTER_MIN_VOLUME_RATIO and SCOUTER_VOLUME_LOOKBACK_RUNS
+    pub fn from_env() -> Self {
+        let min_ratio = std::env::var("SCOUTER_MIN_VOLUME_RATIO")
+            .ok()
+            .and_then(|ratio| ratio.parse::<f64>().ok())
+            .filter(|ratio| *ratio >= 0.0)
+            .unwrap_or(DEFAULT_MIN_VOLUME_RATIO);
+
+        let lookback_runs = std::env::var("SCOUTER_VOLUME_LOOKBACK_RUNS")
+            .ok()
+            .and_then(|runs| runs.parse::<i32>().ok())
+            .filter(|runs| *runs > 0)
+            .unwrap_or(DEFAULT_VOLUME_LOOKBACK_RUNS);
+
+        Self {
+            min_ratio,
+            lookback_runs,
+        }
+    }
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        Self {
+            min_ratio: DEFAULT_MIN_VOLUME_RATIO,
+            lookback_runs: DEFAULT_VOLUME_LOOKBACK_RUNS,
+        }
+    }
+}
+
+/// Build alerts for features that stopped reporting or whose record volume dropped
+///
+/// Features without a trailing baseline never alert, and a feature is not alerted again
+/// while an alert of the same kind is still open for it
+///
+/// # Arguments
+///
+/// * `volumes` - Record counts for each monitored feature
+/// * `config` - Volume alert settings
+///
+/// # Returns
+///
+/// * `Vec<BTreeMap<String, String>>` - One alert per affected feature
+pub fn volume_alerts(
+    volumes: &[FeatureVolume],
+    config: &VolumeConfig,
+) -> Vec<BTreeMap<String, String>> {
+    volumes
+        .iter()
+        .filter_map(|volume| {
+            if volume.trailing_average <= 0.0 {
+                return None;
+            }
+
+            let kind = if volume.current_count == 0 {
+                MISSING_DATA_KIND
+            } else if (volume.current_count as f64) < volume.trailing_average * config.min_ratio {
+                LOW_VOLUME_KIND
+            } else {
+                return None;
+            };
+
+            if volume.open_alerts.iter().any(|open| open == kind) {
+                return None;
+            }
+
+            let mut alert = BTreeMap::new();
+            alert.insert("kind".to_string(), kind.to_string());
+            alert.insert("feature".to_string(), volume.feature.clone());
+            alert.insert("records".to_string(), volume.current_count.to_string());
+            alert.insert(
+                "expected".to_string(),
+                format!("{:.1}", volume.trailing_average),
+            );
+            Some(alert)
+        })
+        .collect()
+}
+
+/// Check the record volume of every monitored feature since the previous run and
+/// dispatch any alerts raised
+///
+/// Errors are logged rather than returned so a failed check never blocks drift alerting
+///
+/// # Arguments
+///
+/// * `db_client` - Postgres client to count records with
+/// * `source` - Records to count for the profile's drift type
+/// * `service_info` - Service the drift run is executed for
+/// * `features_to_monitor` - Monitored features, all catalogued features when empty
+/// * `previous_run` - Previous run timestamp
+/// * `config` - Volume alert settings
+/// * `dispatch` - Dispatcher settings of the profile
+///
+/// # Returns
+///
+/// * `Vec<BTreeMap<String, String>>` - Missing data and low volume alerts
+pub async fn check_feature_volume(
+    db_client: &PostgresClient,
+    source: VolumeSource,
+    service_info: &ServiceInfo,
+    features_to_monitor: &[String],
+    previous_run: NaiveDateTime,
+    config: &VolumeConfig,
+    dispatch: VolumeDispatch<'_>,
+) -> Vec<BTreeMap<String, String>> {
+    let volumes = match db_client
+        .get_feature_volumes(
+            source,
+            service_info,
+            features_to_monitor,
+            previous_run,
+            config.lookback_runs,
+        )
+        .await
+    {
+        Ok(volumes) => volumes,
+        Err(e) => {
+            error!(
+                "Error checking feature volume for {}/{}/{}: {:?}",
+                service_info.repository, service_info.name, service_info.version, e
+            );
+            return Vec::new();
+        }
+    };
+
+    let alerts = volume_alerts(&volumes, config);
+
+    for alert in alerts.iter() {
+        warn!(
+            "{} for {}/{}/{} feature {}",
+            alert["kind"],
+            service_info.repository,
+            service_info.name,
+            service_info.version,
+            alert["feature"]
+        );
+    }
+
+    // failures are logged by the dispatcher, the alerts are still persisted
+    let _ = dispatch_alerts(
+        service_info,
+        dispatch.dispatch_type,
+        dispatch.dispatch_kwargs,
+        &alerts,
+    )
+    .await;
+
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn volume(feature: &str, current_count: i64, trailing_average: f64) -> FeatureVolume {
+        FeatureVolume {
+            feature: feature.to_string(),
+            current_count,
+            trailing_average,
+            open_alerts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_volume_alerts() {
+        let volumes = vec![
+            volume("missing", 0, 100.0),
+            volume("never_seen", 0, 0.0),
+            volume("low", 40, 100.0),
+            volume("healthy", 60, 100.0),
+            volume("new", 10, 0.0),
+        ];
+
+        let alerts = volume_alerts(&volumes, &VolumeConfig::default());
+        let kinds = alerts
+            .iter()
+            .map(|alert| (alert["feature"].as_str(), alert["kind"].as_str()))
+            .collect::<Vec<_>>();
+
+        // features without a baseline are never alerted on
+        assert_eq!(
+            kinds,
+            vec![("missing", MISSING_DATA_KIND), ("low", LOW_VOLUME_KIND)]
+        );
+        assert_eq!(alerts[1]["records"], "40");
+        assert_eq!(alerts[1]["expected"], "100.0");
+
+        // a ratio of 0 only alerts on missing data
+        let config = VolumeConfig {
+            min_ratio: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(volume_alerts(&volumes, &config).len(), 1);
+
+        // open alerts of the same kind are not raised again
+        let mut volumes = vec![volume("missing", 0, 100.0), volume("low", 40, 100.0)];
+        volumes[0].open_alerts = vec![MISSING_DATA_KIND.to_string()];
+        volumes[1].open_alerts = vec![MISSING_DATA_KIND.to_string()];
+
+        let alerts = volume_alerts(&volumes, &VolumeConfig::default());
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0]["kind"], LOW_VOLUME_KIND);
+    }
+}
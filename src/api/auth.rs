@@ -0,0 +1,554 @@
+use crate::api::jwt::{is_jwt, JwtConfig, JwtValidator};
+use crate::api::route::{AppState, ROUTE_PREFIX};
+use crate::sql::postgres::PostgresClient;
+use crate::sql::schema::ApiKeyRecord;
+use anyhow::{anyhow, Context};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header::AUTHORIZATION, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+    Json,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// Prefix prepended to every generated api key
+pub const API_KEY_PREFIX: &str = "sct_";
+
+/// Number of characters (after API_KEY_PREFIX) stored in plain text to identify a key
+const KEY_PREFIX_LENGTH: usize = 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Ingest,
+    Read,
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Ingest => "ingest",
+            Scope::Read => "read",
+            Scope::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = anyhow::Error;
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        match scope {
+            "ingest" => Ok(Scope::Ingest),
+            "read" => Ok(Scope::Read),
+            "admin" => Ok(Scope::Admin),
+            _ => Err(anyhow!("Invalid scope: {}", scope)),
+        }
+    }
+}
+
+/// Per repository roles. Each role includes the permissions of the roles below it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    // read drift, profiles, alerts and metrics
+    Viewer,
+    // ingest records and insert or update profiles
+    Editor,
+    // activate and deactivate monitors
+    Owner,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Owner => "owner",
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = anyhow::Error;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        match role {
+            "viewer" => Ok(Role::Viewer),
+            "editor" => Ok(Role::Editor),
+            "owner" => Ok(Role::Owner),
+            _ => Err(anyhow!("Invalid role: {}", role)),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct AuthConfig {
+    pub enabled: bool,
+    // validates JWT bearer tokens when a JWKS source is configured
+    pub jwt: Option<JwtValidator>,
+}
+
+impl AuthConfig {
+    /// Load auth settings from the environment. Auth is disabled unless SCOUTER_AUTH_ENABLED=true.
+    /// JWT validation is enabled when SCOUTER_JWKS_URL or SCOUTER_JWKS_PATH is set
+    pub async fn from_env() -> Result<Self, anyhow::Error> {
+        let enabled = std::env::var("SCOUTER_AUTH_ENABLED")
+            .map(|value| value.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let jwt = match JwtConfig::from_env() {
+            Some(config) if enabled => {
+                let validator = JwtValidator::new(config)
+                    .await
+                    .with_context(|| "Failed to load JWKS")?;
+                validator.start_refresh_task();
+                Some(validator)
+            }
+            _ => None,
+        };
+
+        Ok(Self { enabled, jwt })
+    }
+}
+
+/// Identity attached to every authenticated request
+///
+/// Access to a repository is resolved in three steps:
+/// * `repositories` - if set, the credential can never touch any other repository
+/// * `scopes` - admin grants owner on every reachable repository. Credentials restricted to
+///   a list of repositories are implicitly editor (ingest) or viewer (read) on those.
+///   Unrestricted credentials are only implicitly viewer, editing needs a role binding
+/// * `roles` - role bindings from scouter.repository_roles, keyed by repository
+#[derive(Debug, Clone)]
+pub struct AuthContext {
+    pub subject: String,
+    pub scopes: Vec<Scope>,
+    pub repositories: Option<Vec<String>>,
+    pub roles: HashMap<String, Role>,
+}
+
+impl AuthContext {
+    /// Context used when auth is disabled. Grants every scope on every repository
+    pub fn anonymous() -> Self {
+        Self {
+            subject: "anonymous".to_string(),
+            scopes: vec![Scope::Admin],
+            repositories: None,
+            roles: HashMap::new(),
+        }
+    }
+
+    pub fn from_api_key(record: ApiKeyRecord) -> Self {
+        Self {
+            subject: format!("api_key:{}", record.id),
+            scopes: record
+                .scopes
+                .iter()
+                .filter_map(|scope| scope.parse().ok())
+                .collect(),
+            repositories: record.repositories,
+            roles: HashMap::new(),
+        }
+    }
+
+    /// Admin implies every other scope
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&Scope::Admin) || self.scopes.contains(&scope)
+    }
+
+    pub fn can_access(&self, repository: &str) -> bool {
+        match &self.repositories {
+            Some(repositories) => repositories.iter().any(|repo| repo == repository),
+            None => true,
+        }
+    }
+
+    /// Effective role of the caller on a repository
+    pub fn role_for(&self, repository: &str) -> Option<Role> {
+        if !self.can_access(repository) {
+            return None;
+        }
+
+        self.implicit_role()
+            .max(self.roles.get(repository).copied())
+    }
+
+    /// Role granted by the credential's scopes on every repository it can reach
+    ///
+    /// Ingest only makes a credential an editor on the repositories it is restricted to,
+    /// so an unrestricted key cannot overwrite another team's profiles
+    fn implicit_role(&self) -> Option<Role> {
+        if self.has_scope(Scope::Admin) {
+            Some(Role::Owner)
+        } else if self.has_scope(Scope::Ingest) && self.repositories.is_some() {
+            Some(Role::Editor)
+        } else if self.has_scope(Scope::Read) || self.has_scope(Scope::Ingest) {
+            Some(Role::Viewer)
+        } else {
+            None
+        }
+    }
+
+    /// Repositories the caller can view, or None when every repository is visible
+    pub fn viewable_repositories(&self) -> Option<Vec<String>> {
+        if self.implicit_role().is_some() {
+            return self.repositories.clone();
+        }
+
+        let mut repositories = self
+            .repositories
+            .iter()
+            .flatten()
+            .chain(self.roles.keys())
+            .filter(|repository| self.role_for(repository).is_some())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        repositories.sort();
+        repositories.dedup();
+        Some(repositories)
+    }
+
+    /// Returns a 403 response unless the caller holds at least `role` on the repository
+    pub fn authorize(
+        &self,
+        repository: &str,
+        role: Role,
+    ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+        if self
+            .role_for(repository)
+            .is_some_and(|granted| granted >= role)
+        {
+            return Ok(());
+        }
+
+        Err((
+            StatusCode::FORBIDDEN,
+            Json(json!({
+                "status": "error",
+                "message": format!(
+                    "{} role is required on repository {}",
+                    role.as_str(),
+                    repository
+                )
+            })),
+        ))
+    }
+}
+
+/// Generate a new api key
+///
+/// # Returns
+///
+/// * `(String, String)` - The full key and the non-secret prefix used to identify it
+pub fn generate_api_key() -> (String, String) {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    let secret = hex::encode(bytes);
+    let prefix = secret[..KEY_PREFIX_LENGTH].to_string();
+
+    (format!("{}{}", API_KEY_PREFIX, secret), prefix)
+}
+
+/// Keys are 256 bits of randomness, so an unsalted sha256 digest is sufficient for storage
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Scope required to call a route. Returns None for public routes
+pub fn required_scope(method: &Method, path: &str) -> Option<Scope> {
+    let route = path.strip_prefix(ROUTE_PREFIX).unwrap_or(path);
+
+    match (method, route) {
+        (_, "/healthcheck") => None,
+        (&Method::POST, "/drift")
+        | (&Method::POST, "/profile")
+        | (&Method::PUT, "/profile")
+        | (&Method::PUT, "/profile/status")
+        | (&Method::PUT, "/profile/catch_up")
+        | (&Method::POST, "/profile/run")
+        | (&Method::DELETE, "/profile")
+        | (&Method::POST, "/observability/metrics")
+        | (&Method::POST, "/observability/profile")
+        | (&Method::PUT, "/observability/profile")
+        | (&Method::PUT, "/alerts/:id") => Some(Scope::Ingest),
+        (_, route) if route.starts_with("/auth") => Some(Scope::Admin),
+        (&Method::GET, _)
+        | (&Method::POST, "/profile/backtest")
+        | (&Method::POST, "/drift/check") => Some(Scope::Read),
+        _ => Some(Scope::Admin),
+    }
+}
+
+fn auth_error(status: StatusCode, message: &str) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        status,
+        Json(json!({
+            "status": "error",
+            "message": message
+        })),
+    )
+}
+
+/// Middleware that resolves the caller from the Authorization header and enforces route scopes
+///
+/// Api keys and JWTs are passed as `Authorization: Bearer <token>`. The resolved AuthContext is
+/// added to the request extensions so handlers can enforce repository restrictions.
+pub async fn authenticate(
+    State(state): State<Arc<AppState>>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, (StatusCode, Json<serde_json::Value>)> {
+    let path = if let Some(matched_path) = req.extensions().get::<MatchedPath>() {
+        matched_path.as_str().to_owned()
+    } else {
+        req.uri().path().to_owned()
+    };
+
+    let scope = required_scope(req.method(), &path);
+
+    if !state.auth.enabled {
+        req.extensions_mut().insert(AuthContext::anonymous());
+        return Ok(next.run(req).await);
+    }
+
+    let scope = match scope {
+        Some(scope) => scope,
+        None => return Ok(next.run(req).await),
+    };
+
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| value.trim().to_string());
+
+    let token = match token {
+        Some(token) if !token.is_empty() => token,
+        _ => return Err(auth_error(StatusCode::UNAUTHORIZED, "Missing bearer token")),
+    };
+
+    let mut context = match &state.auth.jwt {
+        Some(validator) if is_jwt(&token) => match validator.validate(&token) {
+            Ok(context) => context,
+            Err(e) => {
+                warn!("Rejected bearer token: {:?}", e);
+                return Err(auth_error(StatusCode::UNAUTHORIZED, "Invalid bearer token"));
+            }
+        },
+        _ => match state.db.get_api_key(&hash_api_key(&token)).await {
+            Ok(Some(record)) => AuthContext::from_api_key(record),
+            Ok(None) => return Err(auth_error(StatusCode::UNAUTHORIZED, "Invalid api key")),
+            Err(e) => {
+                error!("Failed to authenticate request: {:?}", e);
+                return Err(auth_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to authenticate request",
+                ));
+            }
+        },
+    };
+
+    if !context.has_scope(scope) {
+        return Err(auth_error(
+            StatusCode::FORBIDDEN,
+            &format!("Credentials are missing the {} scope", scope.as_str()),
+        ));
+    }
+
+    if !context.has_scope(Scope::Admin) {
+        match state
+            .db
+            .get_repository_roles(Some(&context.subject), None)
+            .await
+        {
+            Ok(bindings) => {
+                context.roles = bindings
+                    .into_iter()
+                    .filter_map(|binding| {
+                        let role = binding.role.parse().ok()?;
+                        Some((binding.repository, role))
+                    })
+                    .collect();
+            }
+            Err(e) => {
+                error!("Failed to load repository roles: {:?}", e);
+                return Err(auth_error(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Failed to authenticate request",
+                ));
+            }
+        }
+    }
+
+    req.extensions_mut().insert(context);
+
+    Ok(next.run(req).await)
+}
+
+/// Register the key in SCOUTER_BOOTSTRAP_API_KEY as an unrestricted admin key.
+/// This is how the first admin key is provisioned when auth is enabled on a fresh database
+pub async fn bootstrap_api_key(db: &PostgresClient) -> Result<(), anyhow::Error> {
+    let key = match std::env::var("SCOUTER_BOOTSTRAP_API_KEY") {
+        Ok(key) if !key.is_empty() => key,
+        _ => return Ok(()),
+    };
+
+    let prefix: String = key
+        .trim_start_matches(API_KEY_PREFIX)
+        .chars()
+        .take(KEY_PREFIX_LENGTH)
+        .collect();
+
+    let inserted = db
+        .insert_api_key(
+            "bootstrap",
+            &prefix,
+            &hash_api_key(&key),
+            &[Scope::Admin.as_str().to_string()],
+            None,
+        )
+        .await
+        .with_context(|| "Failed to register bootstrap api key")?;
+
+    if inserted.is_some() {
+        info!("Registered bootstrap api key");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required_scope(&Method::GET, "/scouter/healthcheck"), None);
+        assert_eq!(
+            required_scope(&Method::POST, "/scouter/drift"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/scouter/drift"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::PUT, "/scouter/profile/status"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::PUT, "/scouter/profile/catch_up"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/scouter/profile/run"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/scouter/profile/backtest"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/scouter/drift/check"),
+            Some(Scope::Read)
+        );
+        assert_eq!(
+            required_scope(&Method::PUT, "/scouter/alerts/:id"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::DELETE, "/scouter/profile"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/scouter/observability/metrics"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::POST, "/scouter/observability/profile"),
+            Some(Scope::Ingest)
+        );
+        assert_eq!(
+            required_scope(&Method::GET, "/scouter/auth/keys"),
+            Some(Scope::Admin)
+        );
+    }
+
+    #[test]
+    fn test_generate_api_key() {
+        let (key, prefix) = generate_api_key();
+
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(key.len(), API_KEY_PREFIX.len() + 64);
+        assert_eq!(&key[API_KEY_PREFIX.len()..][..KEY_PREFIX_LENGTH], prefix);
+        assert_eq!(hash_api_key(&key), hash_api_key(&key));
+        assert_ne!(hash_api_key(&key), hash_api_key(&generate_api_key().0));
+    }
+
+    #[test]
+    fn test_role_for() {
+        let mut context = AuthContext {
+            subject: "api_key:1".to_string(),
+            scopes: vec![Scope::Read, Scope::Ingest],
+            repositories: None,
+            roles: HashMap::from([
+                ("team-a".to_string(), Role::Owner),
+                ("team-b".to_string(), Role::Viewer),
+            ]),
+        };
+
+        // unrestricted credentials are implicitly viewers everywhere, editing needs a binding
+        assert_eq!(context.role_for("team-a"), Some(Role::Owner));
+        assert!(context.authorize("team-a", Role::Editor).is_ok());
+        assert!(context.authorize("team-b", Role::Editor).is_err());
+        assert_eq!(context.role_for("team-c"), Some(Role::Viewer));
+        assert!(context.authorize("team-c", Role::Editor).is_err());
+        assert_eq!(context.viewable_repositories(), None);
+
+        context.scopes = vec![Scope::Ingest];
+        assert_eq!(context.role_for("team-c"), Some(Role::Viewer));
+
+        context.scopes = vec![Scope::Read];
+        assert_eq!(context.role_for("team-c"), Some(Role::Viewer));
+
+        // credentials without a repository scope only reach their bindings
+        context.scopes = vec![];
+        assert_eq!(context.role_for("team-b"), Some(Role::Viewer));
+        assert_eq!(context.role_for("team-c"), None);
+        assert_eq!(
+            context.viewable_repositories(),
+            Some(vec!["team-a".to_string(), "team-b".to_string()])
+        );
+
+        context.scopes = vec![Scope::Read, Scope::Ingest];
+
+        // restricted credentials are implicitly editors on their repositories
+        context.repositories = Some(vec!["team-b".to_string(), "team-c".to_string()]);
+        assert_eq!(context.role_for("team-a"), None);
+        assert_eq!(context.role_for("team-b"), Some(Role::Editor));
+        assert_eq!(context.role_for("team-c"), Some(Role::Editor));
+        assert_eq!(
+            context.viewable_repositories(),
+            Some(vec!["team-b".to_string(), "team-c".to_string()])
+        );
+
+        context.scopes = vec![Scope::Admin];
+        assert_eq!(context.role_for("team-c"), Some(Role::Owner));
+        assert_eq!(context.role_for("team-a"), None);
+
+        assert_eq!(AuthContext::anonymous().viewable_repositories(), None);
+    }
+}
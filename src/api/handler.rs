@@ -1,24 +1,47 @@
+use crate::alerts::backtest::{backtest, check_backtest_size, split_windows};
+use crate::alerts::base::{get_task_drifter, DriftExecutor};
+use crate::alerts::observability::types::ObservabilityAlertProfile;
+use crate::alerts::types::{DriftProfile, Drifter, RunOptions};
+use crate::api::auth::{generate_api_key, hash_api_key, AuthContext, Role};
 use crate::api::schema::{
-    DriftAlertRequest, DriftRequest, ObservabilityMetricRequest, ProfileRequest,
-    ProfileStatusRequest, ServiceInfo,
+    AlertStatusRequest, ApiKeyRequest, BacktestRequest, DriftAlertRequest, DriftCheckRequest,
+    DriftRequest, DriftRunRequest, GetProfileRequest, ObservabilityMetricRequest,
+    ObservabilityRecordsRequest, ProfileCatchUpRequest, ProfileDeleteRequest, ProfileListRequest,
+    ProfileRequest, ProfileRunRequest, ProfileStatusRequest, RoleBindingQuery, RoleBindingRequest,
+    ServiceInfo,
 };
-use crate::consumer::base::ToDriftRecords;
-use scouter::core::drift::base::DriftProfile;
-use scouter::core::drift::base::ServerRecords;
+use crate::consumer::base::{IngestRecords, MessageHandler};
+use crate::sql::schema::{AlertCursor, AlertResult};
 
 use axum::{
-    extract::{Query, State},
+    extract::{Extension, Path, Query, State},
     http::StatusCode,
     response::IntoResponse,
     Json,
 };
 
+use anyhow::anyhow;
 use serde_json::json;
 use std::sync::Arc;
-use tracing::error;
+use tracing::{error, info};
 
 use crate::api::route::AppState;
 
+const DEFAULT_PROFILE_PAGE_SIZE: i64 = 100;
+const MAX_PROFILE_PAGE_SIZE: i64 = 1000;
+const DEFAULT_PROFILE_RUN_LIMIT: i64 = 50;
+const MAX_PROFILE_RUN_LIMIT: i64 = 500;
+
+fn bad_request(e: anyhow::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({
+            "status": "error",
+            "message": e.to_string()
+        })),
+    )
+}
+
 pub async fn health_check() -> impl IntoResponse {
     const MESSAGE: &str = "Alive";
 
@@ -32,11 +55,14 @@ pub async fn health_check() -> impl IntoResponse {
 
 pub async fn get_drift(
     State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     params: Query<DriftRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    // validate time window
+    auth.authorize(&params.repository, Role::Viewer)?;
 
-    let query_result = &data.db.get_binned_drift_records(&params).await;
+    let time_range = params.time_range().map_err(bad_request)?;
+
+    let query_result = &data.db.get_binned_drift_records(&params, &time_range).await;
 
     match query_result {
         Ok(result) => {
@@ -57,34 +83,90 @@ pub async fn get_drift(
     }
 }
 
+/// Insert a batch of drift or observability records
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<IngestRecords> - Server records of any supported record type
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Accepted and rejected record counts
 pub async fn insert_drift(
     State(data): State<Arc<AppState>>,
-    Json(body): Json<ServerRecords>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<IngestRecords>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let record = body.to_spc_drift_records().map_err(|e| {
-        error!("Failed to convert drift records: {:?}", e);
-        (
-            StatusCode::BAD_REQUEST,
-            json!({ "status": "error", "message": format!("{:?}", e) }),
-        )
-    });
-
-    if record.is_err() {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(json!({ "status": "error", "message": "Invalid drift record" })),
-        ));
+    for record in body.records.iter() {
+        auth.authorize(record.repository(), Role::Editor)?;
     }
 
-    let query_result = &data.db.insert_spc_drift_record(&record.unwrap()[0]).await;
+    let message_handler = MessageHandler::Postgres(data.db.clone());
+
+    let query_result = &message_handler.insert_server_records(&body).await;
 
     match query_result {
-        Ok(_) => Ok(Json(json!({
+        Ok(result) => Ok(Json(json!({
+            "status": "success",
+            "message": format!(
+                "Inserted {} of {} records",
+                result.accepted,
+                body.records.len()
+            ),
+            "data": result
+        }))),
+        Err(e) => {
+            error!("Failed to insert drift records: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Insert observability metrics sent directly by a service
+///
+/// Accepts a `ServerRecords` batch of type OBSERVABILITY or a single `ServerRecord`.
+/// Records may carry a `created_at` timestamp, otherwise the time they were received is used
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<ObservabilityRecordsRequest> - Observability server records
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Accepted and rejected record counts
+pub async fn insert_observability_metrics(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ObservabilityRecordsRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let records = body.into_records().map_err(bad_request)?;
+
+    for record in records.records.iter() {
+        auth.authorize(record.repository(), Role::Editor)?;
+    }
+
+    let message_handler = MessageHandler::Postgres(data.db.clone());
+
+    match message_handler.insert_server_records(&records).await {
+        Ok(result) => Ok(Json(json!({
             "status": "success",
-            "message": "Record inserted successfully"
+            "message": format!(
+                "Inserted {} of {} records",
+                result.accepted,
+                records.records.len()
+            ),
+            "data": result
         }))),
         Err(e) => {
-            error!("Failed to insert drift record: {:?}", e);
+            error!("Failed to insert observability metrics: {:?}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({
@@ -98,25 +180,36 @@ pub async fn insert_drift(
 
 pub async fn insert_drift_profile(
     State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Json(body): Json<ProfileRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     // validate profile is correct
     // this will be used to validate different versions of the drift profile in the future
 
-    let body = DriftProfile::from_value(body.profile, &body.drift_type.value());
+    let body = DriftProfile::from_value(body.profile, &body.drift_type);
 
-    if body.is_err() {
+    if let Err(e) = &body {
         // future: - validate against older versions of the drift profile
         let json_response = json!({
             "status": "error",
-            "message": "Invalid drift profile"
+            "message": format!("Invalid drift profile: {}", e)
         });
         return Err((StatusCode::BAD_REQUEST, Json(json_response)));
     }
 
-    let query_result = &data.db.insert_drift_profile(&body.unwrap()).await;
+    let body = body.unwrap();
+    auth.authorize(&body.get_base_args().repository, Role::Editor)?;
+
+    let query_result = &data.db.insert_drift_profile(&body).await;
 
     match query_result {
+        Ok(result) if result.rows_affected() == 0 => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": "A profile of this drift type already exists for the service, use PUT to update it"
+            })),
+        )),
         Ok(_) => {
             let json_response = json!({
                 "status": "success",
@@ -145,24 +238,35 @@ pub async fn insert_drift_profile(
 ///
 pub async fn update_drift_profile(
     State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
     Json(body): Json<ProfileRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     // validate profile is correct
     // this will be used to validate different versions of the drift profile in the future
-    let body = DriftProfile::from_value(body.profile, &body.drift_type.value());
+    let body = DriftProfile::from_value(body.profile, &body.drift_type);
 
-    if body.is_err() {
+    if let Err(e) = &body {
         // future: - validate against older versions of the drift profile
         let json_response = json!({
             "status": "error",
-            "message": "Invalid drift profile"
+            "message": format!("Invalid drift profile: {}", e)
         });
         return Err((StatusCode::BAD_REQUEST, Json(json_response)));
     }
 
-    let query_result = &data.db.update_drift_profile(&body.unwrap()).await;
+    let body = body.unwrap();
+    auth.authorize(&body.get_base_args().repository, Role::Editor)?;
+
+    let query_result = &data.db.update_drift_profile(&body).await;
 
     match query_result {
+        Ok(result) if result.rows_affected() == 0 => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Profile not found"
+            })),
+        )),
         Ok(_) => {
             let json_response = json!({
                 "status": "success",
@@ -181,6 +285,107 @@ pub async fn update_drift_profile(
     }
 }
 
+/// Route to register an observability alert profile
+/// The profile is scheduled by the drift executor once it is activated
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<ObservabilityAlertProfile> - Observability alert profile
+///
+pub async fn insert_observability_profile(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ObservabilityAlertProfile>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if let Err(e) = body.validate() {
+        let json_response = json!({
+            "status": "error",
+            "message": format!("Invalid observability profile: {}", e)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(json_response)));
+    }
+
+    auth.authorize(&body.repository, Role::Editor)?;
+
+    let query_result = &data.db.insert_observability_profile(&body).await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": "An observability profile already exists for the service, use PUT to update it"
+            })),
+        )),
+        Ok(_) => {
+            let json_response = json!({
+                "status": "success",
+                "message": "Observability profile inserted successfully"
+            });
+            Ok(Json(json_response))
+        }
+        Err(e) => {
+            error!("Failed to insert observability profile: {:?}", e);
+            let json_response = json!({
+                "status": "error",
+                "message": format!("{:?}", e)
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json_response)))
+        }
+    }
+}
+
+/// Route to update an observability alert profile
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<ObservabilityAlertProfile> - Observability alert profile
+///
+pub async fn update_observability_profile(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ObservabilityAlertProfile>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if let Err(e) = body.validate() {
+        let json_response = json!({
+            "status": "error",
+            "message": format!("Invalid observability profile: {}", e)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(json_response)));
+    }
+
+    auth.authorize(&body.repository, Role::Editor)?;
+
+    let query_result = &data.db.update_observability_profile(&body).await;
+
+    match query_result {
+        Ok(result) if result.rows_affected() == 0 => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Observability profile not found"
+            })),
+        )),
+        Ok(_) => {
+            let json_response = json!({
+                "status": "success",
+                "message": "Observability profile updated successfully"
+            });
+            Ok(Json(json_response))
+        }
+        Err(e) => {
+            error!("Failed to update observability profile: {:?}", e);
+            let json_response = json!({
+                "status": "error",
+                "message": format!("{:?}", e)
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json_response)))
+        }
+    }
+}
+
 /// Retrieve a drift profile from the database
 ///
 /// # Arguments
@@ -193,9 +398,21 @@ pub async fn update_drift_profile(
 /// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
 pub async fn get_profile(
     State(data): State<Arc<AppState>>,
-    params: Query<ServiceInfo>,
+    Extension(auth): Extension<AuthContext>,
+    params: Query<GetProfileRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let profile = &data.db.get_drift_profile(&params).await;
+    auth.authorize(&params.repository, Role::Viewer)?;
+
+    let service_info = ServiceInfo {
+        name: params.name.clone(),
+        repository: params.repository.clone(),
+        version: params.version.clone(),
+    };
+
+    let profile = &data
+        .db
+        .get_drift_profile(&service_info, params.drift_type.as_deref())
+        .await;
 
     match profile {
         Ok(Some(result)) => Ok(Json(json!({
@@ -222,35 +439,43 @@ pub async fn get_profile(
     }
 }
 
-/// Update drift profile status
+/// Retrieve the most recent scheduled runs of a drift profile
+///
+/// Failed runs carry the error that stopped them, so a profile whose monitoring is
+/// broken can be told apart from one that is healthy.
 ///
 /// # Arguments
 ///
 /// * `data` - Arc<AppState> - Application state
-/// * `body` - Json<ProfileStatusRequest> - Profile status request
+/// * `params` - Query<ProfileRunRequest> - Query parameters
 ///
 /// # Returns
 ///
-/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
-pub async fn update_drift_profile_status(
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Runs, newest first
+pub async fn get_profile_runs(
     State(data): State<Arc<AppState>>,
-    Json(body): Json<ProfileStatusRequest>,
+    Extension(auth): Extension<AuthContext>,
+    params: Query<ProfileRunRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = &data.db.update_drift_profile_status(&body).await;
+    auth.authorize(&params.repository, Role::Viewer)?;
 
-    match query_result {
-        Ok(_) => Ok(Json(json!({
+    let service_info = ServiceInfo {
+        name: params.name.clone(),
+        repository: params.repository.clone(),
+        version: params.version.clone(),
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_PROFILE_RUN_LIMIT)
+        .clamp(1, MAX_PROFILE_RUN_LIMIT);
+
+    match data.db.get_drift_task_runs(&service_info, limit).await {
+        Ok(runs) => Ok(Json(json!({
             "status": "success",
-            "message": format!(
-                "Monitor profile status updated to {} for {} {} {}",
-                &body.active, &body.name, &body.repository, &body.version
-            )
+            "data": runs
         }))),
         Err(e) => {
-            error!(
-                "Failed to update drift profile status for {} {} {} : {:?}",
-                &body.name, &body.repository, &body.version, e
-            );
+            error!("Failed to query drift task runs: {:?}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(json!({
@@ -262,62 +487,1234 @@ pub async fn update_drift_profile_status(
     }
 }
 
-/// Retrieve drift alerts from the database
+/// Run a drift profile immediately over a caller supplied window
+///
+/// The profile's schedule is left untouched. With `dry_run` set, alerts are returned
+/// without being inserted or dispatched.
 ///
 /// # Arguments
 ///
 /// * `data` - Arc<AppState> - Application state
-/// * `params` - Query<DriftAlertRequest> - Query parameters
+/// * `body` - Json<DriftRunRequest> - Profile and window to run
 ///
 /// # Returns
 ///
-/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
-pub async fn get_drift_alerts(
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Computed drift and alerts
+pub async fn run_drift_profile(
     State(data): State<Arc<AppState>>,
-    params: Query<DriftAlertRequest>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<DriftRunRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = &data.db.get_drift_alerts(&params).await;
+    auth.authorize(&body.repository, Role::Editor)?;
 
-    match query_result {
-        Ok(result) => {
-            let json_response = json!({
-                "status": "success",
-                "data": result
-            });
-            Ok(Json(json_response))
+    let time_range = body.time_range().map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": format!("Invalid run window: {}", e)
+            })),
+        )
+    })?;
+
+    let service_info = ServiceInfo {
+        name: body.name.clone(),
+        repository: body.repository.clone(),
+        version: body.version.clone(),
+    };
+
+    let task = match data
+        .db
+        .get_profile_task(&service_info, body.drift_type.as_deref())
+        .await
+    {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Profile not found"
+                })),
+            ));
         }
         Err(e) => {
-            error!("Failed to query drift alerts: {:?}", e);
-            let json_response = json!({
-                "status": "error",
-                "message": format!("{:?}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json_response)))
+            error!("Failed to query drift profile: {:?}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ));
+        }
+    };
+
+    let dry_run = body.dry_run.unwrap_or(false);
+    let options = RunOptions {
+        start: time_range.start,
+        end: time_range.end,
+        dispatch: !dry_run,
+    };
+
+    let executor = DriftExecutor::new(data.db.clone());
+
+    match executor.run_profile(&task, &options, !dry_run).await {
+        Ok(result) => Ok(Json(json!({
+            "status": "success",
+            "start": time_range.start,
+            "end": time_range.end,
+            "dry_run": dry_run,
+            "data": result
+        }))),
+        Err(e) => {
+            error!("Failed to run drift profile: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
         }
     }
 }
 
-pub async fn get_observability_metrics(
+/// Replay a drift profile over historical records in fixed-size windows
+///
+/// Alerts are never inserted or dispatched. Passing a `profile` replays it in place of the
+/// stored one, so alert rules and control limits can be tuned before the profile is activated.
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<BacktestRequest> - Profile, range and window size to replay
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Alerts raised in each window
+pub async fn backtest_drift_profile(
     State(data): State<Arc<AppState>>,
-    params: Query<ObservabilityMetricRequest>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<BacktestRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
-    let query_result = &data.db.get_binned_observability_metrics(&params).await;
+    auth.authorize(&body.repository, Role::Viewer)?;
 
-    match query_result {
-        Ok(result) => {
-            let json_response = serde_json::json!({
+    let windows = body
+        .time_range()
+        .and_then(|time_range| {
+            split_windows(time_range.start, time_range.end, body.window_minutes()?)
+        })
+        .map_err(|e| bad_request(anyhow!("Invalid backtest range: {}", e)))?;
+
+    let service_info = ServiceInfo {
+        name: body.name.clone(),
+        repository: body.repository.clone(),
+        version: body.version.clone(),
+    };
+
+    let mut task = match data
+        .db
+        .get_profile_task(&service_info, body.drift_type.as_deref())
+        .await
+    {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Profile not found"
+                })),
+            ));
+        }
+        Err(e) => {
+            error!("Failed to query drift profile: {:?}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ));
+        }
+    };
+
+    if let Some(profile) = &body.profile {
+        task.profile = profile.to_string();
+    }
+
+    let drifter = get_task_drifter(&task)
+        .map_err(|e| bad_request(anyhow!("Invalid drift profile: {:?}", e)))?;
+
+    // an override replays a tuned copy of this profile, never another service's profile
+    let profile_service = drifter.service_info();
+    if profile_service.name != body.name
+        || profile_service.repository != body.repository
+        || profile_service.version != body.version
+    {
+        return Err(bad_request(anyhow!(
+            "Profile override is for {}/{}/{}, expected {}/{}/{}",
+            profile_service.repository,
+            profile_service.name,
+            profile_service.version,
+            body.repository,
+            body.name,
+            body.version
+        )));
+    }
+
+    check_backtest_size(&drifter, windows.len()).map_err(bad_request)?;
+
+    match backtest(&data.db, &drifter, &windows).await {
+        Ok(windows) => {
+            let total_alerts: usize = windows.iter().map(|window| window.alerts.len()).sum();
+
+            Ok(Json(json!({
                 "status": "success",
-                "data": result
-            });
-            Ok(Json(json_response))
+                "total_alerts": total_alerts,
+                "data": windows
+            })))
         }
         Err(e) => {
-            error!("Failed to query observability_metrics: {:?}", e);
-            let json_response = json!({
-                "status": "error",
-                "message": format!("{:?}", e)
-            });
-            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json_response)))
+            error!("Failed to backtest drift profile: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Check a sample of feature values against a stored spc profile
+///
+/// Drift and rule violations are computed in memory. The sample is not stored and alerts
+/// are neither inserted nor dispatched.
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<DriftCheckRequest> - Profile reference and feature values
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Drift and rule violations
+pub async fn check_drift_sample(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<DriftCheckRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&body.repository, Role::Viewer)?;
+
+    let service_info = ServiceInfo {
+        name: body.name.clone(),
+        repository: body.repository.clone(),
+        version: body.version.clone(),
+    };
+
+    let task = match data.db.get_profile_task(&service_info, None).await {
+        Ok(Some(task)) => task,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Profile not found"
+                })),
+            ));
+        }
+        Err(e) => {
+            error!("Failed to query drift profile: {:?}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ));
+        }
+    };
+
+    let drifter = match get_task_drifter(&task) {
+        Ok(Drifter::SpcDrifter(drifter)) => drifter,
+        Ok(_) => {
+            return Err(bad_request(anyhow!(
+                "Sample checks are only supported for SPC profiles, {} is {}",
+                body.name,
+                task.drift_type
+            )));
+        }
+        Err(e) => {
+            error!("Failed to load drift profile: {:?}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ));
+        }
+    };
+
+    match drifter.check_sample(&body.features) {
+        Ok(result) => Ok(Json(json!({
+            "status": "success",
+            "data": result
+        }))),
+        Err(e) => Err(bad_request(anyhow!("Invalid sample: {:?}", e))),
+    }
+}
+
+/// List the features recorded for a service
+///
+/// Features are read from the catalogue maintained at ingestion time, so `last_seen`
+/// shows when a feature last reported. Routes reported in observability metrics are
+/// listed with the `route` data type.
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `params` - Query<ServiceInfo> - Query parameters
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
+pub async fn get_features(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    params: Query<ServiceInfo>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&params.repository, Role::Viewer)?;
+
+    let features = &data.db.get_feature_catalogue(&params).await;
+
+    match features {
+        Ok(result) => Ok(Json(json!({
+            "status": "success",
+            "data": result
+        }))),
+        Err(e) => {
+            error!("Failed to query feature catalogue: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Delete a drift profile, optionally purging all data recorded for the service
+///
+/// The profile is removed immediately. When `purge` is set the service's data is
+/// deleted by a background job and the response carries the job to poll.
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - AuthContext - Caller deleting the profile
+/// * `body` - Json<ProfileDeleteRequest> - Profile to delete and purge flag
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
+pub async fn delete_drift_profile(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ProfileDeleteRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&body.repository, Role::Owner)?;
+
+    let service_info = ServiceInfo {
+        name: body.name.clone(),
+        repository: body.repository.clone(),
+        version: body.version.clone(),
+    };
+    let purge = body.purge.unwrap_or(false);
+
+    let deleted = match data
+        .db
+        .delete_drift_profile(&service_info, body.drift_type.as_deref())
+        .await
+    {
+        Ok(deleted) => deleted,
+        Err(e) => {
+            error!("Failed to delete drift profile: {:?}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ));
+        }
+    };
+
+    // data for an already deleted profile can still be purged
+    if !deleted && !purge {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Profile not found"
+            })),
+        ));
+    }
+
+    if deleted {
+        info!(
+            "Deleted drift profile {}/{}/{} by {}",
+            body.repository, body.name, body.version, auth.subject
+        );
+    }
+
+    if !purge {
+        return Ok((
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": "Profile deleted"
+            })),
+        ));
+    }
+
+    match data.db.insert_purge_job(&service_info, &auth.subject).await {
+        Ok(job) => {
+            let db = data.db.clone();
+            let purge_job = job.clone();
+            tokio::spawn(async move {
+                if let Err(e) = db.run_purge_job(&purge_job).await {
+                    error!("Purge job {} failed: {:?}", purge_job.id, e);
+                }
+            });
+
+            Ok((
+                StatusCode::ACCEPTED,
+                Json(json!({
+                    "status": "success",
+                    "message": "Profile deleted, purge job started",
+                    "data": job
+                })),
+            ))
+        }
+        Err(e) => {
+            error!("Failed to start purge job: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Retrieve the status of a purge job
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - AuthContext - Caller requesting the job
+/// * `id` - Path<i32> - Id of the purge job
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Purge job
+pub async fn get_purge_job(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match data.db.get_purge_job(id).await {
+        Ok(Some(job)) => {
+            auth.authorize(&job.repository, Role::Viewer)?;
+
+            Ok(Json(json!({
+                "status": "success",
+                "data": job
+            })))
+        }
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Purge job not found"
+            })),
+        )),
+        Err(e) => {
+            error!("Failed to get purge job: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// List drift profiles the caller can view
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - AuthContext - Caller listing profiles
+/// * `params` - Query<ProfileListRequest> - Filters and pagination
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Page of profile summaries
+pub async fn list_profiles(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    params: Query<ProfileListRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let mut params = params.0;
+    params.limit = Some(
+        params
+            .limit
+            .unwrap_or(DEFAULT_PROFILE_PAGE_SIZE)
+            .clamp(1, MAX_PROFILE_PAGE_SIZE),
+    );
+    params.offset = Some(params.offset.unwrap_or(0).max(0));
+
+    let repositories = match &params.repository {
+        Some(repository) => {
+            auth.authorize(repository, Role::Viewer)?;
+            None
+        }
+        None => auth.viewable_repositories(),
+    };
+
+    if repositories.as_ref().is_some_and(|repos| repos.is_empty()) {
+        return Ok(Json(json!({
+            "status": "success",
+            "data": [],
+            "total": 0,
+            "next_offset": null
+        })));
+    }
+
+    let query_result = tokio::try_join!(
+        data.db
+            .list_drift_profiles(&params, repositories.as_deref()),
+        data.db
+            .count_drift_profiles(&params, repositories.as_deref())
+    );
+
+    match query_result {
+        Ok((result, total)) => {
+            let offset = params.offset.unwrap_or(0);
+            let next_offset = offset + result.len() as i64;
+
+            Ok(Json(json!({
+                "status": "success",
+                "data": result,
+                "total": total,
+                "next_offset": (next_offset < total).then_some(next_offset)
+            })))
+        }
+        Err(e) => {
+            error!("Failed to list drift profiles: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Update drift profile status
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<ProfileStatusRequest> - Profile status request
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
+pub async fn update_drift_profile_status(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ProfileStatusRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&body.repository, Role::Owner)?;
+
+    let query_result = &data.db.update_drift_profile_status(&body).await;
+
+    match query_result {
+        Ok(_) => Ok(Json(json!({
+            "status": "success",
+            "message": format!(
+                "Monitor profile status updated to {} for {} {} {}",
+                &body.active, &body.name, &body.repository, &body.version
+            )
+        }))),
+        Err(e) => {
+            error!(
+                "Failed to update drift profile status for {} {} {} : {:?}",
+                &body.name, &body.repository, &body.version, e
+            );
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Update how the drift executor handles missed runs of a profile
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `body` - Json<ProfileCatchUpRequest> - Profile catch-up request
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
+pub async fn update_drift_profile_catch_up(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ProfileCatchUpRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&body.repository, Role::Owner)?;
+
+    match data.db.update_drift_profile_catch_up(&body).await {
+        Ok(true) => Ok(Json(json!({
+            "status": "success",
+            "message": format!(
+                "Catch-up policy updated to {} for {} {} {}",
+                body.catch_up.as_str(), &body.name, &body.repository, &body.version
+            )
+        }))),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Profile not found"
+            })),
+        )),
+        Err(e) => {
+            error!(
+                "Failed to update drift profile catch-up policy for {} {} {} : {:?}",
+                &body.name, &body.repository, &body.version, e
+            );
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Retrieve a page of drift alerts from the database
+///
+/// Alerts are returned newest first. When a full page is returned the response
+/// carries a `next_cursor` that can be passed back as `cursor` to fetch the next page.
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `params` - Query<DriftAlertRequest> - Query parameters
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
+pub async fn get_drift_alerts(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    params: Query<DriftAlertRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&params.repository, Role::Viewer)?;
+    params.time_bounds().map_err(bad_request)?;
+
+    if let Some(cursor) = &params.cursor {
+        if AlertCursor::decode(cursor).is_err() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "Invalid cursor"
+                })),
+            ));
+        }
+    }
+
+    let query_result = tokio::try_join!(
+        data.db.get_drift_alerts(&params),
+        data.db.count_drift_alerts(&params)
+    );
+
+    match query_result {
+        Ok((result, total)) => {
+            let next_cursor = match (params.limit, result.last()) {
+                (Some(limit), Some(last)) if result.len() as i32 >= limit => {
+                    Some(AlertCursor::from(last).encode())
+                }
+                _ => None,
+            };
+
+            let json_response = json!({
+                "status": "success",
+                "data": result,
+                "next_cursor": next_cursor,
+                "total": total
+            });
+            Ok(Json(json_response))
+        }
+        Err(e) => {
+            error!("Failed to query drift alerts: {:?}", e);
+            let json_response = json!({
+                "status": "error",
+                "message": format!("{:?}", e)
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json_response)))
+        }
+    }
+}
+
+/// Acknowledge, resolve or reopen a drift alert
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - AuthContext - Caller changing the alert status
+/// * `id` - Path<i32> - Id of the alert
+/// * `body` - Json<AlertStatusRequest> - Action, actor and comment
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Updated alert
+pub async fn update_drift_alert_status(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<i32>,
+    Json(body): Json<AlertStatusRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let alert = get_alert_or_not_found(&data, id).await?;
+
+    auth.authorize(&alert.repository, Role::Editor)?;
+
+    let conflict = |status: &str| {
+        (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": format!(
+                    "Cannot {} alert {} with status {}",
+                    body.action.as_str(),
+                    id,
+                    status
+                )
+            })),
+        )
+    };
+
+    if !body.action.allowed_from().contains(&alert.status.as_str()) {
+        return Err(conflict(&alert.status));
+    }
+
+    let actor = body.actor.clone().unwrap_or_else(|| auth.subject.clone());
+
+    let query_result = &data
+        .db
+        .update_drift_alert_status(
+            id,
+            &body.action,
+            &actor,
+            &auth.subject,
+            body.comment.as_deref(),
+        )
+        .await;
+
+    match query_result {
+        Ok(Some(result)) => Ok(Json(json!({
+            "status": "success",
+            "message": format!("Alert {} {}", id, body.action.status()),
+            "data": result
+        }))),
+        // status changed between the read above and the update
+        Ok(None) => Err(conflict("changed concurrently")),
+        Err(e) => {
+            error!("Failed to update alert {} status: {:?}", id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Retrieve the status transitions of a drift alert
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `id` - Path<i32> - Id of the alert
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Alert history, oldest first
+pub async fn get_drift_alert_history(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let alert = get_alert_or_not_found(&data, id).await?;
+
+    auth.authorize(&alert.repository, Role::Viewer)?;
+
+    let query_result = &data.db.get_drift_alert_history(id).await;
+
+    match query_result {
+        Ok(result) => Ok(Json(json!({
+            "status": "success",
+            "data": result
+        }))),
+        Err(e) => {
+            error!("Failed to query alert {} history: {:?}", id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+async fn get_alert_or_not_found(
+    data: &AppState,
+    id: i32,
+) -> Result<AlertResult, (StatusCode, Json<serde_json::Value>)> {
+    match data.db.get_drift_alert(id).await {
+        Ok(Some(alert)) => Ok(alert),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": format!("Alert {} not found", id)
+            })),
+        )),
+        Err(e) => {
+            error!("Failed to query alert {}: {:?}", id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn get_observability_metrics(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    params: Query<ObservabilityMetricRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&params.repository, Role::Viewer)?;
+
+    let time_range = params.time_range().map_err(bad_request)?;
+
+    let query_result = &data
+        .db
+        .get_binned_observability_metrics(&params, &time_range)
+        .await;
+
+    match query_result {
+        Ok(result) => {
+            let json_response = serde_json::json!({
+                "status": "success",
+                "data": result
+            });
+            Ok(Json(json_response))
+        }
+        Err(e) => {
+            error!("Failed to query observability_metrics: {:?}", e);
+            let json_response = json!({
+                "status": "error",
+                "message": format!("{:?}", e)
+            });
+            Err((StatusCode::INTERNAL_SERVER_ERROR, Json(json_response)))
+        }
+    }
+}
+
+/// Create a new api key. The plain text key is only returned in this response
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - AuthContext - Caller creating the key
+/// * `body` - Json<ApiKeyRequest> - Name, scopes and optional repository restrictions
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Created key
+pub async fn create_api_key(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<ApiKeyRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if body.scopes.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "status": "error",
+                "message": "At least one scope is required"
+            })),
+        ));
+    }
+
+    // a restricted caller cannot mint keys for repositories it cannot access
+    match &body.repositories {
+        Some(repositories) => {
+            for repository in repositories.iter() {
+                auth.authorize(repository, Role::Owner)?;
+            }
+        }
+        None if auth.repositories.is_some() => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "status": "error",
+                    "message": "Repository restricted keys cannot create unrestricted keys"
+                })),
+            ));
+        }
+        None => {}
+    }
+
+    let (key, key_prefix) = generate_api_key();
+    let scopes: Vec<String> = body
+        .scopes
+        .iter()
+        .map(|scope| scope.as_str().to_string())
+        .collect();
+
+    let query_result = &data
+        .db
+        .insert_api_key(
+            &body.name,
+            &key_prefix,
+            &hash_api_key(&key),
+            &scopes,
+            body.repositories.as_deref(),
+        )
+        .await;
+
+    match query_result {
+        Ok(Some(record)) => {
+            info!(
+                "Api key {} ({}) created by {}",
+                record.id, record.name, auth.subject
+            );
+            Ok(Json(json!({
+                "status": "success",
+                "message": "Api key created successfully",
+                "data": {
+                    "key": key,
+                    "record": record
+                }
+            })))
+        }
+        Ok(None) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "error",
+                "message": "Api key already exists"
+            })),
+        )),
+        Err(e) => {
+            error!("Failed to create api key: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Revoke an api key
+///
+/// The caller needs the owner role on every repository the key can access, and only
+/// unrestricted callers can revoke unrestricted keys
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - Extension<AuthContext> - Authenticated caller
+/// * `id` - Path<i32> - Id of the key to revoke
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
+pub async fn revoke_api_key(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let record = match data.db.get_api_key_by_id(id).await {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            return Err((
+                StatusCode::NOT_FOUND,
+                Json(json!({
+                    "status": "error",
+                    "message": "Api key not found"
+                })),
+            ))
+        }
+        Err(e) => {
+            error!("Failed to get api key {}: {:?}", id, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ));
+        }
+    };
+
+    // the caller must be able to create the key it is revoking
+    match &record.repositories {
+        Some(repositories) => {
+            for repository in repositories.iter() {
+                auth.authorize(repository, Role::Owner)?;
+            }
+        }
+        None if auth.repositories.is_some() => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(json!({
+                    "status": "error",
+                    "message": "Repository restricted keys cannot revoke unrestricted keys"
+                })),
+            ));
+        }
+        None => {}
+    }
+
+    let query_result = &data.db.revoke_api_key(id).await;
+
+    match query_result {
+        Ok(true) => {
+            info!("Api key {} revoked by {}", id, auth.subject);
+            Ok(Json(json!({
+                "status": "success",
+                "message": format!("Api key {} revoked", id)
+            })))
+        }
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Api key not found"
+            })),
+        )),
+        Err(e) => {
+            error!("Failed to revoke api key {}: {:?}", id, e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// List repository role bindings
+///
+/// Callers restricted to explicit repositories only see bindings on those repositories
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - Extension<AuthContext> - Authenticated caller
+/// * `params` - Query<RoleBindingQuery> - Optional subject and repository filters
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Matching role bindings
+pub async fn get_repository_roles(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    params: Query<RoleBindingQuery>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if let Some(repository) = &params.repository {
+        auth.authorize(repository, Role::Owner)?;
+    }
+
+    let query_result = data
+        .db
+        .get_repository_roles(params.subject.as_deref(), params.repository.as_deref())
+        .await;
+
+    match query_result {
+        Ok(mut result) => {
+            result.retain(|binding| auth.can_access(&binding.repository));
+
+            Ok(Json(json!({
+                "status": "success",
+                "data": result
+            })))
+        }
+        Err(e) => {
+            error!("Failed to query repository roles: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Assign a role to a subject on a repository
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - AuthContext - Caller assigning the role
+/// * `body` - Json<RoleBindingRequest> - Subject, repository and role
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Stored role binding
+pub async fn update_repository_role(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<RoleBindingRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&body.repository, Role::Owner)?;
+
+    let role = match body.role {
+        Some(role) => role,
+        None => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "status": "error",
+                    "message": "A role is required"
+                })),
+            ))
+        }
+    };
+
+    let query_result = &data
+        .db
+        .upsert_repository_role(&body.subject, &body.repository, role.as_str())
+        .await;
+
+    match query_result {
+        Ok(result) => {
+            info!(
+                "Assigned {} role on {} to {} by {}",
+                role.as_str(),
+                body.repository,
+                body.subject,
+                auth.subject
+            );
+            Ok(Json(json!({
+                "status": "success",
+                "data": result
+            })))
+        }
+        Err(e) => {
+            error!("Failed to update repository role: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
+        }
+    }
+}
+
+/// Remove a subject's role on a repository
+///
+/// # Arguments
+///
+/// * `data` - Arc<AppState> - Application state
+/// * `auth` - AuthContext - Caller removing the role
+/// * `body` - Json<RoleBindingRequest> - Subject and repository
+///
+/// # Returns
+///
+/// * `Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)>` - Result of the request
+pub async fn delete_repository_role(
+    State(data): State<Arc<AppState>>,
+    Extension(auth): Extension<AuthContext>,
+    Json(body): Json<RoleBindingRequest>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    auth.authorize(&body.repository, Role::Owner)?;
+
+    let query_result = &data
+        .db
+        .delete_repository_role(&body.subject, &body.repository)
+        .await;
+
+    match query_result {
+        Ok(true) => {
+            info!(
+                "Removed role on {} from {} by {}",
+                body.repository, body.subject, auth.subject
+            );
+            Ok(Json(json!({
+                "status": "success",
+                "message": format!("Role removed for {} on {}", body.subject, body.repository)
+            })))
+        }
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "status": "error",
+                "message": "Role binding not found"
+            })),
+        )),
+        Err(e) => {
+            error!("Failed to delete repository role: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(json!({
+                    "status": "error",
+                    "message": format!("{:?}", e)
+                })),
+            ))
         }
     }
 }
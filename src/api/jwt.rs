@@ -0,0 +1,425 @@
+use crate::api::auth::{AuthContext, Scope};
+use anyhow::{anyhow, Context};
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tracing::{error, info};
+
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 300;
+const DEFAULT_SCOPES_CLAIM: &str = "scope";
+const DEFAULT_REPOSITORIES_CLAIM: &str = "repositories";
+
+/// Claim value granting access to every repository
+const ALL_REPOSITORIES: &str = "*";
+
+#[derive(Debug, Clone)]
+pub enum JwksSource {
+    File(PathBuf),
+    Url(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub source: JwksSource,
+    pub issuer: Option<String>,
+    pub audience: Option<String>,
+    pub refresh_interval: Duration,
+    // claim holding the granted scopes, either a space separated string or an array
+    pub scopes_claim: String,
+    // optional prefix stripped from scope values (e.g. "scouter:" for "scouter:read")
+    pub scope_prefix: String,
+    // claim holding the repositories the token can access
+    pub repositories_claim: String,
+}
+
+impl JwtConfig {
+    pub fn new(source: JwksSource) -> Self {
+        Self {
+            source,
+            issuer: None,
+            audience: None,
+            refresh_interval: Duration::from_secs(DEFAULT_REFRESH_INTERVAL_SECS),
+            scopes_claim: DEFAULT_SCOPES_CLAIM.to_string(),
+            scope_prefix: String::new(),
+            repositories_claim: DEFAULT_REPOSITORIES_CLAIM.to_string(),
+        }
+    }
+
+    /// Load JWT settings from the environment.
+    /// Returns None unless SCOUTER_JWKS_URL or SCOUTER_JWKS_PATH is set
+    pub fn from_env() -> Option<Self> {
+        let source = if let Ok(url) = std::env::var("SCOUTER_JWKS_URL") {
+            JwksSource::Url(url)
+        } else if let Ok(path) = std::env::var("SCOUTER_JWKS_PATH") {
+            JwksSource::File(PathBuf::from(path))
+        } else {
+            return None;
+        };
+
+        let mut config = Self::new(source);
+        config.issuer = std::env::var("SCOUTER_JWT_ISSUER").ok();
+        config.audience = std::env::var("SCOUTER_JWT_AUDIENCE").ok();
+
+        if let Some(secs) = std::env::var("SCOUTER_JWKS_REFRESH_SECS")
+            .ok()
+            .and_then(|secs| secs.parse::<u64>().ok())
+        {
+            config.refresh_interval = Duration::from_secs(secs);
+        }
+
+        if let Ok(claim) = std::env::var("SCOUTER_JWT_SCOPES_CLAIM") {
+            config.scopes_claim = claim;
+        }
+
+        if let Ok(prefix) = std::env::var("SCOUTER_JWT_SCOPE_PREFIX") {
+            config.scope_prefix = prefix;
+        }
+
+        if let Ok(claim) = std::env::var("SCOUTER_JWT_REPOSITORIES_CLAIM") {
+            config.repositories_claim = claim;
+        }
+
+        Some(config)
+    }
+}
+
+#[derive(Clone)]
+struct VerificationKey {
+    kid: Option<String>,
+    algorithm: Option<Algorithm>,
+    key: DecodingKey,
+}
+
+/// Validates bearer tokens against a JWKS document that is periodically reloaded
+#[derive(Clone)]
+pub struct JwtValidator {
+    config: JwtConfig,
+    keys: Arc<RwLock<Vec<VerificationKey>>>,
+}
+
+impl JwtValidator {
+    /// Create a validator and load the initial key set
+    pub async fn new(config: JwtConfig) -> Result<Self, anyhow::Error> {
+        let validator = Self {
+            config,
+            keys: Arc::new(RwLock::new(Vec::new())),
+        };
+
+        validator.refresh().await?;
+
+        Ok(validator)
+    }
+
+    async fn load_jwks(&self) -> Result<JwkSet, anyhow::Error> {
+        match &self.config.source {
+            JwksSource::File(path) => {
+                let contents = tokio::fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("Failed to read JWKS file: {:?}", path))?;
+
+                serde_json::from_str(&contents).with_context(|| "Failed to parse JWKS file")
+            }
+            JwksSource::Url(url) => reqwest::get(url)
+                .await
+                .with_context(|| format!("Failed to fetch JWKS from {}", url))?
+                .error_for_status()?
+                .json::<JwkSet>()
+                .await
+                .with_context(|| "Failed to parse JWKS response"),
+        }
+    }
+
+    /// Reload the key set. Returns the number of usable keys
+    pub async fn refresh(&self) -> Result<usize, anyhow::Error> {
+        let jwks = self.load_jwks().await?;
+
+        let keys: Vec<VerificationKey> = jwks
+            .keys
+            .iter()
+            .filter_map(|jwk| match verification_key(jwk) {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    error!("Skipping JWK {:?}: {:?}", jwk.common.key_id, e);
+                    None
+                }
+            })
+            .collect();
+
+        if keys.is_empty() {
+            return Err(anyhow!("JWKS does not contain any usable keys"));
+        }
+
+        let count = keys.len();
+        *self.keys.write().map_err(|e| anyhow!("{:?}", e))? = keys;
+
+        Ok(count)
+    }
+
+    pub fn start_refresh_task(&self) {
+        let validator = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(validator.config.refresh_interval);
+            // the first tick completes immediately and the keys were loaded on creation
+            interval.tick().await;
+
+            loop {
+                interval.tick().await;
+                match validator.refresh().await {
+                    Ok(count) => info!("Refreshed JWKS with {} keys", count),
+                    Err(e) => error!("Failed to refresh JWKS: {:?}", e),
+                }
+            }
+        });
+    }
+
+    /// Validate a token and map its claims to an AuthContext
+    pub fn validate(&self, token: &str) -> Result<AuthContext, anyhow::Error> {
+        let header = decode_header(token).with_context(|| "Invalid token header")?;
+
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err(anyhow!("Symmetric token algorithms are not supported"));
+        }
+
+        let key = {
+            let keys = self.keys.read().map_err(|e| anyhow!("{:?}", e))?;
+
+            let key = match &header.kid {
+                Some(kid) => keys.iter().find(|key| key.kid.as_ref() == Some(kid)),
+                None if keys.len() == 1 => keys.first(),
+                None => None,
+            }
+            .ok_or_else(|| anyhow!("No matching key for token kid {:?}", header.kid))?;
+
+            if key.algorithm.is_some_and(|alg| alg != header.alg) {
+                return Err(anyhow!("Token algorithm does not match key algorithm"));
+            }
+
+            key.key.clone()
+        };
+
+        let mut validation = Validation::new(header.alg);
+        match &self.config.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+        if let Some(issuer) = &self.config.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        let claims = decode::<Map<String, Value>>(token, &key, &validation)
+            .with_context(|| "Failed to validate token")?
+            .claims;
+
+        self.map_claims(&claims)
+    }
+
+    fn map_claims(&self, claims: &Map<String, Value>) -> Result<AuthContext, anyhow::Error> {
+        // the subject identifies the caller in audit logs, so tokens must carry one
+        let subject = claims
+            .get("sub")
+            .and_then(|sub| sub.as_str())
+            .filter(|sub| !sub.is_empty())
+            .ok_or_else(|| anyhow!("Token has no subject"))?;
+
+        let scopes = claim_values(claims.get(&self.config.scopes_claim))
+            .iter()
+            .filter_map(|scope| {
+                scope
+                    .strip_prefix(&self.config.scope_prefix)
+                    .and_then(|scope| Scope::from_str(scope).ok())
+            })
+            .collect();
+
+        // tokens without the repositories claim are not granted any repository
+        let repositories = claim_values(claims.get(&self.config.repositories_claim));
+        let repositories = if repositories.iter().any(|repo| repo == ALL_REPOSITORIES) {
+            None
+        } else {
+            Some(repositories)
+        };
+
+        Ok(AuthContext {
+            subject: format!("jwt:{}", subject),
+            scopes,
+            repositories,
+            roles: HashMap::new(),
+        })
+    }
+}
+
+fn verification_key(jwk: &Jwk) -> Result<VerificationKey, anyhow::Error> {
+    let algorithm = match jwk.common.key_algorithm {
+        Some(alg) => Some(
+            Algorithm::from_str(&alg.to_string())
+                .map_err(|_| anyhow!("Unsupported key algorithm {}", alg))?,
+        ),
+        None => None,
+    };
+
+    Ok(VerificationKey {
+        kid: jwk.common.key_id.clone(),
+        algorithm,
+        key: DecodingKey::from_jwk(jwk)?,
+    })
+}
+
+/// Claims may be a space separated string or an array of strings
+fn claim_values(claim: Option<&Value>) -> Vec<String> {
+    match claim {
+        Some(Value::String(value)) => value.split_whitespace().map(String::from).collect(),
+        Some(Value::Array(values)) => values
+            .iter()
+            .filter_map(|value| value.as_str().map(String::from))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Api keys never contain dots, so anything shaped like header.payload.signature is a JWT
+pub fn is_jwt(token: &str) -> bool {
+    token.split('.').count() == 3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const PRIVATE_KEY: &[u8] = include_bytes!("../../tests/fixtures/jwt/private_key.pem");
+    const JWKS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jwt/jwks.json");
+
+    fn sign(claims: Value, kid: Option<&str>) -> String {
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = kid.map(String::from);
+
+        encode(
+            &header,
+            &claims,
+            &EncodingKey::from_rsa_pem(PRIVATE_KEY).unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn expiry() -> i64 {
+        chrono::Utc::now().timestamp() + 600
+    }
+
+    async fn validator() -> JwtValidator {
+        let mut config = JwtConfig::new(JwksSource::File(PathBuf::from(JWKS_PATH)));
+        config.issuer = Some("https://sso.example.com".to_string());
+        config.scope_prefix = "scouter:".to_string();
+
+        JwtValidator::new(config).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_validate_token() {
+        let validator = validator().await;
+
+        let token = sign(
+            json!({
+                "sub": "user@example.com",
+                "iss": "https://sso.example.com",
+                "exp": expiry(),
+                "scope": "openid scouter:read scouter:ingest",
+                "repositories": ["team-a", "team-b"],
+            }),
+            Some("scouter-test"),
+        );
+
+        assert!(is_jwt(&token));
+
+        let context = validator.validate(&token).unwrap();
+        assert_eq!(context.subject, "jwt:user@example.com");
+        assert!(context.has_scope(Scope::Read));
+        assert!(context.has_scope(Scope::Ingest));
+        assert!(!context.has_scope(Scope::Admin));
+        assert!(context.can_access("team-a"));
+        assert!(!context.can_access("team-c"));
+
+        // wildcard repositories claim grants every repository
+        let token = sign(
+            json!({
+                "sub": "admin@example.com",
+                "iss": "https://sso.example.com",
+                "exp": expiry(),
+                "scope": ["scouter:admin"],
+                "repositories": "*",
+            }),
+            None,
+        );
+
+        let context = validator.validate(&token).unwrap();
+        assert!(context.has_scope(Scope::Admin));
+        assert!(context.can_access("team-c"));
+    }
+
+    #[tokio::test]
+    async fn test_reject_invalid_tokens() {
+        let validator = validator().await;
+
+        // wrong issuer
+        let token = sign(
+            json!({"sub": "user", "iss": "https://other.example.com", "exp": expiry()}),
+            Some("scouter-test"),
+        );
+        assert!(validator.validate(&token).is_err());
+
+        // expired
+        let token = sign(
+            json!({"sub": "user", "iss": "https://sso.example.com", "exp": 1000}),
+            Some("scouter-test"),
+        );
+        assert!(validator.validate(&token).is_err());
+
+        // unknown kid
+        let token = sign(
+            json!({"sub": "user", "iss": "https://sso.example.com", "exp": expiry()}),
+            Some("unknown"),
+        );
+        assert!(validator.validate(&token).is_err());
+
+        // tokens without a subject
+        let token = sign(
+            json!({"iss": "https://sso.example.com", "exp": expiry(), "scope": "scouter:read"}),
+            Some("scouter-test"),
+        );
+        assert!(validator.validate(&token).is_err());
+
+        // hmac tokens are never accepted
+        let token = encode(
+            &Header::new(Algorithm::HS256),
+            &json!({"sub": "user", "iss": "https://sso.example.com", "exp": expiry()}),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+        assert!(validator.validate(&token).is_err());
+
+        // payload swapped under a valid signature
+        let token = sign(
+            json!({"sub": "user", "iss": "https://sso.example.com", "exp": expiry(), "scope": "scouter:read"}),
+            Some("scouter-test"),
+        );
+        let escalated = sign(
+            json!({"sub": "user", "iss": "https://sso.example.com", "exp": expiry(), "scope": "scouter:admin"}),
+            Some("scouter-test"),
+        );
+        let parts: Vec<&str> = token.split('.').collect();
+        let escalated_parts: Vec<&str> = escalated.split('.').collect();
+        let tampered = format!("{}.{}.{}", parts[0], escalated_parts[1], parts[2]);
+        assert!(validator.validate(&tampered).is_err());
+    }
+}
@@ -1,4 +1,6 @@
+pub mod auth;
 pub mod handler;
+pub mod jwt;
 pub mod metrics;
 pub mod route;
 pub mod schema;
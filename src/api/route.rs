@@ -1,6 +1,12 @@
+use crate::api::auth::{authenticate, AuthConfig};
 use crate::api::handler::{
-    get_drift, get_drift_alerts, get_observability_metrics, get_profile, health_check,
-    insert_drift, insert_drift_profile, update_drift_profile_status,
+    backtest_drift_profile, check_drift_sample, create_api_key, delete_drift_profile,
+    delete_repository_role, get_drift, get_drift_alert_history, get_drift_alerts, get_features,
+    get_observability_metrics, get_profile, get_profile_runs, get_purge_job, get_repository_roles,
+    health_check, insert_drift, insert_drift_profile, insert_observability_metrics,
+    insert_observability_profile, list_profiles, revoke_api_key, run_drift_profile,
+    update_drift_alert_status, update_drift_profile_catch_up, update_drift_profile_status,
+    update_observability_profile, update_repository_role,
 };
 use crate::api::metrics::track_metrics;
 use crate::sql::postgres::PostgresClient;
@@ -10,7 +16,7 @@ use axum::http::{
 };
 use axum::middleware;
 use axum::{
-    routing::{get, post, put},
+    routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -18,10 +24,11 @@ use tower_http::cors::CorsLayer;
 
 use super::handler::update_drift_profile;
 
-const ROUTE_PREFIX: &str = "/scouter";
+pub const ROUTE_PREFIX: &str = "/scouter";
 
 pub struct AppState {
     pub db: PostgresClient,
+    pub auth: AuthConfig,
 }
 
 pub fn create_router(app_state: Arc<AppState>) -> Router {
@@ -36,21 +43,75 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
             &format!("{}/drift", ROUTE_PREFIX),
             get(get_drift).post(insert_drift),
         )
+        .route(
+            &format!("{}/drift/check", ROUTE_PREFIX),
+            post(check_drift_sample),
+        )
         .route(
             &format!("{}/profile", ROUTE_PREFIX),
             post(insert_drift_profile)
                 .put(update_drift_profile)
-                .get(get_profile),
+                .get(get_profile)
+                .delete(delete_drift_profile),
+        )
+        .route(
+            &format!("{}/profile/purge/:id", ROUTE_PREFIX),
+            get(get_purge_job),
+        )
+        .route(
+            &format!("{}/profile/runs", ROUTE_PREFIX),
+            get(get_profile_runs),
         )
+        .route(
+            &format!("{}/profile/run", ROUTE_PREFIX),
+            post(run_drift_profile),
+        )
+        .route(
+            &format!("{}/profile/backtest", ROUTE_PREFIX),
+            post(backtest_drift_profile),
+        )
+        .route(&format!("{}/profiles", ROUTE_PREFIX), get(list_profiles))
+        .route(&format!("{}/features", ROUTE_PREFIX), get(get_features))
         .route(
             &format!("{}/profile/status", ROUTE_PREFIX),
             put(update_drift_profile_status),
         )
+        .route(
+            &format!("{}/profile/catch_up", ROUTE_PREFIX),
+            put(update_drift_profile_catch_up),
+        )
         .route(&format!("{}/alerts", ROUTE_PREFIX), get(get_drift_alerts))
+        .route(
+            &format!("{}/alerts/:id", ROUTE_PREFIX),
+            put(update_drift_alert_status),
+        )
+        .route(
+            &format!("{}/alerts/:id/history", ROUTE_PREFIX),
+            get(get_drift_alert_history),
+        )
         .route(
             &format!("{}/observability/metrics", ROUTE_PREFIX),
-            get(get_observability_metrics),
+            get(get_observability_metrics).post(insert_observability_metrics),
+        )
+        .route(
+            &format!("{}/observability/profile", ROUTE_PREFIX),
+            post(insert_observability_profile).put(update_observability_profile),
+        )
+        .route(&format!("{}/auth/keys", ROUTE_PREFIX), post(create_api_key))
+        .route(
+            &format!("{}/auth/keys/:id", ROUTE_PREFIX),
+            delete(revoke_api_key),
+        )
+        .route(
+            &format!("{}/auth/roles", ROUTE_PREFIX),
+            get(get_repository_roles)
+                .put(update_repository_role)
+                .delete(delete_repository_role),
         )
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            authenticate,
+        ))
         .route_layer(middleware::from_fn(track_metrics))
         .with_state(app_state)
         .layer(cors)
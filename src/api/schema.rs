@@ -1,14 +1,71 @@
-use scouter::core::drift::base::DriftType;
+use crate::alerts::catch_up::CatchUpPolicy;
+use crate::api::auth::{Role, Scope};
+use crate::consumer::base::{IngestRecord, IngestRecords};
+use crate::types::TimeInterval;
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use scouter::core::drift::base::{DriftType, RecordType};
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// Window used when neither a time_window nor a start timestamp is provided
+const DEFAULT_TIME_WINDOW: &str = "6hour";
+
+// Upper bound on bins per query, gap filled series return every one of them
+const MAX_DATA_POINTS: i32 = 10_000;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DriftRequest {
     pub name: String,
     pub repository: String,
     pub version: String,
-    pub time_window: String,
+    // relative window ending at `end` (or now), e.g. 24hour. Ignored when start is set
+    pub time_window: Option<String>,
+    // ISO-8601 timestamps bounding the query
+    pub start: Option<String>,
+    pub end: Option<String>,
     pub max_data_points: i32,
+    // how values in each bin are reduced, defaults to avg
+    pub aggregation: Option<Aggregation>,
+    // also return the min and max of each bin
+    pub bands: Option<bool>,
+    // return every bin in the range, with null values for bins without records
+    pub fill_gaps: Option<bool>,
+    // comma separated features to return, all features when not set
+    pub features: Option<String>,
+}
+
+/// Reduction applied to the values in each time bin
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    #[default]
+    Avg,
+    Min,
+    Max,
+    Median,
+    P05,
+    P95,
+    Count,
+    Stddev,
+}
+
+impl Aggregation {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Aggregation::Avg => "avg(value)",
+            Aggregation::Min => "min(value)",
+            Aggregation::Max => "max(value)",
+            Aggregation::Median => "percentile_cont(0.5) WITHIN GROUP (ORDER BY value)",
+            Aggregation::P05 => "percentile_cont(0.05) WITHIN GROUP (ORDER BY value)",
+            Aggregation::P95 => "percentile_cont(0.95) WITHIN GROUP (ORDER BY value)",
+            Aggregation::Count => "count(value)::double precision",
+            // a bin with a single value has no sample deviation
+            Aggregation::Stddev => "coalesce(stddev_samp(value), 0)",
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -23,16 +80,172 @@ pub struct ProfileStatusRequest {
     pub repository: String,
     pub version: String,
     pub active: bool,
+    // limits the update to the service's profile of this drift type (e.g. SPC, OBSERVABILITY)
+    pub drift_type: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfileCatchUpRequest {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    pub catch_up: CatchUpPolicy,
+    // limits the update to the service's profile of this drift type (e.g. SPC, OBSERVABILITY)
+    pub drift_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct DriftAlertRequest {
     pub name: String,
     pub repository: String,
     pub version: String,
+    // lower bound (inclusive) on created_at
     pub limit_timestamp: Option<String>,
+    // upper bound (exclusive) on created_at
+    pub end_timestamp: Option<String>,
     pub active: Option<bool>,
     pub limit: Option<i32>,
+    pub feature: Option<String>,
+    pub status: Option<String>,
+    pub zone: Option<String>,
+    pub kind: Option<String>,
+    // opaque cursor returned as next_cursor by the previous page
+    pub cursor: Option<String>,
+}
+
+impl DriftAlertRequest {
+    /// Parse the optional created_at bounds of the request
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(Option<NaiveDateTime>, Option<NaiveDateTime>)>` - Lower and upper bound
+    pub fn time_bounds(
+        &self,
+    ) -> Result<(Option<NaiveDateTime>, Option<NaiveDateTime>), anyhow::Error> {
+        let limit_timestamp = self
+            .limit_timestamp
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()?;
+        let end_timestamp = self
+            .end_timestamp
+            .as_deref()
+            .map(parse_timestamp)
+            .transpose()?;
+
+        Ok((limit_timestamp, end_timestamp))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProfileListRequest {
+    pub repository: Option<String>,
+    // matches profiles whose name starts with this value
+    pub name: Option<String>,
+    pub drift_type: Option<String>,
+    pub active: Option<bool>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetProfileRequest {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    // selects the service's profile of this drift type (e.g. SPC, OBSERVABILITY)
+    pub drift_type: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfileRunRequest {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DriftRunRequest {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    // relative window ending at `end` (or now), e.g. 24hour
+    pub time_window: Option<String>,
+    // ISO-8601 timestamps bounding the run
+    pub start: Option<String>,
+    pub end: Option<String>,
+    // compute drift and alerts without inserting or dispatching them
+    pub dry_run: Option<bool>,
+    // selects the service's profile of this drift type (e.g. SPC, OBSERVABILITY)
+    pub drift_type: Option<String>,
+}
+
+impl DriftRunRequest {
+    /// Resolve the window the run evaluates
+    pub fn time_range(&self) -> Result<TimeRange, anyhow::Error> {
+        TimeRange::resolve(
+            self.time_window.as_deref(),
+            self.start.as_deref(),
+            self.end.as_deref(),
+            1,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BacktestRequest {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    // relative range ending at `end` (or now), e.g. 5day
+    pub time_window: Option<String>,
+    // ISO-8601 timestamps bounding the replayed range
+    pub start: Option<String>,
+    pub end: Option<String>,
+    // size of each replayed window, e.g. 1hour
+    pub window: String,
+    // profile to replay instead of the stored one, e.g. with a tuned alert rule or control limits
+    pub profile: Option<serde_json::Value>,
+    // selects the service's profile of this drift type (e.g. SPC, OBSERVABILITY)
+    pub drift_type: Option<String>,
+}
+
+impl BacktestRequest {
+    /// Resolve the range that is replayed
+    pub fn time_range(&self) -> Result<TimeRange, anyhow::Error> {
+        TimeRange::resolve(
+            self.time_window.as_deref(),
+            self.start.as_deref(),
+            self.end.as_deref(),
+            1,
+        )
+    }
+
+    /// Size of each replayed window in minutes
+    pub fn window_minutes(&self) -> Result<i32, anyhow::Error> {
+        Ok(TimeInterval::from_str(&self.window)?.to_minutes())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DriftCheckRequest {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    // values of each feature, every feature needs the same number of values
+    pub features: BTreeMap<String, Vec<f64>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfileDeleteRequest {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    // also delete the service's drift records, alerts and observability metrics
+    pub purge: Option<bool>,
+    // deletes only the service's profile of this drift type (e.g. SPC, OBSERVABILITY)
+    pub drift_type: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -47,6 +260,315 @@ pub struct ObservabilityMetricRequest {
     pub name: String,
     pub repository: String,
     pub version: String,
-    pub time_window: String,
+    pub time_window: Option<String>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub max_data_points: i32,
+    // return every bin in the range, with null latencies for bins without metrics
+    pub fill_gaps: Option<bool>,
+}
+
+/// Observability metrics sent directly by a service, either a `ServerRecords` batch
+/// or a single `ServerRecord`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ObservabilityRecordsRequest {
+    Batch(IngestRecords),
+    Single(IngestRecord),
+}
+
+impl ObservabilityRecordsRequest {
+    /// Records to insert, every record must be an observability record
+    pub fn into_records(self) -> Result<IngestRecords, anyhow::Error> {
+        let records = match self {
+            ObservabilityRecordsRequest::Batch(records) => records,
+            ObservabilityRecordsRequest::Single(record) => IngestRecords {
+                record_type: RecordType::OBSERVABILITY,
+                records: vec![record],
+            },
+        };
+
+        if !matches!(records.record_type, RecordType::OBSERVABILITY)
+            || records
+                .records
+                .iter()
+                .any(|record| !matches!(record, IngestRecord::Observability { .. }))
+        {
+            return Err(anyhow!(
+                "Expected OBSERVABILITY records, other record types belong on /scouter/drift"
+            ));
+        }
+
+        Ok(records)
+    }
+}
+
+impl DriftRequest {
+    pub fn feature_list(&self) -> Vec<String> {
+        self.features
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|feature| !feature.is_empty())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn time_range(&self) -> Result<TimeRange, anyhow::Error> {
+        TimeRange::resolve(
+            self.time_window.as_deref(),
+            self.start.as_deref(),
+            self.end.as_deref(),
+            self.max_data_points,
+        )
+    }
+}
+
+impl ObservabilityMetricRequest {
+    pub fn time_range(&self) -> Result<TimeRange, anyhow::Error> {
+        TimeRange::resolve(
+            self.time_window.as_deref(),
+            self.start.as_deref(),
+            self.end.as_deref(),
+            self.max_data_points,
+        )
+    }
+}
+
+/// Parse an ISO-8601 timestamp. Timestamps with an offset are converted to utc,
+/// timestamps without one are assumed to already be utc
+pub fn parse_timestamp(value: &str) -> Result<NaiveDateTime, anyhow::Error> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.naive_utc());
+    }
+
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Invalid timestamp: {}. Expected an ISO-8601 timestamp",
+                value
+            )
+        })
+}
+
+/// Time range and bin size for a binned drift or observability query
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeRange {
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
     pub max_data_points: i32,
 }
+
+impl TimeRange {
+    /// Resolve a query range from either an explicit start or a relative time window
+    ///
+    /// # Arguments
+    ///
+    /// * `time_window` - Relative window ending at `end`, used when no start is provided
+    /// * `start` - ISO-8601 start of the range
+    /// * `end` - ISO-8601 end of the range, defaults to now
+    /// * `max_data_points` - Number of bins the range is split into
+    ///
+    /// # Returns
+    ///
+    /// * `Result<TimeRange>` - Validated time range
+    pub fn resolve(
+        time_window: Option<&str>,
+        start: Option<&str>,
+        end: Option<&str>,
+        max_data_points: i32,
+    ) -> Result<Self, anyhow::Error> {
+        if max_data_points <= 0 || max_data_points > MAX_DATA_POINTS {
+            return Err(anyhow!(
+                "max_data_points must be between 1 and {}",
+                MAX_DATA_POINTS
+            ));
+        }
+
+        let end = match end {
+            Some(end) => parse_timestamp(end)?,
+            None => Utc::now().naive_utc(),
+        };
+
+        let start = match (start, time_window) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!("Provide either start or time_window, not both"));
+            }
+            (Some(start), None) => parse_timestamp(start)?,
+            (None, time_window) => {
+                let minutes = TimeInterval::from_str(time_window.unwrap_or(DEFAULT_TIME_WINDOW))?
+                    .to_minutes();
+                end - chrono::Duration::minutes(minutes as i64)
+            }
+        };
+
+        if start >= end {
+            return Err(anyhow!("start must be before end"));
+        }
+
+        Ok(TimeRange {
+            start,
+            end,
+            max_data_points,
+        })
+    }
+
+    /// Width of each bin in seconds
+    pub fn bin_seconds(&self) -> f64 {
+        (self.end - self.start).num_milliseconds() as f64 / 1000.0 / self.max_data_points as f64
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub repositories: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoleBindingRequest {
+    pub subject: String,
+    pub repository: String,
+    // required when assigning a role, ignored when removing one
+    pub role: Option<Role>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RoleBindingQuery {
+    pub subject: Option<String>,
+    pub repository: Option<String>,
+}
+
+/// Lifecycle transitions for drift alerts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertAction {
+    Acknowledge,
+    Resolve,
+    Reopen,
+}
+
+impl AlertAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertAction::Acknowledge => "acknowledge",
+            AlertAction::Resolve => "resolve",
+            AlertAction::Reopen => "reopen",
+        }
+    }
+
+    /// Status the alert moves to
+    pub fn status(&self) -> &'static str {
+        match self {
+            AlertAction::Acknowledge => "acknowledged",
+            AlertAction::Resolve => "resolved",
+            AlertAction::Reopen => "active",
+        }
+    }
+
+    /// Statuses the alert must currently be in for the transition to apply
+    pub fn allowed_from(&self) -> &'static [&'static str] {
+        match self {
+            AlertAction::Acknowledge => &["active"],
+            AlertAction::Resolve => &["active", "acknowledged"],
+            AlertAction::Reopen => &["acknowledged", "resolved"],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlertStatusRequest {
+    pub action: AlertAction,
+    // defaults to the authenticated subject
+    pub actor: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamp() {
+        let expected = NaiveDate::from_ymd_opt(2024, 11, 1)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap();
+
+        assert_eq!(parse_timestamp("2024-11-01T12:30:00").unwrap(), expected);
+        assert_eq!(parse_timestamp("2024-11-01 12:30:00").unwrap(), expected);
+        assert_eq!(parse_timestamp("2024-11-01T12:30:00Z").unwrap(), expected);
+        assert_eq!(
+            parse_timestamp("2024-11-01T14:30:00+02:00").unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_timestamp("2024-11-01").unwrap(),
+            expected.date().and_hms_opt(0, 0, 0).unwrap()
+        );
+        assert!(parse_timestamp("last week").is_err());
+    }
+
+    #[test]
+    fn test_time_range() {
+        let range = TimeRange::resolve(
+            None,
+            Some("2024-11-01T00:00:00Z"),
+            Some("2024-11-02T00:00:00Z"),
+            1440,
+        )
+        .unwrap();
+        assert_eq!(range.bin_seconds(), 60.0);
+
+        // relative windows end at `end`
+        let range =
+            TimeRange::resolve(Some("1hour"), None, Some("2024-11-01T01:00:00"), 60).unwrap();
+        assert_eq!(range.start, parse_timestamp("2024-11-01T00:00:00").unwrap());
+        assert_eq!(range.bin_seconds(), 60.0);
+
+        let range = TimeRange::resolve(None, None, None, 360).unwrap();
+        assert_eq!((range.end - range.start).num_minutes(), 360);
+
+        assert!(TimeRange::resolve(Some("1hours"), None, None, 100).is_err());
+        assert!(TimeRange::resolve(Some("1hour"), Some("2024-11-01"), None, 100).is_err());
+        assert!(TimeRange::resolve(None, Some("2024-11-02"), Some("2024-11-01"), 100).is_err());
+        assert!(TimeRange::resolve(Some("1hour"), None, None, 0).is_err());
+        assert!(TimeRange::resolve(Some("1hour"), None, None, 10_001).is_err());
+    }
+
+    #[test]
+    fn test_feature_list() {
+        let mut request: DriftRequest = serde_json::from_value(serde_json::json!({
+            "name": "test_app",
+            "repository": "test",
+            "version": "1.0.0",
+            "max_data_points": 100,
+        }))
+        .unwrap();
+        assert!(request.feature_list().is_empty());
+
+        request.features = Some("feature0, feature2,,".to_string());
+        assert_eq!(request.feature_list(), vec!["feature0", "feature2"]);
+    }
+
+    #[test]
+    fn test_aggregation() {
+        let aggregation: Aggregation = serde_json::from_str("\"p95\"").unwrap();
+        assert_eq!(aggregation, Aggregation::P95);
+        assert_eq!(
+            aggregation.as_sql(),
+            "percentile_cont(0.95) WITHIN GROUP (ORDER BY value)"
+        );
+        assert_eq!(Aggregation::default().as_sql(), "avg(value)");
+        assert!(serde_json::from_str::<Aggregation>("\"mode\"").is_err());
+    }
+}
@@ -9,7 +9,7 @@ const DEFAULT_TIME_PATTERN: &str =
     "[year]-[month]-[day]T[hour repr:24]:[minute]:[second]::[subsecond digits:4]";
 
 pub async fn setup_logging() -> Result<(), anyhow::Error> {
-    let time_format = time::format_description::parse(DEFAULT_TIME_PATTERN).unwrap();
+    let time_format = time::format_description::parse_borrowed::<1>(DEFAULT_TIME_PATTERN).unwrap();
 
     tracing_subscriber::fmt()
         .json()
@@ -25,7 +25,6 @@ pub async fn setup_logging() -> Result<(), anyhow::Error> {
 }
 
 /// Setup the application with the given database pool.
-
 pub async fn create_db_pool(database_url: Option<String>) -> Result<Pool<Postgres>, anyhow::Error> {
     // get env var
     let database_url = match database_url {
@@ -1,23 +1,60 @@
+use crate::alerts::psi::types::PsiServerRecord;
+use crate::consumer::bulk::{batch_size_from_env, RecordBuffer};
+use crate::observe::record::ObservabilityRecord;
 use crate::sql::postgres::PostgresClient;
+use crate::sql::schema::BatchInsertResult;
 use anyhow::*;
-use scouter::core::drift::base::{RecordType, ServerRecord, ServerRecords};
+use scouter::core::drift::base::RecordType;
 use scouter::core::drift::spc::types::SpcServerRecord;
-use scouter::core::observe::observer::ObservabilityMetrics;
+use serde::Deserialize;
 use std::result::Result::Ok;
 use tracing::error;
 
+/// Server record as received over http, kafka or rabbitmq
+///
+/// Mirrors `ServerRecord`, but observability records also keep the `created_at` and
+/// per route `latency_sketch` sent by clients
+#[derive(Debug, Clone, Deserialize)]
+pub enum IngestRecord {
+    #[serde(rename = "SPC")]
+    Spc { record: SpcServerRecord },
+    #[serde(rename = "OBSERVABILITY")]
+    Observability { record: ObservabilityRecord },
+    #[serde(rename = "PSI")]
+    Psi { record: PsiServerRecord },
+}
+
+impl IngestRecord {
+    /// Repository the record belongs to
+    pub fn repository(&self) -> &str {
+        match self {
+            IngestRecord::Spc { record } => &record.repository,
+            IngestRecord::Observability { record } => &record.metrics.repository,
+            IngestRecord::Psi { record } => &record.repository,
+        }
+    }
+}
+
+/// Batch of server records as received over http, kafka or rabbitmq. Mirrors `ServerRecords`
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngestRecords {
+    pub record_type: RecordType,
+    pub records: Vec<IngestRecord>,
+}
+
 pub trait ToDriftRecords {
     fn to_spc_drift_records(&self) -> Result<Vec<SpcServerRecord>>;
-    fn to_observability_drift_records(&self) -> Result<Vec<ObservabilityMetrics>>;
+    fn to_observability_drift_records(&self) -> Result<Vec<ObservabilityRecord>>;
+    fn to_psi_drift_records(&self) -> Result<Vec<PsiServerRecord>>;
 }
-impl ToDriftRecords for ServerRecords {
+impl ToDriftRecords for IngestRecords {
     fn to_spc_drift_records(&self) -> Result<Vec<SpcServerRecord>> {
         match self.record_type {
             RecordType::SPC => {
                 let mut records = Vec::new();
                 for record in self.records.iter() {
                     match record {
-                        ServerRecord::SPC {
+                        IngestRecord::Spc {
                             record: inner_record,
                         } => {
                             records.push(inner_record.clone());
@@ -29,19 +66,45 @@ impl ToDriftRecords for ServerRecords {
                 }
                 Ok(records)
             }
-            RecordType::OBSERVABILITY => todo!(),
-            RecordType::PSI => todo!(),
+            _ => Err(anyhow!(
+                "Unexpected record type {:?}, expected SPC",
+                self.record_type
+            )),
         }
     }
 
-    fn to_observability_drift_records(&self) -> Result<Vec<ObservabilityMetrics>> {
+    fn to_observability_drift_records(&self) -> Result<Vec<ObservabilityRecord>> {
         match self.record_type {
-            RecordType::SPC => todo!(),
             RecordType::OBSERVABILITY => {
                 let mut records = Vec::new();
                 for record in self.records.iter() {
                     match record {
-                        ServerRecord::OBSERVABILITY {
+                        IngestRecord::Observability {
+                            record: inner_record,
+                        } => {
+                            records.push(inner_record.clone());
+                        }
+                        _ => {
+                            error!("Unexpected record type");
+                        }
+                    }
+                }
+                Ok(records)
+            }
+            _ => Err(anyhow!(
+                "Unexpected record type {:?}, expected OBSERVABILITY",
+                self.record_type
+            )),
+        }
+    }
+
+    fn to_psi_drift_records(&self) -> Result<Vec<PsiServerRecord>> {
+        match self.record_type {
+            RecordType::PSI => {
+                let mut records = Vec::new();
+                for record in self.records.iter() {
+                    match record {
+                        IngestRecord::Psi {
                             record: inner_record,
                         } => {
                             records.push(inner_record.clone());
@@ -53,7 +116,10 @@ impl ToDriftRecords for ServerRecords {
                 }
                 Ok(records)
             }
-            RecordType::PSI => todo!(),
+            _ => Err(anyhow!(
+                "Unexpected record type {:?}, expected PSI",
+                self.record_type
+            )),
         }
     }
 }
@@ -63,34 +129,94 @@ pub enum MessageHandler {
 }
 
 impl MessageHandler {
-    pub async fn insert_server_records(&self, records: &ServerRecords) -> Result<()> {
+    /// Insert a batch of server records
+    ///
+    /// `Postgres` writes the batch immediately in a single transaction, rolling back on
+    /// any database error. Records that don't match the batch record type, or that already exist,
+    /// are counted as rejected.
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - Server records to insert
+    ///
+    /// # Returns
+    ///
+    /// * `Result<BatchInsertResult>` - Accepted and rejected record counts
+    pub async fn insert_server_records(
+        &self,
+        records: &IngestRecords,
+    ) -> Result<BatchInsertResult> {
         match self {
             Self::Postgres(client) => {
-                match records.record_type {
-                    RecordType::SPC => {
-                        let records = records.to_spc_drift_records()?;
-                        for record in records.iter() {
-                            let _ = client.insert_spc_drift_record(record).await.map_err(|e| {
-                                error!("Failed to insert drift record: {:?}", e);
-                            });
-                        }
-                    }
-                    RecordType::OBSERVABILITY => {
-                        let records = records.to_observability_drift_records()?;
-                        for record in records.iter() {
-                            let _ = client
-                                .insert_observability_record(record)
-                                .await
-                                .map_err(|e| {
-                                    error!("Failed to insert observability record: {:?}", e);
-                                });
-                        }
-                    }
-                    RecordType::PSI => todo!(),
-                };
+                let mut buffer = RecordBuffer::default();
+                buffer.push(records)?;
+
+                let accepted = buffer.write(client, batch_size_from_env()).await?;
+
+                Ok(BatchInsertResult {
+                    accepted,
+                    rejected: records.records.len() - accepted,
+                })
             }
         }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observe::sketch::LatencySketch;
+    use scouter::core::drift::base::{ServerRecord, ServerRecords};
+    use scouter::core::observe::observer::{LatencyMetrics, ObservabilityMetrics, RouteMetrics};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_ingest_records() {
+        let metrics = ObservabilityMetrics {
+            name: "test_app".to_string(),
+            repository: "test".to_string(),
+            version: "1.0.0".to_string(),
+            request_count: 1,
+            error_count: 0,
+            route_metrics: vec![RouteMetrics {
+                route_name: "/predict".to_string(),
+                metrics: LatencyMetrics {
+                    p5: 0.1,
+                    p25: 0.1,
+                    p50: 0.1,
+                    p95: 0.1,
+                    p99: 0.1,
+                },
+                request_count: 1,
+                error_count: 0,
+                error_latency: 0.0,
+                status_codes: HashMap::new(),
+            }],
+        };
+
+        let mut sketch = LatencySketch::default();
+        sketch.add(0.2, 3);
+
+        // batches are read in the client format, keeping the observability extras
+        let mut batch = serde_json::to_value(ServerRecords {
+            record_type: RecordType::OBSERVABILITY,
+            records: vec![ServerRecord::OBSERVABILITY { record: metrics }],
+        })
+        .unwrap();
+        let record = &mut batch["records"][0]["OBSERVABILITY"]["record"];
+        record["created_at"] = "2024-11-25T10:00:00".into();
+        record["route_metrics"][0]["latency_sketch"] = serde_json::to_value(&sketch).unwrap();
+
+        let records: IngestRecords = serde_json::from_value(batch).unwrap();
+        assert_eq!(records.records[0].repository(), "test");
 
-        Ok(())
+        let observability = records.to_observability_drift_records().unwrap();
+        assert_eq!(observability.len(), 1);
+        assert_eq!(
+            observability[0].created_at.to_string(),
+            "2024-11-25 10:00:00"
+        );
+        assert_eq!(observability[0].latency_sketches["/predict"], sketch);
+        assert!(records.to_spc_drift_records().is_err());
     }
 }
@@ -0,0 +1,313 @@
+use crate::alerts::psi::types::PsiServerRecord;
+use crate::consumer::base::{IngestRecords, ToDriftRecords};
+use crate::observe::record::ObservabilityRecord;
+use crate::sql::postgres::PostgresClient;
+use anyhow::*;
+use scouter::core::drift::base::RecordType;
+use scouter::core::drift::spc::types::SpcServerRecord;
+use std::result::Result::Ok;
+
+#[cfg(any(feature = "kafka", feature = "rabbitmq"))]
+pub use writer::{BulkWriter, BulkWriterConfig, FlushTicket};
+
+const DEFAULT_BATCH_SIZE: usize = 1000;
+
+// Load the maximum number of rows per insert statement from SCOUTER_BULK_BATCH_SIZE
+pub fn batch_size_from_env() -> usize {
+    std::env::var("SCOUTER_BULK_BATCH_SIZE")
+        .ok()
+        .and_then(|size| size.parse::<usize>().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(DEFAULT_BATCH_SIZE)
+}
+
+// Records waiting to be written, grouped by destination table
+#[derive(Debug, Default)]
+pub struct RecordBuffer {
+    spc: Vec<SpcServerRecord>,
+    psi: Vec<PsiServerRecord>,
+    observability: Vec<ObservabilityRecord>,
+}
+
+impl RecordBuffer {
+    pub fn len(&self) -> usize {
+        self.spc.len() + self.psi.len() + self.observability.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add server records to the buffer
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - Server records to buffer
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize>` - Number of records that were buffered
+    pub fn push(&mut self, records: &IngestRecords) -> Result<usize> {
+        let buffered = match records.record_type {
+            RecordType::SPC => {
+                let records = records.to_spc_drift_records()?;
+                let buffered = records.len();
+                self.spc.extend(records);
+                buffered
+            }
+            RecordType::OBSERVABILITY => {
+                let records = records.to_observability_drift_records()?;
+                let buffered = records.len();
+                self.observability.extend(records);
+                buffered
+            }
+            RecordType::PSI => {
+                let records = records.to_psi_drift_records()?;
+                let buffered = records.len();
+                self.psi.extend(records);
+                buffered
+            }
+        };
+
+        Ok(buffered)
+    }
+
+    /// Write all buffered records in a single transaction
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Postgres client to write with
+    /// * `batch_size` - Maximum number of rows per insert statement
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize>` - Number of records written (duplicates are skipped)
+    pub async fn write(&self, client: &PostgresClient, batch_size: usize) -> Result<usize> {
+        if self.is_empty() {
+            return Ok(0);
+        }
+
+        let mut transaction = client.pool.begin().await?;
+        let mut inserted = 0;
+
+        for chunk in self.spc.chunks(batch_size) {
+            inserted += PostgresClient::insert_spc_drift_records(&mut transaction, chunk).await?;
+        }
+
+        for chunk in self.psi.chunks(batch_size) {
+            inserted += PostgresClient::insert_psi_drift_records(&mut transaction, chunk).await?;
+        }
+
+        for chunk in self.observability.chunks(batch_size) {
+            inserted +=
+                PostgresClient::insert_observability_records(&mut transaction, chunk).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(inserted)
+    }
+}
+
+#[cfg(any(feature = "kafka", feature = "rabbitmq"))]
+mod writer {
+    use super::{batch_size_from_env, RecordBuffer};
+    use crate::consumer::base::IngestRecords;
+    use crate::sql::postgres::PostgresClient;
+    use crate::sql::schema::BatchInsertResult;
+    use anyhow::*;
+    use std::result::Result::Ok;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::{watch, Mutex};
+    use tracing::{debug, error, info, warn};
+
+    const DEFAULT_FLUSH_INTERVAL_MS: u64 = 1000;
+
+    // Writes wait for a flush once this many batches are buffered (e.g. while the database is down)
+    const MAX_BUFFERED_BATCHES: usize = 10;
+
+    #[derive(Debug, Clone)]
+    pub struct BulkWriterConfig {
+        pub batch_size: usize,
+        pub flush_interval: Duration,
+    }
+
+    impl BulkWriterConfig {
+        // Load bulk writer settings from SCOUTER_BULK_BATCH_SIZE and SCOUTER_BULK_FLUSH_INTERVAL_MS
+        pub fn from_env() -> Self {
+            let flush_interval = std::env::var("SCOUTER_BULK_FLUSH_INTERVAL_MS")
+                .ok()
+                .and_then(|interval| interval.parse::<u64>().ok())
+                .filter(|interval| *interval > 0)
+                .unwrap_or(DEFAULT_FLUSH_INTERVAL_MS);
+
+            Self {
+                batch_size: batch_size_from_env(),
+                flush_interval: Duration::from_millis(flush_interval),
+            }
+        }
+    }
+
+    impl Default for BulkWriterConfig {
+        fn default() -> Self {
+            Self {
+                batch_size: super::DEFAULT_BATCH_SIZE,
+                flush_interval: Duration::from_millis(DEFAULT_FLUSH_INTERVAL_MS),
+            }
+        }
+    }
+
+    /// Identifies the flush that writes a set of buffered records
+    ///
+    /// Records are in the database once `BulkWriter::subscribe` reports a
+    /// generation at or above the ticket
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct FlushTicket(pub u64);
+
+    #[derive(Debug, Default)]
+    struct BufferState {
+        records: RecordBuffer,
+        // number of batches taken from the buffer so far
+        generation: u64,
+    }
+
+    impl BufferState {
+        // Put records from a batch that failed to write back in front of the buffer
+        fn requeue(&mut self, mut batch: RecordBuffer) {
+            batch.spc.append(&mut self.records.spc);
+            batch.psi.append(&mut self.records.psi);
+            batch.observability.append(&mut self.records.observability);
+            self.records = batch;
+        }
+    }
+
+    // Buffers records from queue consumers and writes them in bulk
+    // once the buffer reaches the batch size or the flush interval elapses.
+    // Clones share the same buffer, so a single writer can back several consumers.
+    // Consumers acknowledge messages once the flush holding their records succeeds.
+    #[derive(Clone)]
+    pub struct BulkWriter {
+        client: PostgresClient,
+        config: BulkWriterConfig,
+        buffer: Arc<Mutex<BufferState>>,
+        // held for the whole flush so batches are written, and reported, in order
+        flush_lock: Arc<Mutex<()>>,
+        flushed: Arc<watch::Sender<u64>>,
+    }
+
+    impl BulkWriter {
+        pub fn new(client: PostgresClient, config: BulkWriterConfig) -> Self {
+            Self {
+                client,
+                config,
+                buffer: Arc::new(Mutex::new(BufferState::default())),
+                flush_lock: Arc::new(Mutex::new(())),
+                flushed: Arc::new(watch::channel(0).0),
+            }
+        }
+
+        /// Generation of the last successful flush, every ticket at or below it has been written
+        pub fn subscribe(&self) -> watch::Receiver<u64> {
+            self.flushed.subscribe()
+        }
+
+        /// Buffer server records, writing the buffer if it has reached the batch size
+        ///
+        /// A failed write keeps the records buffered for the next flush, so the records
+        /// are only acknowledged once the flush for the returned ticket succeeds
+        ///
+        /// # Arguments
+        ///
+        /// * `records` - Server records to write
+        ///
+        /// # Returns
+        ///
+        /// * `Result<(BatchInsertResult, FlushTicket)>` - Accepted (buffered) and rejected record counts
+        pub async fn write(
+            &self,
+            records: &IngestRecords,
+        ) -> Result<(BatchInsertResult, FlushTicket)> {
+            // apply backpressure instead of growing the buffer while writes are failing
+            while self.buffer.lock().await.records.len()
+                >= self.config.batch_size * MAX_BUFFERED_BATCHES
+            {
+                warn!("Bulk writer buffer is full, waiting for a flush");
+                tokio::time::sleep(self.config.flush_interval).await;
+            }
+
+            let mut buffer = self.buffer.lock().await;
+            let accepted = buffer.records.push(records)?;
+            let ticket = FlushTicket(buffer.generation + 1);
+            let full = buffer.records.len() >= self.config.batch_size;
+            drop(buffer);
+
+            if full {
+                if let Err(e) = self.flush().await {
+                    error!("Failed to flush buffered records: {:?}", e);
+                }
+            }
+
+            Ok((
+                BatchInsertResult {
+                    accepted,
+                    rejected: records.records.len() - accepted,
+                },
+                ticket,
+            ))
+        }
+
+        /// Write everything currently buffered
+        ///
+        /// If the write fails the records are put back in front of the buffer
+        ///
+        /// # Returns
+        ///
+        /// * `Result<usize>` - Number of records written
+        pub async fn flush(&self) -> Result<usize> {
+            let _flush = self.flush_lock.lock().await;
+
+            let (batch, generation) = {
+                let mut buffer = self.buffer.lock().await;
+                buffer.generation += 1;
+                (std::mem::take(&mut buffer.records), buffer.generation)
+            };
+            let buffered = batch.len();
+
+            match batch.write(&self.client, self.config.batch_size).await {
+                Ok(inserted) => {
+                    if buffered > 0 {
+                        debug!("Wrote {} of {} buffered records", inserted, buffered);
+                    }
+                    self.flushed.send_replace(generation);
+                    Ok(inserted)
+                }
+                Err(e) => {
+                    error!("Failed to write {} buffered records: {:?}", buffered, e);
+                    self.buffer.lock().await.requeue(batch);
+                    Err(e)
+                }
+            }
+        }
+
+        // Start background task that flushes the buffer every flush interval
+        pub fn start_flush_task(&self) {
+            let writer = self.clone();
+
+            info!(
+                "Starting bulk writer with batch size {} and flush interval {:?}",
+                self.config.batch_size, self.config.flush_interval
+            );
+
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(writer.config.flush_interval);
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = writer.flush().await {
+                        error!("Failed to flush buffered records: {:?}", e);
+                    }
+                }
+            });
+        }
+    }
+}
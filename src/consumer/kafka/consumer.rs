@@ -1,16 +1,15 @@
 #[cfg(feature = "kafka")]
 pub mod kafka_consumer {
-    use crate::consumer::base::MessageHandler;
+    use crate::consumer::bulk::{BulkWriter, FlushTicket};
 
+    use crate::consumer::base::IngestRecords;
     use anyhow::*;
     use rdkafka::config::ClientConfig;
-    use rdkafka::consumer::CommitMode;
     use rdkafka::consumer::Consumer;
     use rdkafka::consumer::StreamConsumer;
     use rdkafka::message::BorrowedMessage;
     use rdkafka::message::Message;
-    use scouter::core::drift::base::ServerRecords;
-    use std::collections::HashMap;
+    use std::collections::{HashMap, VecDeque};
     use std::result::Result::Ok;
     use tracing::error;
     use tracing::info;
@@ -36,7 +35,9 @@ pub mod kafka_consumer {
             .set("bootstrap.servers", brokers)
             .set("enable.partition.eof", "false")
             .set("session.timeout.ms", "6000")
-            .set("enable.auto.commit", "true");
+            .set("enable.auto.commit", "true")
+            // offsets are stored once the bulk writer has flushed the message's records
+            .set("enable.auto.offset.store", "false");
 
         if username.is_some() && password.is_some() {
             config
@@ -64,68 +65,122 @@ pub mod kafka_consumer {
         Ok(consumer)
     }
 
+    // Offset of a consumed message, stored once its records have been flushed
+    struct PendingOffset {
+        ticket: FlushTicket,
+        topic: String,
+        partition: i32,
+        offset: i64,
+    }
+
     pub async fn stream_from_kafka_topic(
-        message_handler: &MessageHandler,
+        writer: &BulkWriter,
         consumer: &StreamConsumer,
     ) -> Result<()> {
+        let mut flushed = writer.subscribe();
+        let mut pending: VecDeque<PendingOffset> = VecDeque::new();
+
         loop {
-            match consumer.recv().await {
-                Err(e) => error!("Kafka error: {}", e),
-                Ok(message) => {
-                    if let Err(e) = process_kafka_message(message_handler, consumer, &message).await
-                    {
-                        error!("Error processing Kafka message: {:?}", e);
+            tokio::select! {
+                message = consumer.recv() => match message {
+                    Err(e) => error!("Kafka error: {}", e),
+                    Ok(message) => {
+                        let ticket = match process_kafka_message(writer, &message).await {
+                            Ok(ticket) => ticket,
+                            Err(e) => {
+                                error!("Error processing Kafka message: {:?}", e);
+                                None
+                            }
+                        };
+
+                        // skipped messages are stored along with the message before them
+                        let ticket = ticket.or_else(|| pending.back().map(|pending| pending.ticket));
+
+                        match ticket {
+                            Some(ticket) => pending.push_back(PendingOffset {
+                                ticket,
+                                topic: message.topic().to_string(),
+                                partition: message.partition(),
+                                offset: message.offset(),
+                            }),
+                            None => store_offset(
+                                consumer,
+                                message.topic(),
+                                message.partition(),
+                                message.offset(),
+                            ),
+                        }
+                    }
+                },
+                changed = flushed.changed() => {
+                    changed.with_context(|| "Bulk writer stopped")?;
+                    let generation = FlushTicket(*flushed.borrow_and_update());
+
+                    while pending.front().is_some_and(|pending| pending.ticket <= generation) {
+                        if let Some(pending) = pending.pop_front() {
+                            store_offset(consumer, &pending.topic, pending.partition, pending.offset);
+                        }
                     }
                 }
             }
         }
     }
 
+    fn store_offset(consumer: &StreamConsumer, topic: &str, partition: i32, offset: i64) {
+        if let Err(e) = consumer.store_offset(topic, partition, offset) {
+            error!(
+                "Failed to store offset for {} [{}]: {:?}",
+                topic, partition, e
+            );
+        }
+    }
+
+    // Buffer the records in a message
+    //
+    // # Returns
+    //
+    // * `Result<Option<FlushTicket>>` - Ticket of the flush that writes the records, None if the message was skipped
     async fn process_kafka_message(
-        message_handler: &MessageHandler,
-        consumer: &StreamConsumer,
+        writer: &BulkWriter,
         message: &BorrowedMessage<'_>,
-    ) -> Result<()> {
+    ) -> Result<Option<FlushTicket>> {
         let payload = match message.payload_view::<str>() {
             None => {
                 error!("No payload received");
-                return Ok(());
+                return Ok(None);
             }
             Some(Ok(s)) => s,
             Some(Err(e)) => {
                 error!("Error while deserializing message payload: {:?}", e);
-                return Ok(());
+                return Ok(None);
             }
         };
 
-        let records: ServerRecords = match serde_json::from_str(payload) {
+        let records: IngestRecords = match serde_json::from_str(payload) {
             Ok(records) => records,
             Err(e) => {
                 error!("Failed to deserialize message: {:?}", e);
-                return Ok(());
+                return Ok(None);
             }
         };
 
-        match message_handler.insert_server_records(&records).await {
-            Ok(_) => {
-                consumer.commit_message(message, CommitMode::Async).unwrap();
-            }
+        match writer.write(&records).await {
+            Ok((_, ticket)) => Ok(Some(ticket)),
             Err(e) => {
                 error!("Failed to insert drift record: {:?}", e);
+                Ok(None)
             }
         }
-
-        Ok(())
     }
 
     // Start background task to poll kafka topic
     //
     // This function will poll the kafka topic and insert the records into the database
-    // using the provided message handler.
+    // using the provided bulk writer.
     //
     // # Arguments
     //
-    // * `message_handler` - The message handler to process the records
+    // * `writer` - The bulk writer to buffer the records with
     // * `group_id` - The kafka consumer group id
     // * `brokers` - The kafka brokers
     // * `topics` - The kafka topics to subscribe to
@@ -140,7 +195,7 @@ pub mod kafka_consumer {
     #[allow(clippy::unnecessary_unwrap)]
     #[allow(clippy::too_many_arguments)]
     pub async fn start_kafka_background_poll(
-        message_handler: MessageHandler,
+        writer: BulkWriter,
         group_id: String,
         brokers: String,
         topics: Vec<String>,
@@ -163,7 +218,7 @@ pub mod kafka_consumer {
         .unwrap();
 
         loop {
-            if let Err(e) = stream_from_kafka_topic(&message_handler, &consumer).await {
+            if let Err(e) = stream_from_kafka_topic(&writer, &consumer).await {
                 error!("Error in stream_from_kafka_topic: {:?}", e);
             }
         }
@@ -1,7 +1,7 @@
 #[cfg(feature = "kafka")]
 pub mod kafka_startup {
 
-    use crate::consumer::base::MessageHandler;
+    use crate::consumer::bulk::{BulkWriter, BulkWriterConfig};
     use crate::consumer::kafka::consumer::kafka_consumer::start_kafka_background_poll;
     use crate::sql::postgres::PostgresClient;
     use anyhow::*;
@@ -16,11 +16,14 @@ pub mod kafka_startup {
             .parse::<usize>()
             .with_context(|| "Failed to parse NUM_KAFKA_WORKERS")?;
 
+        // all kafka workers share a single bulk writer
+        let kafka_db_client =
+            PostgresClient::new(pool).with_context(|| "Failed to create Postgres client")?;
+        let bulk_writer = BulkWriter::new(kafka_db_client, BulkWriterConfig::from_env());
+        bulk_writer.start_flush_task();
+
         for _ in 0..num_kafka_workers {
-            let kafka_db_client = PostgresClient::new(pool.clone())
-                .with_context(|| "Failed to create Postgres client")
-                .unwrap();
-            let message_handler = MessageHandler::Postgres(kafka_db_client);
+            let writer = bulk_writer.clone();
             let brokers =
                 std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "localhost:9092".to_owned());
             let topics =
@@ -42,7 +45,7 @@ pub mod kafka_startup {
             // send task to background
             tokio::spawn(async move {
                 start_kafka_background_poll(
-                    message_handler,
+                    writer,
                     group_id,
                     brokers,
                     topics,
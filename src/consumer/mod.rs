@@ -1,3 +1,4 @@
 pub mod base;
+pub mod bulk;
 pub mod kafka;
 pub mod rabbitmq;
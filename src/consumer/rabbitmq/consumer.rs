@@ -1,17 +1,19 @@
 #[cfg(feature = "rabbitmq")]
 pub mod rabbitmq_consumer {
 
-    use crate::consumer::base::MessageHandler;
-    use scouter::core::drift::base::ServerRecords;
+    use crate::consumer::base::IngestRecords;
+    use crate::consumer::bulk::{BulkWriter, FlushTicket};
 
     use futures::StreamExt;
 
+    use std::collections::VecDeque;
     use std::result::Result::Ok;
     use tracing::error;
     use tracing::info;
 
     use lapin::{
-        options::*, types::FieldTable, Connection, ConnectionProperties, Consumer, Result,
+        message::Delivery, options::*, types::FieldTable, Connection, ConnectionProperties,
+        Consumer, Result,
     };
 
     pub async fn create_rabbitmq_consumer(address: &str, prefetch_count: &u16) -> Result<Consumer> {
@@ -44,32 +46,49 @@ pub mod rabbitmq_consumer {
     }
 
     pub async fn stream_from_rabbit_queue(
-        message_handler: &MessageHandler,
+        writer: &BulkWriter,
         consumer: &mut Consumer,
     ) -> Result<()> {
-        // start consumer. The `start` method returns a future that resolves to a `Result` once the consumer has started.
-        while let Some(delivery) = consumer.next().await {
-            match delivery {
-                // try loading the message from the delivery. if message serialization fails, log the error
-                Ok(delivery) => match serde_json::from_slice::<ServerRecords>(&delivery.data) {
-                    // insert the records into the database. If insertion fails, log the error
-                    Ok(records) => match message_handler.insert_server_records(&records).await {
-                        // acknowledge the message. If acknowledgment fails, log the error
-                        Ok(_) => {
-                            if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
-                                error!("Failed to acknowledge message: {:?}", e);
+        let mut flushed = writer.subscribe();
+        // deliveries are acknowledged once the bulk writer has flushed their records
+        let mut pending: VecDeque<(FlushTicket, Delivery)> = VecDeque::new();
+
+        loop {
+            tokio::select! {
+                delivery = consumer.next() => match delivery {
+                    None => break,
+                    // try loading the message from the delivery. if message serialization fails, log the error
+                    Some(Ok(delivery)) => match serde_json::from_slice::<IngestRecords>(&delivery.data) {
+                        // buffer the records. If buffering fails, log the error
+                        Ok(records) => match writer.write(&records).await {
+                            Ok((_, ticket)) => pending.push_back((ticket, delivery)),
+                            Err(e) => {
+                                error!("Failed to insert drift record: {:?}", e);
                             }
-                        }
+                        },
                         Err(e) => {
-                            error!("Failed to insert drift record: {:?}", e);
+                            error!("Failed to deserialize message: {:?}", e);
                         }
                     },
-                    Err(e) => {
-                        error!("Failed to deserialize message: {:?}", e);
+                    Some(Err(e)) => {
+                        error!("Failed to receive delivery: {:?}", e);
                     }
                 },
-                Err(e) => {
-                    error!("Failed to receive delivery: {:?}", e);
+                changed = flushed.changed() => {
+                    if changed.is_err() {
+                        error!("Bulk writer stopped");
+                        break;
+                    }
+                    let generation = FlushTicket(*flushed.borrow_and_update());
+
+                    while pending.front().is_some_and(|(ticket, _)| *ticket <= generation) {
+                        if let Some((_, delivery)) = pending.pop_front() {
+                            // acknowledge the message. If acknowledgment fails, log the error
+                            if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+                                error!("Failed to acknowledge message: {:?}", e);
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -84,7 +103,7 @@ pub mod rabbitmq_consumer {
     //
     // # Arguments
     //
-    // * `message_handler` - The message handler to process the records
+    // * `writer` - The bulk writer to buffer the records with
     // * `group_id` - The kafka consumer group id
     // * `brokers` - The kafka brokers
     // * `topics` - The kafka topics to subscribe to
@@ -98,14 +117,14 @@ pub mod rabbitmq_consumer {
     // * `Result<(), anyhow::Error>` - The result of the operation
 
     pub async fn start_rabbitmq_background_poll(
-        message_handler: MessageHandler,
+        writer: BulkWriter,
         address: String,
         prefetch_count: u16,
     ) -> Result<()> {
         let mut consumer = create_rabbitmq_consumer(&address, &prefetch_count).await?;
 
         loop {
-            if let Err(e) = stream_from_rabbit_queue(&message_handler, &mut consumer).await {
+            if let Err(e) = stream_from_rabbit_queue(&writer, &mut consumer).await {
                 error!("Error in stream_from_rabbit_queue: {:?}", e);
             }
         }
@@ -1,4 +1,6 @@
 pub mod alerts;
 pub mod api;
 pub mod consumer;
+pub mod observe;
 pub mod sql;
+pub mod types;
@@ -1,9 +1,12 @@
 mod alerts;
 mod api;
 mod consumer;
+mod observe;
 mod sql;
+mod types;
 
 use crate::alerts::base::DriftExecutor;
+use crate::api::auth::{bootstrap_api_key, AuthConfig};
 use crate::api::metrics::metrics_app;
 use crate::api::route::AppState;
 use crate::api::setup::{create_db_pool, setup_logging};
@@ -82,8 +85,22 @@ async fn start_main_server() -> Result<(), anyhow::Error> {
     let server_db_client =
         PostgresClient::new(pool.clone()).with_context(|| "Failed to create Postgres client")?;
 
+    bootstrap_api_key(&server_db_client).await?;
+
+    // resume purge jobs interrupted by a previous shutdown. Jobs are claimed before they
+    // run, so instances starting together never run the same job
+    for job in server_db_client.get_unfinished_purge_jobs().await? {
+        let purge_db_client = server_db_client.clone();
+        tokio::task::spawn(async move {
+            if let Err(e) = purge_db_client.run_purge_job(&job).await {
+                error!("Purge job {} failed: {:?}", job.id, e);
+            }
+        });
+    }
+
     let app = create_router(Arc::new(AppState {
         db: server_db_client,
+        auth: AuthConfig::from_env().await?,
     }));
 
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8000")
@@ -129,6 +146,7 @@ mod tests {
 
         let app = create_router(Arc::new(AppState {
             db: db_client.clone(),
+            auth: AuthConfig::default(),
         }));
 
         let response = app
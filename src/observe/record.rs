@@ -0,0 +1,191 @@
+use crate::api::schema::parse_timestamp;
+use crate::observe::sketch::LatencySketch;
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use scouter::core::observe::observer::ObservabilityMetrics;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+// Client clocks may run slightly ahead of the server
+const MAX_CLOCK_SKEW_MINUTES: i64 = 5;
+
+/// Observability metrics as written to scouter.observability_metrics
+///
+/// Clients may report a mergeable latency sketch per route alongside the fixed
+/// percentiles, routes without one get a sketch approximated from the percentiles.
+/// Sketches are stored with the route metrics under `latency_sketch`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "Value")]
+pub struct ObservabilityRecord {
+    pub metrics: ObservabilityMetrics,
+    // time the metrics were recorded by the client, defaults to the time they were received
+    pub created_at: NaiveDateTime,
+    // route name -> latency sketch
+    pub latency_sketches: BTreeMap<String, LatencySketch>,
+}
+
+impl TryFrom<Value> for ObservabilityRecord {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        Self::from_value(&value)
+    }
+}
+
+impl ObservabilityRecord {
+    /// Read a record from its json form, keeping the optional `created_at` of the record and
+    /// the `latency_sketch` of each route, which the client metrics type does not carry
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Serialized observability metrics
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ObservabilityRecord>` - Record to insert
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let metrics: ObservabilityMetrics = serde_json::from_value(value.clone())
+            .map_err(|e| anyhow!("Invalid observability metrics: {}", e))?;
+
+        let created_at = match value.get("created_at") {
+            Some(Value::String(created_at)) => Some(parse_timestamp(created_at)?),
+            Some(Value::Null) | None => None,
+            Some(created_at) => return Err(anyhow!("Invalid timestamp: {}", created_at)),
+        };
+
+        let received_at = Utc::now().naive_utc();
+        let created_at = match created_at {
+            Some(created_at)
+                if created_at > received_at + Duration::minutes(MAX_CLOCK_SKEW_MINUTES) =>
+            {
+                return Err(anyhow!("created_at {} is in the future", created_at));
+            }
+            Some(created_at) => created_at,
+            None => received_at,
+        };
+
+        let mut latency_sketches = BTreeMap::new();
+        let routes = value.get("route_metrics").and_then(Value::as_array);
+
+        for route in routes.into_iter().flatten() {
+            let (Some(route_name), Some(sketch)) = (
+                route.get("route_name").and_then(Value::as_str),
+                route.get("latency_sketch"),
+            ) else {
+                continue;
+            };
+
+            let sketch: LatencySketch = serde_json::from_value(sketch.clone())
+                .map_err(|e| anyhow!("Invalid latency sketch for {}: {}", route_name, e))?;
+            sketch.validate()?;
+
+            latency_sketches.insert(route_name.to_string(), sketch);
+        }
+
+        for route in metrics.route_metrics.iter() {
+            if latency_sketches.contains_key(&route.route_name) {
+                continue;
+            }
+
+            if let Some(sketch) =
+                LatencySketch::from_percentiles(&route.metrics, route.request_count)
+            {
+                latency_sketches.insert(route.route_name.clone(), sketch);
+            }
+        }
+
+        Ok(Self {
+            metrics,
+            created_at,
+            latency_sketches,
+        })
+    }
+
+    /// Route metrics json with each route's sketch attached
+    pub fn route_metrics_value(&self) -> Result<Value> {
+        let mut route_metrics = serde_json::to_value(&self.metrics.route_metrics)?;
+
+        if let Some(routes) = route_metrics.as_array_mut() {
+            for route in routes.iter_mut() {
+                let sketch = route
+                    .get("route_name")
+                    .and_then(Value::as_str)
+                    .and_then(|route_name| self.latency_sketches.get(route_name));
+
+                if let Some(sketch) = sketch {
+                    route["latency_sketch"] = serde_json::to_value(sketch)?;
+                }
+            }
+        }
+
+        Ok(route_metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scouter::core::observe::observer::{LatencyMetrics, RouteMetrics};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_from_value() {
+        let metrics = ObservabilityMetrics {
+            name: "test_app".to_string(),
+            repository: "test".to_string(),
+            version: "1.0.0".to_string(),
+            request_count: 1,
+            error_count: 0,
+            route_metrics: vec![RouteMetrics {
+                route_name: "/predict".to_string(),
+                metrics: LatencyMetrics {
+                    p5: 0.1,
+                    p25: 0.1,
+                    p50: 0.1,
+                    p95: 0.1,
+                    p99: 0.1,
+                },
+                request_count: 1,
+                error_count: 0,
+                error_latency: 0.0,
+                status_codes: HashMap::new(),
+            }],
+        };
+
+        let mut sketch = LatencySketch::default();
+        sketch.add(0.1, 2);
+
+        // the fields the client metrics type does not carry
+        let mut value = serde_json::to_value(&metrics).unwrap();
+        value["created_at"] = Value::from("2024-11-25T10:00:00Z");
+        value["route_metrics"][0]["latency_sketch"] = serde_json::to_value(&sketch).unwrap();
+
+        let record: ObservabilityRecord = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(
+            record.created_at,
+            parse_timestamp("2024-11-25T10:00:00").unwrap()
+        );
+        assert_eq!(record.latency_sketches["/predict"], sketch);
+
+        // without a timestamp or sketch
+        let record =
+            ObservabilityRecord::from_value(&serde_json::to_value(&metrics).unwrap()).unwrap();
+        assert!(record.created_at > Utc::now().naive_utc() - Duration::minutes(1));
+
+        // a sketch is approximated from the reported percentiles
+        let route_metrics = record.route_metrics_value().unwrap();
+        let approximated: LatencySketch =
+            serde_json::from_value(route_metrics[0]["latency_sketch"].clone()).unwrap();
+        assert_eq!(approximated.count(), 1);
+
+        // timestamps far in the future are rejected
+        let mut future = value.clone();
+        future["created_at"] = Value::from("2999-01-01T00:00:00");
+        assert!(ObservabilityRecord::from_value(&future).is_err());
+
+        // as are invalid sketches
+        value["route_metrics"][0]["latency_sketch"]["relative_accuracy"] = Value::from(2.0);
+        assert!(serde_json::from_value::<ObservabilityRecord>(value).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use anyhow::{anyhow, Result};
+use scouter::core::observe::observer::LatencyMetrics;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Relative accuracy used when a client does not pick one. Any quantile is within 1% of the true value
+pub const DEFAULT_RELATIVE_ACCURACY: f64 = 0.01;
+
+/// Percentiles reported for each observability bin
+pub const LATENCY_QUANTILES: [f64; 5] = [0.05, 0.25, 0.5, 0.95, 0.99];
+
+/// Mergeable latency sketch (DDSketch)
+///
+/// Latencies are counted in logarithmically sized buckets so sketches recorded by different
+/// services or over different intervals can be merged by adding bucket counts, and any
+/// quantile of the merged distribution is returned within the sketch's relative accuracy
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LatencySketch {
+    pub relative_accuracy: f64,
+    // latencies of exactly zero
+    #[serde(default)]
+    pub zero_count: u64,
+    // bucket index -> count. Bucket i covers (gamma^(i - 1), gamma^i]
+    #[serde(default)]
+    pub bins: BTreeMap<i32, u64>,
+}
+
+impl Default for LatencySketch {
+    fn default() -> Self {
+        Self::new(DEFAULT_RELATIVE_ACCURACY)
+    }
+}
+
+impl LatencySketch {
+    pub fn new(relative_accuracy: f64) -> Self {
+        Self {
+            relative_accuracy,
+            zero_count: 0,
+            bins: BTreeMap::new(),
+        }
+    }
+
+    fn gamma(&self) -> f64 {
+        (1.0 + self.relative_accuracy) / (1.0 - self.relative_accuracy)
+    }
+
+    /// Check that the sketch can be merged and queried
+    pub fn validate(&self) -> Result<()> {
+        if !(self.relative_accuracy > 0.0 && self.relative_accuracy < 1.0) {
+            return Err(anyhow!(
+                "relative_accuracy must be between 0 and 1, got {}",
+                self.relative_accuracy
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Record `count` latencies of `value` seconds. Negative latencies are counted as zero
+    pub fn add(&mut self, value: f64, count: u64) {
+        if count == 0 {
+            return;
+        }
+
+        if value <= 0.0 {
+            self.zero_count += count;
+            return;
+        }
+
+        let index = (value.ln() / self.gamma().ln()).ceil() as i32;
+        *self.bins.entry(index).or_insert(0) += count;
+    }
+
+    /// Approximate the latencies of a route from the fixed percentiles reported by clients
+    /// that don't send a sketch
+    ///
+    /// Requests between two reported percentiles are counted at the higher one, so
+    /// quantiles of merged sketches err towards the slower latency
+    ///
+    /// # Arguments
+    ///
+    /// * `metrics` - Reported p5, p25, p50, p95 and p99
+    /// * `request_count` - Requests the percentiles were computed over
+    ///
+    /// # Returns
+    ///
+    /// * `Option<LatencySketch>` - None if no requests were recorded
+    pub fn from_percentiles(metrics: &LatencyMetrics, request_count: i64) -> Option<Self> {
+        if request_count <= 0 {
+            return None;
+        }
+
+        let request_count = request_count as u64;
+        let percentiles = [
+            metrics.p5,
+            metrics.p25,
+            metrics.p50,
+            metrics.p95,
+            metrics.p99,
+            metrics.p99,
+        ];
+        let upper_quantiles = [0.05, 0.25, 0.5, 0.95, 0.99, 1.0];
+
+        let mut sketch = Self::default();
+        let mut counted = 0;
+        for (value, quantile) in percentiles.into_iter().zip(upper_quantiles) {
+            let upto = (quantile * request_count as f64).round() as u64;
+            sketch.add(value, upto - counted);
+            counted = upto;
+        }
+
+        Some(sketch)
+    }
+
+    /// Total number of latencies recorded
+    pub fn count(&self) -> u64 {
+        self.zero_count + self.bins.values().sum::<u64>()
+    }
+
+    /// Add the counts of another sketch into this one
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - Sketch to merge, must use the same relative accuracy
+    pub fn merge(&mut self, other: &LatencySketch) -> Result<()> {
+        if (self.relative_accuracy - other.relative_accuracy).abs() > f64::EPSILON {
+            return Err(anyhow!(
+                "Cannot merge sketches with relative accuracy {} and {}",
+                self.relative_accuracy,
+                other.relative_accuracy
+            ));
+        }
+
+        self.zero_count += other.zero_count;
+        for (index, count) in other.bins.iter() {
+            *self.bins.entry(*index).or_insert(0) += count;
+        }
+
+        Ok(())
+    }
+
+    /// Estimate a quantile of the recorded latencies
+    ///
+    /// # Arguments
+    ///
+    /// * `quantile` - Quantile between 0 and 1
+    ///
+    /// # Returns
+    ///
+    /// * `Option<f64>` - Estimated latency, None if the sketch is empty
+    pub fn quantile(&self, quantile: f64) -> Option<f64> {
+        let count = self.count();
+
+        if count == 0 {
+            return None;
+        }
+
+        let rank = quantile.clamp(0.0, 1.0) * (count - 1) as f64;
+        let mut seen = self.zero_count;
+
+        if seen as f64 > rank {
+            return Some(0.0);
+        }
+
+        let gamma = self.gamma();
+        for (index, bin_count) in self.bins.iter() {
+            seen += bin_count;
+
+            if seen as f64 > rank {
+                return Some(2.0 * gamma.powi(*index) / (gamma + 1.0));
+            }
+        }
+
+        // rounding can leave the rank on the last bucket
+        self.bins
+            .keys()
+            .next_back()
+            .map(|index| 2.0 * gamma.powi(*index) / (gamma + 1.0))
+    }
+
+    /// Merge sketches and compute the reported latency percentiles
+    ///
+    /// # Arguments
+    ///
+    /// * `sketches` - Sketches to merge
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<[f64; 5]>>` - p5, p25, p50, p95 and p99, None if nothing was recorded
+    pub fn merged_percentiles(sketches: &[LatencySketch]) -> Result<Option<[f64; 5]>> {
+        let Some(first) = sketches.first() else {
+            return Ok(None);
+        };
+
+        let mut merged = LatencySketch::new(first.relative_accuracy);
+        merged.validate()?;
+
+        for sketch in sketches.iter() {
+            merged.merge(sketch)?;
+        }
+
+        let mut percentiles = [0.0; 5];
+        for (percentile, quantile) in percentiles.iter_mut().zip(LATENCY_QUANTILES) {
+            match merged.quantile(quantile) {
+                Some(value) => *percentile = value,
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(percentiles))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn within_accuracy(estimate: f64, expected: f64) -> bool {
+        (estimate - expected).abs() <= expected * DEFAULT_RELATIVE_ACCURACY
+    }
+
+    #[test]
+    fn test_sketch_quantiles() {
+        let mut sketch = LatencySketch::default();
+        assert_eq!(sketch.quantile(0.5), None);
+
+        for i in 1..=1000 {
+            sketch.add(i as f64 / 1000.0, 1);
+        }
+
+        assert_eq!(sketch.count(), 1000);
+        assert!(within_accuracy(sketch.quantile(0.5).unwrap(), 0.5));
+        assert!(within_accuracy(sketch.quantile(0.99).unwrap(), 0.99));
+        assert!(within_accuracy(sketch.quantile(1.0).unwrap(), 1.0));
+
+        // survives a json round trip
+        let value = serde_json::to_value(&sketch).unwrap();
+        let decoded: LatencySketch = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded, sketch);
+    }
+
+    #[test]
+    fn test_sketch_merge() {
+        // a fast, busy service and a slow, quiet one
+        let mut fast = LatencySketch::default();
+        fast.add(0.01, 990);
+
+        let mut slow = LatencySketch::default();
+        slow.add(2.0, 20);
+        slow.add(0.0, 1);
+
+        let percentiles = LatencySketch::merged_percentiles(&[fast.clone(), slow.clone()])
+            .unwrap()
+            .unwrap();
+
+        // averaging the two p99s would report ~1s, the merged p99 is the slow tail
+        assert!(within_accuracy(percentiles[2], 0.01));
+        assert!(within_accuracy(percentiles[4], 2.0));
+
+        let mut other = LatencySketch::new(0.05);
+        other.add(1.0, 1);
+        assert!(fast.merge(&other).is_err());
+        assert_eq!(LatencySketch::merged_percentiles(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_sketch_from_percentiles() {
+        let metrics = LatencyMetrics {
+            p5: 0.01,
+            p25: 0.02,
+            p50: 0.05,
+            p95: 0.2,
+            p99: 1.0,
+        };
+
+        let sketch = LatencySketch::from_percentiles(&metrics, 1000).unwrap();
+        assert_eq!(sketch.count(), 1000);
+
+        let [p5, p25, p50, p95, p99] = LatencySketch::merged_percentiles(&[sketch])
+            .unwrap()
+            .unwrap();
+        assert!(within_accuracy(p5, 0.01));
+        assert!(within_accuracy(p25, 0.02));
+        assert!(within_accuracy(p50, 0.05));
+        assert!(within_accuracy(p95, 0.2));
+        assert!(within_accuracy(p99, 1.0));
+
+        assert_eq!(LatencySketch::from_percentiles(&metrics, 0), None);
+    }
+}
@@ -0,0 +1,352 @@
+use anyhow::anyhow;
+use sqlx::{Encode, Postgres, QueryBuilder, Type};
+
+// Placeholder for a required value bound in template order
+const BIND_TOKEN: &str = "{bind}";
+
+// Placeholder where optional filters are appended (each as `AND <condition>`)
+const FILTERS_TOKEN: &str = "{filters}";
+
+// Placeholder for a where clause made up only of optional filters
+const WHERE_TOKEN: &str = "{where}";
+
+// Placeholder for a sql expression chosen from a fixed set (e.g. an aggregation)
+const EXPR_TOKEN: &str = "{expr}";
+
+const FILTER_TOKENS: [&str; 2] = [FILTERS_TOKEN, WHERE_TOKEN];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Lt,
+    Lte,
+    Gte,
+    Like,
+}
+
+impl Op {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            Op::Eq => "=",
+            Op::Lt => "<",
+            Op::Lte => "<=",
+            Op::Gte => ">=",
+            Op::Like => "LIKE",
+        }
+    }
+}
+
+/// Builds a query from a sql script template without interpolating any values
+///
+/// Templates mark required values with `{bind}`, the position of optional
+/// filters with `{filters}` (or `{where}` when every filter is optional) and
+/// expressions picked by the caller with `{expr}`. Column names, operators and casts only accept
+/// `&'static str`, so request data can only ever reach the query as a bind parameter.
+///
+/// Calls that do not match the template are recorded and returned as an error by `build`.
+pub struct SqlBuilder<'args> {
+    builder: QueryBuilder<'args, Postgres>,
+    rest: String,
+    conditions: usize,
+    error: Option<String>,
+}
+
+impl<'args> SqlBuilder<'args> {
+    pub fn new(template: &str) -> Self {
+        let (head, rest) = Self::split_at_token(template);
+
+        SqlBuilder {
+            builder: QueryBuilder::new(head),
+            rest: rest.to_string(),
+            conditions: 0,
+            error: None,
+        }
+    }
+
+    // Keeps the first template mismatch so it can be returned by `build`
+    fn fail(&mut self, message: String) {
+        self.error.get_or_insert(message);
+    }
+
+    fn split_at_token(sql: &str) -> (&str, &str) {
+        let next = [BIND_TOKEN, FILTERS_TOKEN, WHERE_TOKEN, EXPR_TOKEN]
+            .iter()
+            .filter_map(|token| sql.find(token))
+            .min()
+            .unwrap_or(sql.len());
+
+        sql.split_at(next)
+    }
+
+    // Consumes the token at the start of the remaining template and pushes
+    // the sql up to the next token
+    fn advance(&mut self, token: &str) {
+        let rest = std::mem::take(&mut self.rest);
+        let Some(rest) = rest.strip_prefix(token) else {
+            self.fail(format!("Expected {} in query template", token));
+            self.rest = rest;
+            return;
+        };
+
+        let (sql, rest) = Self::split_at_token(rest);
+        self.builder.push(sql);
+        self.rest = rest.to_string();
+        self.conditions = 0;
+    }
+
+    fn skip_filters(&mut self) {
+        if let Some(token) = FILTER_TOKENS
+            .iter()
+            .find(|token| self.rest.starts_with(**token))
+        {
+            self.advance(token);
+        }
+    }
+
+    fn push_condition(&mut self) -> &mut QueryBuilder<'args, Postgres> {
+        let keyword = if self.rest.starts_with(WHERE_TOKEN) && self.conditions == 0 {
+            "WHERE"
+        } else if FILTER_TOKENS
+            .iter()
+            .any(|token| self.rest.starts_with(token))
+        {
+            "AND"
+        } else {
+            self.fail(
+                "Filters must be added where the template expects {filters} or {where}".to_string(),
+            );
+            "AND"
+        };
+        self.conditions += 1;
+
+        if !self.builder.sql().ends_with(char::is_whitespace) {
+            self.builder.push(" ");
+        }
+        self.builder.push(format_args!("{} ", keyword))
+    }
+
+    /// Bind the value for the next `{bind}` placeholder in the template
+    pub fn bind<T>(&mut self, value: T) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+    {
+        self.skip_filters();
+        self.builder.push_bind(value);
+        self.advance(BIND_TOKEN);
+        self
+    }
+
+    /// Fill the next `{expr}` placeholder in the template
+    pub fn expr(&mut self, expr: &'static str) -> &mut Self {
+        self.skip_filters();
+        self.builder.push(expr);
+        self.advance(EXPR_TOKEN);
+        self
+    }
+
+    /// Add a `column <op> value` filter
+    pub fn filter<T>(&mut self, column: &'static str, op: Op, value: T) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+    {
+        self.push_condition()
+            .push(format_args!("{} {} ", column, op.as_sql()))
+            .push_bind(value);
+        self
+    }
+
+    /// Add a `column <op> value` filter when a value is provided
+    pub fn filter_opt<T>(&mut self, column: &'static str, op: Op, value: Option<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.filter(column, op, value);
+        }
+        self
+    }
+
+    /// Add a `column = ANY(values)` filter, skipped when no values are provided
+    pub fn filter_any(&mut self, column: &'static str, values: &[String]) -> &mut Self {
+        if !values.is_empty() {
+            self.push_condition()
+                .push(format_args!("{} = ANY(", column))
+                .push_bind(values.to_vec())
+                .push(")");
+        }
+        self
+    }
+
+    /// Add a row comparison filter such as `(created_at, id) < (a, b)`
+    pub fn filter_row<A, B>(&mut self, columns: &'static str, op: Op, a: A, b: B) -> &mut Self
+    where
+        A: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+        B: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+    {
+        self.push_condition()
+            .push(format_args!("{} {} (", columns, op.as_sql()))
+            .push_bind(a)
+            .push(", ")
+            .push_bind(b)
+            .push(")");
+        self
+    }
+
+    /// Add a condition that takes no values
+    pub fn condition(&mut self, condition: &'static str) -> &mut Self {
+        self.push_condition().push(condition);
+        self
+    }
+
+    pub fn order_by(&mut self, order: &'static str) -> &mut Self {
+        self.skip_filters();
+        self.builder.push(format_args!(" ORDER BY {}", order));
+        self
+    }
+
+    pub fn limit<T>(&mut self, limit: Option<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+    {
+        self.skip_filters();
+        if let Some(limit) = limit {
+            self.builder.push(" LIMIT ").push_bind(limit);
+        }
+        self
+    }
+
+    pub fn offset<T>(&mut self, offset: Option<T>) -> &mut Self
+    where
+        T: 'args + Encode<'args, Postgres> + Send + Type<Postgres>,
+    {
+        self.skip_filters();
+        if let Some(offset) = offset {
+            self.builder.push(" OFFSET ").push_bind(offset);
+        }
+        self
+    }
+
+    /// Finish the template and return the underlying sqlx builder
+    ///
+    /// Errors if a call did not match the template or a placeholder was left unbound
+    pub fn build(mut self) -> Result<QueryBuilder<'args, Postgres>, anyhow::Error> {
+        self.skip_filters();
+
+        if let Some(error) = self.error {
+            return Err(anyhow!(error));
+        }
+
+        if !self.rest.is_empty() {
+            return Err(anyhow!("Query template has unbound placeholders"));
+        }
+
+        Ok(self.builder)
+    }
+}
+
+/// Escape LIKE wildcards in `prefix` and append `%` so it only matches as a prefix
+pub fn like_prefix(prefix: &str) -> String {
+    let escaped = prefix
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("{}%", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sql_builder() {
+        let mut query = SqlBuilder::new(
+            "SELECT * FROM scouter.drift_alerts WHERE name = {bind} AND version = {bind} {filters}",
+        );
+        query
+            .bind("test_app")
+            .bind("1.0.0")
+            .filter_opt("status", Op::Eq, Some("active"))
+            .filter_opt::<&str>("feature", Op::Eq, None)
+            .filter_opt("created_at", Op::Gte, Some(1))
+            .filter_any("feature", &["a".to_string()])
+            .filter_row("(created_at, id)", Op::Lt, 1, 2)
+            .order_by("created_at DESC")
+            .limit(Some(10));
+
+        assert_eq!(
+            query.build().unwrap().sql(),
+            "SELECT * FROM scouter.drift_alerts WHERE name = $1 AND version = $2 \
+             AND status = $3 AND created_at >= $4 AND feature = ANY($5) \
+             AND (created_at, id) < ($6, $7) ORDER BY created_at DESC LIMIT $8"
+        );
+    }
+
+    #[test]
+    fn test_sql_builder_template_tail() {
+        let mut query = SqlBuilder::new(
+            "WITH a AS (SELECT {expr} AS v FROM t WHERE x = {bind} {filters}) SELECT * FROM a WHERE y > {bind}",
+        );
+        query
+            .expr("avg(value)")
+            .bind(1)
+            .filter("z", Op::Eq, 2)
+            .bind(3);
+
+        assert_eq!(
+            query.build().unwrap().sql(),
+            "WITH a AS (SELECT avg(value) AS v FROM t WHERE x = $1 AND z = $2) SELECT * FROM a WHERE y > $3"
+        );
+    }
+
+    #[test]
+    fn test_sql_builder_where() {
+        let template = "SELECT * FROM scouter.drift_profile {where} ORDER BY name";
+
+        let query = SqlBuilder::new(template);
+        assert_eq!(
+            query.build().unwrap().sql(),
+            "SELECT * FROM scouter.drift_profile  ORDER BY name"
+        );
+
+        let mut query = SqlBuilder::new(template);
+        query
+            .filter("repository", Op::Eq, "test")
+            .condition("active");
+        assert_eq!(
+            query.build().unwrap().sql(),
+            "SELECT * FROM scouter.drift_profile WHERE repository = $1 AND active ORDER BY name"
+        );
+    }
+
+    #[test]
+    fn test_like_prefix() {
+        assert_eq!(like_prefix("model"), "model%");
+        assert_eq!(like_prefix("50%_off\\"), "50\\%\\_off\\\\%");
+    }
+
+    #[test]
+    fn test_sql_builder_unbound() {
+        assert!(SqlBuilder::new("SELECT * FROM t WHERE x = {bind}")
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_sql_builder_mismatch() {
+        // filter where the template expects a value
+        let mut query = SqlBuilder::new("SELECT * FROM t WHERE x = {bind}");
+        query.filter("y", Op::Eq, 1).bind(2);
+        assert!(query.build().is_err());
+
+        // more values than the template has placeholders
+        let mut query = SqlBuilder::new("SELECT * FROM t WHERE x = {bind}");
+        query.bind(1).bind(2);
+        assert!(query.build().is_err());
+
+        // expression where the template expects a value
+        let mut query = SqlBuilder::new("SELECT * FROM t WHERE x = {bind}");
+        query.expr("avg(value)");
+        assert!(query.build().is_err());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod builder;
 pub mod postgres;
 pub mod query;
 pub mod schema;
@@ -1,18 +1,26 @@
+use crate::alerts::observability::types::{ObservabilityAlertProfile, OBSERVABILITY_DRIFT_TYPE};
+use crate::alerts::psi::types::PsiServerRecord;
+use crate::alerts::types::DriftProfile;
+use crate::alerts::volume::VolumeSource;
 use crate::api::schema::{
-    DriftAlertRequest, DriftRequest, ObservabilityMetricRequest, ProfileStatusRequest, ServiceInfo,
+    AlertAction, DriftAlertRequest, DriftRequest, ObservabilityMetricRequest,
+    ProfileCatchUpRequest, ProfileListRequest, ProfileStatusRequest, ServiceInfo, TimeRange,
 };
+use crate::observe::record::ObservabilityRecord;
+use crate::sql::builder::{like_prefix, Op, SqlBuilder};
 use crate::sql::query::Queries;
 use crate::sql::schema::{
-    AlertResult, FeatureResult, ObservabilityResult, QueryResult, SpcFeatureResult, TaskRequest,
+    AlertCursor, AlertHistoryRecord, AlertResult, ApiKeyRecord, BinnedFeature, BinnedFeatureResult,
+    BinnedQueryResult, DriftTaskRun, FeatureBinCount, FeatureRecord, FeatureResult, FeatureVolume,
+    ObservabilityResult, ProfileSummary, PurgeJob, PurgeStatus, QueryResult, RoleBinding,
+    RouteSummary, SpcFeatureResult, TaskRequest,
 };
 use anyhow::*;
-use chrono::Utc;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use cron::Schedule;
 use futures::future::join_all;
 use include_dir::{include_dir, Dir};
-use scouter::core::drift::base::DriftProfile;
 use scouter::core::drift::spc::types::SpcServerRecord;
-use scouter::core::observe::observer::ObservabilityMetrics;
 use serde_json::Value;
 use sqlx::{
     postgres::{PgQueryResult, PgRow},
@@ -21,54 +29,26 @@ use sqlx::{
 use std::collections::BTreeMap;
 use std::result::Result::Ok;
 use std::str::FromStr;
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 
 static _MIGRATIONS: Dir = include_dir!("migrations");
 
-pub enum TimeInterval {
-    FiveMinutes,
-    FifteenMinutes,
-    ThirtyMinutes,
-    OneHour,
-    ThreeHours,
-    SixHours,
-    TwelveHours,
-    TwentyFourHours,
-    TwoDays,
-    FiveDays,
-}
+// Rows deleted per statement when purging service data
+const DEFAULT_PURGE_BATCH_SIZE: i64 = 10_000;
+// running purge jobs that have not reported progress for this long are treated as abandoned
+const DEFAULT_PURGE_JOB_TIMEOUT_SECS: i64 = 600;
 
-impl TimeInterval {
-    pub fn to_minutes(&self) -> i32 {
-        match self {
-            TimeInterval::FiveMinutes => 5,
-            TimeInterval::FifteenMinutes => 15,
-            TimeInterval::ThirtyMinutes => 30,
-            TimeInterval::OneHour => 60,
-            TimeInterval::ThreeHours => 180,
-            TimeInterval::SixHours => 360,
-            TimeInterval::TwelveHours => 720,
-            TimeInterval::TwentyFourHours => 1440,
-            TimeInterval::TwoDays => 2880,
-            TimeInterval::FiveDays => 7200,
-        }
-    }
-
-    pub fn from_string(time_window: &str) -> TimeInterval {
-        match time_window {
-            "5minute" => TimeInterval::FiveMinutes,
-            "15minute" => TimeInterval::FifteenMinutes,
-            "30minute" => TimeInterval::ThirtyMinutes,
-            "1hour" => TimeInterval::OneHour,
-            "3hour" => TimeInterval::ThreeHours,
-            "6hour" => TimeInterval::SixHours,
-            "12hour" => TimeInterval::TwelveHours,
-            "24hour" => TimeInterval::TwentyFourHours,
-            "2day" => TimeInterval::TwoDays,
-            "5day" => TimeInterval::FiveDays,
-            _ => TimeInterval::SixHours,
-        }
-    }
+// Next time a cron schedule fires after now
+pub fn next_run(schedule: &str) -> Result<DateTime<Utc>, anyhow::Error> {
+    let parsed = Schedule::from_str(schedule)
+        .with_context(|| format!("Failed to parse cron expression: {}", schedule))?;
+
+    parsed.upcoming(Utc).take(1).next().with_context(|| {
+        format!(
+            "Failed to get next run time for cron expression: {}",
+            schedule
+        )
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -121,127 +101,379 @@ impl PostgresClient {
         }
     }
 
+    // Binds the service and optional filters shared by the alert list and count queries
+    fn alert_query<'a>(
+        query: Queries,
+        params: &'a DriftAlertRequest,
+        cursor: Option<&AlertCursor>,
+    ) -> Result<SqlBuilder<'a>, anyhow::Error> {
+        let (limit_timestamp, end_timestamp) = params.time_bounds()?;
+        let mut builder = SqlBuilder::new(&query.get_query().sql);
+
+        builder
+            .bind(&params.version)
+            .bind(&params.name)
+            .bind(&params.repository)
+            .filter_opt("status", Op::Eq, params.status.as_deref())
+            .filter_opt("feature", Op::Eq, params.feature.as_deref())
+            .filter_opt("alert->>'zone'", Op::Eq, params.zone.as_deref())
+            .filter_opt("alert->>'kind'", Op::Eq, params.kind.as_deref())
+            .filter_opt("created_at", Op::Gte, limit_timestamp)
+            .filter_opt("created_at", Op::Lt, end_timestamp);
+
+        if params.active.unwrap_or(false) {
+            builder.condition("status = 'active'");
+        }
+
+        if let Some(cursor) = cursor {
+            builder.filter_row("(created_at, id)", Op::Lt, cursor.created_at, cursor.id);
+        }
+
+        Ok(builder)
+    }
+
     pub async fn get_drift_alerts(
         &self,
         params: &DriftAlertRequest,
     ) -> Result<Vec<AlertResult>, anyhow::Error> {
-        let active = params.active.unwrap_or(false);
+        let cursor = params
+            .cursor
+            .as_deref()
+            .map(AlertCursor::decode)
+            .transpose()?;
 
-        let query = Queries::GetDriftAlerts.get_query();
+        let mut builder = Self::alert_query(Queries::GetDriftAlerts, params, cursor.as_ref())?;
+        builder
+            .order_by("created_at DESC, id DESC")
+            .limit(params.limit);
 
-        // check if active
-        let built_query = if active {
-            format!("{} AND status = 'active'", query.sql)
-        } else {
-            query.sql
-        };
+        let mut query = builder.build()?;
+        let result: Result<Vec<AlertResult>, sqlx::Error> =
+            query.build_query_as().fetch_all(&self.pool).await;
 
-        // check if limit timestamp is provided
-        let built_query = if params.limit_timestamp.is_some() {
-            format!(
-                "{} AND created_at >= '{}' ORDER BY created_at DESC",
-                built_query,
-                params.limit_timestamp.as_ref().unwrap()
-            )
-        } else {
-            format!("{} ORDER BY created_at DESC", built_query)
-        };
+        match result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get alerts from database: {:?}", e);
+                Err(anyhow!("Failed to get alerts from database: {:?}", e))
+            }
+        }
+    }
 
-        // check if limit is provided
-        let built_query = if params.limit.is_some() {
-            format!("{} LIMIT {};", built_query, params.limit.unwrap())
-        } else {
-            format!("{};", built_query)
-        };
+    // Counts every alert matching the request filters, ignoring cursor and limit
+    pub async fn count_drift_alerts(
+        &self,
+        params: &DriftAlertRequest,
+    ) -> Result<i64, anyhow::Error> {
+        let mut query = Self::alert_query(Queries::CountDriftAlerts, params, None)?.build()?;
+        let result = query.build().fetch_one(&self.pool).await;
 
-        let result: Result<Vec<AlertResult>, sqlx::Error> = sqlx::query_as(&built_query)
-            .bind(&params.version)
-            .bind(&params.name)
-            .bind(&params.repository)
-            .fetch_all(&self.pool)
+        match result {
+            Ok(row) => Ok(row.get("total")),
+            Err(e) => {
+                error!("Failed to count alerts in database: {:?}", e);
+                Err(anyhow!("Failed to count alerts in database: {:?}", e))
+            }
+        }
+    }
+
+    pub async fn get_drift_alert(&self, id: i32) -> Result<Option<AlertResult>, anyhow::Error> {
+        let query = Queries::GetDriftAlert.get_query();
+
+        let result: Result<Option<AlertResult>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
             .await;
 
         match result {
             Ok(result) => Ok(result),
             Err(e) => {
-                error!("Failed to get alerts from database: {:?}", e);
-                Err(anyhow!("Failed to get alerts from database: {:?}", e))
+                error!("Failed to get alert from database: {:?}", e);
+                Err(anyhow!("Failed to get alert from database: {:?}", e))
             }
         }
     }
 
-    // Inserts a drift record into the database
+    // Applies a lifecycle action to an alert and records the transition in the alert history
     //
     // # Arguments
     //
-    // * `record` - A drift record to insert into the database
-    // * `table_name` - The name of the table to insert the record into
+    // * `id` - Id of the alert
+    // * `action` - Action to apply
+    // * `actor` - Person or system performing the action
+    // * `subject` - Authenticated subject performing the action
+    // * `comment` - Optional comment stored with the transition
+    //
+    // # Returns
     //
-    pub async fn insert_spc_drift_record(
+    // * `Option<AlertResult>` - Updated alert. None if the alert is no longer in a status the action applies to
+    pub async fn update_drift_alert_status(
         &self,
-        record: &SpcServerRecord,
-    ) -> Result<PgQueryResult, anyhow::Error> {
-        let query = Queries::InsertDriftRecord.get_query();
+        id: i32,
+        action: &AlertAction,
+        actor: &str,
+        subject: &str,
+        comment: Option<&str>,
+    ) -> Result<Option<AlertResult>, anyhow::Error> {
+        let query = Queries::UpdateDriftAlertStatus.get_query();
+
+        let result: Result<Option<AlertResult>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(id)
+            .bind(action.status())
+            .bind(action.allowed_from())
+            .bind(actor)
+            .bind(subject)
+            .bind(comment)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to update alert status: {:?}", e);
+                Err(anyhow!("Failed to update alert status: {:?}", e))
+            }
+        }
+    }
+
+    // Resolves all open alerts for the given features and records each transition
+    //
+    // # Returns
+    //
+    // * `u64` - Number of alerts resolved
+    pub async fn resolve_drift_alerts(
+        &self,
+        service_info: &ServiceInfo,
+        features: &[String],
+        actor: &str,
+        comment: &str,
+    ) -> Result<u64, anyhow::Error> {
+        let query = Queries::ResolveDriftAlerts.get_query();
+
+        let result = sqlx::query(&query.sql)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(features)
+            .bind(actor)
+            .bind(comment)
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(row) => Ok(row.get::<i64, _>("resolved") as u64),
+            Err(e) => {
+                error!("Failed to resolve alerts: {:?}", e);
+                Err(anyhow!("Failed to resolve alerts: {:?}", e))
+            }
+        }
+    }
+
+    pub async fn get_drift_alert_history(
+        &self,
+        id: i32,
+    ) -> Result<Vec<AlertHistoryRecord>, anyhow::Error> {
+        let query = Queries::GetDriftAlertHistory.get_query();
+
+        let result: Result<Vec<AlertHistoryRecord>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(id)
+            .fetch_all(&self.pool)
+            .await;
+
+        match result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get alert history from database: {:?}", e);
+                Err(anyhow!(
+                    "Failed to get alert history from database: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
+    // Inserts a batch of spc drift records within a transaction
+    // using a single multi-row insert and updates the feature catalogue
+    //
+    // # Arguments
+    //
+    // * `transaction` - Postgres transaction
+    // * `records` - Drift records to insert into the database
+    //
+    // # Returns
+    //
+    // * The number of records written (duplicates are skipped)
+    pub async fn insert_spc_drift_records(
+        transaction: &mut Transaction<'_, Postgres>,
+        records: &[SpcServerRecord],
+    ) -> Result<usize, anyhow::Error> {
+        let query = Queries::InsertDriftRecords.get_query();
 
         let query_result = sqlx::query(&query.sql)
-            .bind(record.created_at)
-            .bind(&record.name)
-            .bind(&record.repository)
-            .bind(&record.version)
-            .bind(&record.feature)
-            .bind(record.value)
-            .execute(&self.pool)
-            .await
-            .with_context(|| "Failed to insert alert into database");
+            .bind(records.iter().map(|r| r.created_at).collect::<Vec<_>>())
+            .bind(records.iter().map(|r| r.name.clone()).collect::<Vec<_>>())
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.repository.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.version.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.feature.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(records.iter().map(|r| r.value).collect::<Vec<_>>())
+            .fetch_one(&mut **transaction)
+            .await;
 
-        //drop params
         match query_result {
-            Ok(result) => Ok(result),
+            Ok(row) => Ok(row.get::<i64, _>("inserted") as usize),
             Err(e) => {
-                error!("Failed to insert record into database: {:?}", e);
-                Err(anyhow!("Failed to insert record into database: {:?}", e))
+                error!("Failed to insert records into database: {:?}", e);
+                Err(anyhow!("Failed to insert records into database: {:?}", e))
             }
         }
     }
 
-    // Inserts a drift record into the database
+    // Inserts a batch of psi bin count records within a transaction
+    // using a single multi-row insert and updates the feature catalogue
     //
     // # Arguments
     //
-    // * `record` - A drift record to insert into the database
-    // * `table_name` - The name of the table to insert the record into
+    // * `transaction` - Postgres transaction
+    // * `records` - Psi drift records to insert into the database
     //
-    pub async fn insert_observability_record(
-        &self,
-        record: &ObservabilityMetrics,
-    ) -> Result<PgQueryResult, anyhow::Error> {
-        let query = Queries::InsertObservabilityRecord.get_query();
-        let route_metrics = serde_json::to_value(&record.route_metrics).map_err(|e| {
-            error!("Failed to serialize route metrics: {:?}", e);
-            anyhow!("Failed to serialize route metrics: {:?}", e)
-        })?;
+    // # Returns
+    //
+    // * The number of records written (duplicates are skipped)
+    pub async fn insert_psi_drift_records(
+        transaction: &mut Transaction<'_, Postgres>,
+        records: &[PsiServerRecord],
+    ) -> Result<usize, anyhow::Error> {
+        let query = Queries::InsertPsiDriftRecords.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(records.iter().map(|r| r.created_at).collect::<Vec<_>>())
+            .bind(records.iter().map(|r| r.name.clone()).collect::<Vec<_>>())
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.repository.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.version.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.feature.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(records.iter().map(|r| r.bin_id as i32).collect::<Vec<_>>())
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.bin_count as i32)
+                    .collect::<Vec<_>>(),
+            )
+            .fetch_one(&mut **transaction)
+            .await;
+
+        match query_result {
+            Ok(row) => Ok(row.get::<i64, _>("inserted") as usize),
+            Err(e) => {
+                error!("Failed to insert psi records into database: {:?}", e);
+                Err(anyhow!(
+                    "Failed to insert psi records into database: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
+    // Inserts a batch of observability records within a transaction
+    // using a single multi-row insert
+    //
+    // # Arguments
+    //
+    // * `transaction` - Postgres transaction
+    // * `records` - Observability records to insert into the database
+    //
+    // # Returns
+    //
+    // * The number of records written
+    pub async fn insert_observability_records(
+        transaction: &mut Transaction<'_, Postgres>,
+        records: &[ObservabilityRecord],
+    ) -> Result<usize, anyhow::Error> {
+        let query = Queries::InsertObservabilityRecords.get_query();
+
+        let route_metrics = records
+            .iter()
+            .map(|r| r.route_metrics_value())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| {
+                error!("Failed to serialize route metrics: {:?}", e);
+                anyhow!("Failed to serialize route metrics: {:?}", e)
+            })?;
 
         let query_result = sqlx::query(&query.sql)
-            .bind(&record.repository)
-            .bind(&record.name)
-            .bind(&record.version)
-            .bind(record.request_count)
-            .bind(record.error_count)
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.metrics.repository.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.metrics.name.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.metrics.version.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.metrics.request_count)
+                    .collect::<Vec<_>>(),
+            )
+            .bind(
+                records
+                    .iter()
+                    .map(|r| r.metrics.error_count)
+                    .collect::<Vec<_>>(),
+            )
             .bind(route_metrics)
-            .execute(&self.pool)
-            .await
-            .with_context(|| "Failed to insert observability metrics into database");
+            .bind(records.iter().map(|r| r.created_at).collect::<Vec<_>>())
+            .fetch_one(&mut **transaction)
+            .await;
 
-        //drop params
         match query_result {
-            Ok(result) => Ok(result),
+            Ok(row) => Ok(row.get::<i64, _>("inserted") as usize),
             Err(e) => {
                 error!(
-                    "Failed to insert observability record into database: {:?}",
+                    "Failed to insert observability records into database: {:?}",
                     e
                 );
                 Err(anyhow!(
-                    "Failed to insert observability record into database: {:?}",
+                    "Failed to insert observability records into database: {:?}",
                     e
                 ))
             }
@@ -255,15 +487,7 @@ impl PostgresClient {
         let query = Queries::InsertDriftProfile.get_query();
         let base_args = drift_profile.get_base_args();
 
-        let schedule = Schedule::from_str(&base_args.schedule)
-            .with_context(|| format!("Failed to parse cron expression: {}", base_args.schedule))?;
-
-        let next_run = schedule.upcoming(Utc).take(1).next().with_context(|| {
-            format!(
-                "Failed to get next run time for cron expression: {}",
-                base_args.schedule
-            )
-        })?;
+        let next_run = next_run(&base_args.schedule)?;
 
         let query_result = sqlx::query(&query.sql)
             .bind(base_args.name)
@@ -289,6 +513,85 @@ impl PostgresClient {
         }
     }
 
+    // Inserts an observability alert profile into the drift profile table so it is
+    // picked up by the drift executor on its schedule
+    //
+    // # Arguments
+    //
+    // * `profile` - Observability alert profile to insert
+    //
+    // # Returns
+    //
+    // * `PgQueryResult` - Result of the insert
+    pub async fn insert_observability_profile(
+        &self,
+        profile: &ObservabilityAlertProfile,
+    ) -> Result<PgQueryResult, anyhow::Error> {
+        let query = Queries::InsertDriftProfile.get_query();
+        let next_run = next_run(&profile.schedule)?;
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(&profile.name)
+            .bind(&profile.repository)
+            .bind(&profile.version)
+            .bind(env!("CARGO_PKG_VERSION"))
+            .bind(serde_json::to_value(profile)?)
+            .bind(OBSERVABILITY_DRIFT_TYPE)
+            .bind(false)
+            .bind(&profile.schedule)
+            .bind(next_run.naive_utc())
+            .bind(next_run.naive_utc())
+            .execute(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!(
+                    "Failed to insert observability profile into database: {:?}",
+                    e
+                );
+                Err(anyhow!(
+                    "Failed to insert observability profile into database: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
+    // Replaces a stored observability alert profile
+    //
+    // # Arguments
+    //
+    // * `profile` - Updated observability alert profile
+    //
+    // # Returns
+    //
+    // * `PgQueryResult` - Result of the update
+    pub async fn update_observability_profile(
+        &self,
+        profile: &ObservabilityAlertProfile,
+    ) -> Result<PgQueryResult, anyhow::Error> {
+        let query = Queries::UpdateDriftProfile.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(serde_json::to_value(profile)?)
+            .bind(&profile.name)
+            .bind(&profile.repository)
+            .bind(&profile.version)
+            .bind(OBSERVABILITY_DRIFT_TYPE)
+            .execute(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to update observability profile: {:?}", e);
+                Err(anyhow!("Failed to update observability profile: {:?}", e))
+            }
+        }
+    }
+
     pub async fn update_drift_profile(
         &self,
         drift_profile: &DriftProfile,
@@ -298,10 +601,10 @@ impl PostgresClient {
 
         let query_result = sqlx::query(&query.sql)
             .bind(drift_profile.to_value())
-            .bind(base_args.drift_type.value())
             .bind(base_args.name)
             .bind(base_args.repository)
             .bind(base_args.version)
+            .bind(base_args.drift_type.value())
             .execute(&self.pool)
             .await
             .with_context(|| "Failed to insert profile into database");
@@ -318,6 +621,7 @@ impl PostgresClient {
     pub async fn get_drift_profile(
         &self,
         params: &ServiceInfo,
+        drift_type: Option<&str>,
     ) -> Result<Option<Value>, anyhow::Error> {
         let query = Queries::GetDriftProfile.get_query();
 
@@ -325,6 +629,7 @@ impl PostgresClient {
             .bind(&params.name)
             .bind(&params.repository)
             .bind(&params.version)
+            .bind(drift_type.map(str::to_uppercase))
             .fetch_optional(&self.pool)
             .await
             .with_context(|| "Failed to get drift profile from database")?;
@@ -338,42 +643,169 @@ impl PostgresClient {
         }
     }
 
-    pub async fn get_drift_profile_task(
-        transaction: &mut Transaction<'_, Postgres>,
-    ) -> Result<Option<TaskRequest>, Error> {
-        let query = Queries::GetDriftTask.get_query();
-        let result: Result<Option<TaskRequest>, sqlx::Error> = sqlx::query_as(&query.sql)
-            .fetch_optional(&mut **transaction)
-            .await;
-
-        result.map_err(|e| {
-            error!("Failed to get drift task from database: {:?}", e);
-            anyhow!("Failed to get drift task from database: {:?}", e)
-        })
-    }
+    // Binds the optional filters shared by the profile list and count queries
+    //
+    // # Arguments
+    //
+    // * `query` - Query template to build
+    // * `params` - Profile list filters
+    // * `repositories` - Restrict results to these repositories (all if None)
+    fn profile_query<'a>(
+        query: Queries,
+        params: &'a ProfileListRequest,
+        repositories: Option<&[String]>,
+    ) -> SqlBuilder<'a> {
+        let mut builder = SqlBuilder::new(&query.get_query().sql);
+
+        builder
+            .filter_opt("repository", Op::Eq, params.repository.as_deref())
+            .filter_opt("name", Op::Like, params.name.as_deref().map(like_prefix))
+            .filter_opt(
+                "drift_type",
+                Op::Eq,
+                params.drift_type.as_deref().map(str::to_uppercase),
+            )
+            .filter_opt("active", Op::Eq, params.active);
 
-    pub async fn update_drift_profile_run_dates(
-        transaction: &mut Transaction<'_, Postgres>,
-        service_info: &ServiceInfo,
-        schedule: &str,
-    ) -> Result<(), Error> {
-        let query = Queries::UpdateDriftProfileRunDates.get_query();
+        if let Some(repositories) = repositories {
+            builder.filter_any("repository", repositories);
+        }
 
-        let schedule = Schedule::from_str(schedule)
-            .with_context(|| format!("Failed to parse cron expression: {}", schedule))?;
+        builder
+    }
 
-        let next_run = schedule.upcoming(Utc).take(1).next().with_context(|| {
-            format!(
-                "Failed to get next run time for cron expression: {}",
-                schedule
-            )
-        })?;
+    // Lists drift profile summaries matching the given filters
+    //
+    // # Arguments
+    //
+    // * `params` - Profile list filters and pagination
+    // * `repositories` - Restrict results to these repositories (all if None)
+    //
+    // # Returns
+    //
+    // * A page of profile summaries ordered by repository, name and version
+    pub async fn list_drift_profiles(
+        &self,
+        params: &ProfileListRequest,
+        repositories: Option<&[String]>,
+    ) -> Result<Vec<ProfileSummary>, anyhow::Error> {
+        let mut builder = Self::profile_query(Queries::ListDriftProfiles, params, repositories);
+        builder
+            .order_by("repository, name, version")
+            .limit(params.limit)
+            .offset(params.offset);
+
+        let result: Result<Vec<ProfileSummary>, sqlx::Error> = builder
+            .build()?
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await;
 
-        let query_result = sqlx::query(&query.sql)
-            .bind(next_run.naive_utc())
-            .bind(&service_info.name)
+        match result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to list drift profiles from database: {:?}", e);
+                Err(anyhow!(
+                    "Failed to list drift profiles from database: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
+    pub async fn count_drift_profiles(
+        &self,
+        params: &ProfileListRequest,
+        repositories: Option<&[String]>,
+    ) -> Result<i64, anyhow::Error> {
+        let result = Self::profile_query(Queries::CountDriftProfiles, params, repositories)
+            .build()?
+            .build()
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(row) => Ok(row.get("total")),
+            Err(e) => {
+                error!("Failed to count drift profiles in database: {:?}", e);
+                Err(anyhow!(
+                    "Failed to count drift profiles in database: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
+    pub async fn get_drift_profile_task(
+        transaction: &mut Transaction<'_, Postgres>,
+    ) -> Result<Option<TaskRequest>, Error> {
+        let query = Queries::GetDriftTask.get_query();
+        let result: Result<Option<TaskRequest>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .fetch_optional(&mut **transaction)
+            .await;
+
+        result.map_err(|e| {
+            error!("Failed to get drift task from database: {:?}", e);
+            anyhow!("Failed to get drift task from database: {:?}", e)
+        })
+    }
+
+    // Loads the task for a single profile regardless of its schedule
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service to load the profile task for
+    // * `drift_type` - Drift type of the profile, the drift profile ahead of the observability profile if None
+    //
+    // # Returns
+    //
+    // * The profile task, or None if the profile doesn't exist
+    pub async fn get_profile_task(
+        &self,
+        service_info: &ServiceInfo,
+        drift_type: Option<&str>,
+    ) -> Result<Option<TaskRequest>, anyhow::Error> {
+        let query = Queries::GetDriftProfileTask.get_query();
+        let result: Result<Option<TaskRequest>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(drift_type.map(str::to_uppercase))
+            .fetch_optional(&self.pool)
+            .await;
+
+        result.map_err(|e| {
+            error!("Failed to get drift profile task from database: {:?}", e);
+            anyhow!("Failed to get drift profile task from database: {:?}", e)
+        })
+    }
+
+    // Moves a profile on to its next scheduled run
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service whose profile ran
+    // * `drift_type` - Drift type of the profile that ran
+    // * `schedule` - Cron schedule of the profile
+    // * `previous_run` - Latest scheduled time covered by the run, the stored next run if None
+    pub async fn update_drift_profile_run_dates(
+        transaction: &mut Transaction<'_, Postgres>,
+        service_info: &ServiceInfo,
+        drift_type: &str,
+        schedule: &str,
+        previous_run: Option<NaiveDateTime>,
+    ) -> Result<(), Error> {
+        let query = Queries::UpdateDriftProfileRunDates.get_query();
+
+        let next_run = next_run(schedule)?;
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(next_run.naive_utc())
+            .bind(&service_info.name)
             .bind(&service_info.repository)
             .bind(&service_info.version)
+            .bind(previous_run)
+            .bind(drift_type)
             .execute(&mut **transaction)
             .await;
 
@@ -386,17 +818,69 @@ impl PostgresClient {
         }
     }
 
-    // Queries the database for all features under a service
-    // Private method that'll be used to run drift retrieval in parallel
-    async fn get_features(&self, service_info: &ServiceInfo) -> Result<Vec<String>, anyhow::Error> {
-        let query = Queries::GetFeatures.get_query();
+    // Catalogues the features of a service from its stored records. Used for services whose
+    // records were written without going through ingestion, e.g. by an older server
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service to catalogue
+    //
+    // # Returns
+    //
+    // * Number of features added to the catalogue
+    async fn backfill_features(&self, service_info: &ServiceInfo) -> Result<i64, anyhow::Error> {
+        let query = Queries::BackfillFeatures.get_query();
 
-        sqlx::query(&query.sql)
+        let query_result = sqlx::query(&query.sql)
             .bind(&service_info.name)
             .bind(&service_info.repository)
             .bind(&service_info.version)
-            .fetch_all(&self.pool)
-            .await
+            .fetch_one(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(row) => {
+                let inserted: i64 = row.get("inserted");
+
+                if inserted > 0 {
+                    info!(
+                        "Backfilled {} features for {}/{}/{}",
+                        inserted, service_info.repository, service_info.name, service_info.version
+                    );
+                }
+
+                Ok(inserted)
+            }
+            Err(e) => {
+                error!("Failed to backfill feature catalogue: {:?}", e);
+                Err(anyhow!("Failed to backfill feature catalogue: {:?}", e))
+            }
+        }
+    }
+
+    // Queries the feature catalogue for the names of all features under a service
+    // Private method that'll be used to run drift retrieval in parallel
+    async fn get_features(&self, service_info: &ServiceInfo) -> Result<Vec<String>, anyhow::Error> {
+        let query = Queries::GetFeatures.get_query();
+
+        let run_query = || {
+            sqlx::query(&query.sql)
+                .bind(&service_info.name)
+                .bind(&service_info.repository)
+                .bind(&service_info.version)
+                .fetch_all(&self.pool)
+        };
+
+        let mut result = run_query().await;
+
+        // services that were never catalogued are backfilled once, on first read
+        if result.as_ref().is_ok_and(|rows| rows.is_empty())
+            && self.backfill_features(service_info).await? > 0
+        {
+            result = run_query().await;
+        }
+
+        result
             .map_err(|e| {
                 error!("Failed to get features from database: {:?}", e);
                 anyhow!("Failed to get features from database: {:?}", e)
@@ -409,21 +893,73 @@ impl PostgresClient {
             })
     }
 
+    // Lists the catalogued features for a service
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service to list features for
+    //
+    // # Returns
+    //
+    // * Features with when they were first and last seen and how many records they have
+    pub async fn get_feature_catalogue(
+        &self,
+        service_info: &ServiceInfo,
+    ) -> Result<Vec<FeatureRecord>, anyhow::Error> {
+        let query = Queries::GetFeatureCatalogue.get_query();
+
+        let run_query = || {
+            sqlx::query_as(&query.sql)
+                .bind(&service_info.name)
+                .bind(&service_info.repository)
+                .bind(&service_info.version)
+                .fetch_all(&self.pool)
+        };
+
+        let mut features: Result<Vec<FeatureRecord>, sqlx::Error> = run_query().await;
+
+        // services that were never catalogued are backfilled once, on first read
+        if features.as_ref().is_ok_and(|features| features.is_empty())
+            && self.backfill_features(service_info).await? > 0
+        {
+            features = run_query().await;
+        }
+
+        match features {
+            Ok(features) => Ok(features),
+            Err(e) => {
+                error!("Failed to get feature catalogue from database: {:?}", e);
+                Err(anyhow!(
+                    "Failed to get feature catalogue from database: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
     async fn run_spc_feature_query(
         &self,
         feature: &str,
         service_info: &ServiceInfo,
         limit_timestamp: &str,
-    ) -> Result<SpcFeatureResult, anyhow::Error> {
+        end_timestamp: Option<NaiveDateTime>,
+    ) -> Result<Option<SpcFeatureResult>, anyhow::Error> {
         let query = Queries::GetFeatureValues.get_query();
 
-        let feature_values: Result<SpcFeatureResult, anyhow::Error> = sqlx::query_as(&query.sql)
+        let mut builder = SqlBuilder::new(&query.sql);
+        builder
             .bind(limit_timestamp)
             .bind(&service_info.name)
             .bind(&service_info.repository)
             .bind(&service_info.version)
-            .bind(feature)
-            .fetch_one(&self.pool)
+            .filter("feature", Op::Eq, feature)
+            .filter_opt("created_at", Op::Lte, end_timestamp);
+
+        // features without records since the limit timestamp return no row
+        let feature_values: Result<Option<SpcFeatureResult>, anyhow::Error> = builder
+            .build()?
+            .build_query_as()
+            .fetch_optional(&self.pool)
             .await
             .map_err(|e| {
                 error!("Failed to run query: {:?}", e);
@@ -436,128 +972,131 @@ impl PostgresClient {
     pub async fn get_binned_observability_metrics(
         &self,
         params: &ObservabilityMetricRequest,
+        time_range: &TimeRange,
     ) -> Result<Vec<ObservabilityResult>, anyhow::Error> {
         let query = Queries::GetBinnedObservabilityMetrics.get_query();
 
-        let time_window = TimeInterval::from_string(&params.time_window).to_minutes();
-
-        let bin = time_window as f64 / params.max_data_points as f64;
+        let mut builder = SqlBuilder::new(&query.sql);
+        builder
+            .bind(time_range.bin_seconds())
+            .bind(time_range.start)
+            .bind(time_range.end)
+            .bind(&params.name)
+            .bind(&params.repository)
+            .bind(&params.version)
+            .bind(time_range.bin_seconds())
+            .bind(time_range.start)
+            .bind(time_range.end)
+            .bind(time_range.bin_seconds())
+            .bind(params.fill_gaps.unwrap_or(false))
+            .bind(&params.name)
+            .bind(&params.repository)
+            .bind(&params.version)
+            .bind(time_range.end);
 
-        let observability_metrics: Result<Vec<ObservabilityResult>, sqlx::Error> =
-            sqlx::query_as(&query.sql)
-                .bind(bin)
-                .bind(time_window)
-                .bind(&params.name)
-                .bind(&params.repository)
-                .bind(&params.version)
-                .fetch_all(&self.pool)
-                .await;
+        let observability_metrics: Result<Vec<ObservabilityResult>, sqlx::Error> = builder
+            .build()?
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await;
 
-        observability_metrics.map_err(|e| {
+        let mut observability_metrics = observability_metrics.map_err(|e| {
             error!("Failed to run query: {:?}", e);
             anyhow!("Failed to run query: {:?}", e)
-        })
-    }
-
-    async fn get_spc_binned_feature_values(
-        &self,
-        bin: &f64,
-        feature: String,
-        version: &str,
-        time_window: &i32,
-        repository: &str,
-        name: &str,
-    ) -> Result<SpcFeatureResult, anyhow::Error> {
-        let query = Queries::GetBinnedFeatureValues.get_query();
+        })?;
 
-        let binned: Result<SpcFeatureResult, sqlx::Error> = sqlx::query_as(&query.sql)
-            .bind(bin)
-            .bind(time_window)
-            .bind(name)
-            .bind(repository)
-            .bind(version)
-            .bind(feature)
-            .fetch_one(&self.pool)
-            .await;
+        for result in observability_metrics.iter_mut() {
+            for (bin, e) in result.merge_sketches() {
+                warn!(
+                    "Falling back to averaged percentiles for {} at {}: {}",
+                    result.route_name, result.created_at[bin], e
+                );
+            }
+        }
 
-        binned.map_err(|e| {
-            error!("Failed to run query: {:?}", e);
-            anyhow!("Failed to run query: {:?}", e)
-        })
+        Ok(observability_metrics)
     }
 
-    // Queries the database for drift records based on a time window and aggregation
+    // Queries the database for drift records binned over a time range. All
+    // features are binned in a single query
     //
     // # Arguments
     //
-    // * `name` - The name of the service to query drift records for
-    // * `repository` - The name of the repository to query drift records for
-    // * `feature` - The name of the feature to query drift records for
-    // * `aggregation` - The aggregation to use for the query
-    // * `time_window` - The time window to query drift records for
+    // * `params` - The service (and optionally the features) to query drift records for
+    // * `time_range` - The validated time range and number of bins to query
     //
     // # Returns
     //
-    // * A vector of drift records
+    // * Binned drift records keyed by feature
     pub async fn get_binned_drift_records(
         &self,
         params: &DriftRequest,
-    ) -> Result<QueryResult, anyhow::Error> {
-        let service_info = ServiceInfo {
-            repository: params.repository.clone(),
-            name: params.name.clone(),
-            version: params.version.clone(),
-        };
-        // get features
-        let features = self.get_features(&service_info).await?;
-
-        let time_window = TimeInterval::from_string(&params.time_window).to_minutes();
-
-        let bin = time_window as f64 / params.max_data_points as f64;
-
-        let async_queries = features
-            .iter()
-            .map(|feature| {
-                self.get_spc_binned_feature_values(
-                    &bin,
-                    feature.to_string(),
-                    &params.version,
-                    &time_window,
-                    &params.repository,
-                    &params.name,
-                )
-            })
-            .collect::<Vec<_>>();
-
-        let query_results = join_all(async_queries).await;
+        time_range: &TimeRange,
+    ) -> Result<BinnedQueryResult, anyhow::Error> {
+        let query = Queries::GetBinnedFeatureValues.get_query();
+        let aggregation = params.aggregation.unwrap_or_default();
+        let bands = params.bands.unwrap_or(false);
+        let features = params.feature_list();
+
+        let mut builder = SqlBuilder::new(&query.sql);
+        builder
+            .bind(time_range.bin_seconds())
+            .expr(aggregation.as_sql())
+            .bind(time_range.start)
+            .bind(time_range.end)
+            .bind(&params.name)
+            .bind(&params.repository)
+            .bind(&params.version)
+            .filter_any("feature", &features)
+            .bind(time_range.bin_seconds())
+            .bind(time_range.start)
+            .bind(time_range.end)
+            .bind(time_range.bin_seconds())
+            .bind(params.fill_gaps.unwrap_or(false))
+            .bind(&params.name)
+            .bind(&params.repository)
+            .bind(&params.version)
+            .bind(time_range.end)
+            .filter_any("feature", &features);
 
-        // parse results
-        let mut query_result = QueryResult {
-            features: BTreeMap::new(),
-        };
+        let binned: Result<Vec<BinnedFeatureResult>, sqlx::Error> = builder
+            .build()?
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await;
 
-        query_results.iter().for_each(|result| match result {
-            Ok(result) => {
-                query_result.features.insert(
-                    result.feature.clone(),
-                    FeatureResult {
-                        created_at: result.created_at.clone(),
-                        values: result.values.clone(),
-                    },
-                );
+        match binned {
+            Ok(binned) => {
+                let features = binned
+                    .into_iter()
+                    .map(|result| {
+                        (
+                            result.feature,
+                            BinnedFeature {
+                                created_at: result.created_at,
+                                values: result.values,
+                                sample_count: result.sample_count,
+                                min: bands.then_some(result.min_values),
+                                max: bands.then_some(result.max_values),
+                            },
+                        )
+                    })
+                    .collect();
+
+                Ok(BinnedQueryResult { features })
             }
             Err(e) => {
                 error!("Failed to run query: {:?}", e);
+                Err(anyhow!("Failed to run query: {:?}", e))
             }
-        });
-
-        Ok(query_result)
+        }
     }
 
     pub async fn get_drift_records(
         &self,
         service_info: &ServiceInfo,
         limit_timestamp: &str,
+        end_timestamp: Option<NaiveDateTime>,
         features_to_monitor: &[String],
     ) -> Result<QueryResult, anyhow::Error> {
         let mut features = self.get_features(service_info).await?;
@@ -569,7 +1108,14 @@ impl PostgresClient {
         let query_results = join_all(
             features
                 .iter()
-                .map(|feature| self.run_spc_feature_query(feature, service_info, limit_timestamp))
+                .map(|feature| {
+                    self.run_spc_feature_query(
+                        feature,
+                        service_info,
+                        limit_timestamp,
+                        end_timestamp,
+                    )
+                })
                 .collect::<Vec<_>>(),
         )
         .await;
@@ -581,8 +1127,8 @@ impl PostgresClient {
         let feature_sizes = query_results
             .iter()
             .map(|result| match result {
-                Ok(result) => result.values.len(),
-                Err(_) => 0,
+                Ok(Some(result)) => result.values.len(),
+                Ok(None) | Err(_) => 0,
             })
             .collect::<Vec<_>>();
 
@@ -604,7 +1150,7 @@ impl PostgresClient {
 
         for data in query_results {
             match data {
-                Ok(data) if !data.values.is_empty() => {
+                Ok(Some(data)) if !data.values.is_empty() => {
                     query_result.features.insert(
                         data.feature.clone(),
                         FeatureResult {
@@ -623,41 +1169,722 @@ impl PostgresClient {
         Ok(query_result)
     }
 
-    #[allow(dead_code)]
-    pub async fn raw_query(&self, query: &str) -> Result<Vec<PgRow>, anyhow::Error> {
-        let result = sqlx::raw_sql(query).fetch_all(&self.pool).await;
+    // Counts the records received per monitored feature since the previous run
+    //
+    // # Arguments
+    //
+    // * `source` - Records to count, spc values or psi bin counts
+    // * `service_info` - The service to count records for
+    // * `features_to_monitor` - Features to count (all catalogued features if empty)
+    // * `previous_run` - Start of the current run window
+    // * `lookback_runs` - Number of preceding run windows the trailing average covers
+    //
+    // # Returns
+    //
+    // * Current record count and trailing average per feature
+    pub async fn get_feature_volumes(
+        &self,
+        source: VolumeSource,
+        service_info: &ServiceInfo,
+        features_to_monitor: &[String],
+        previous_run: NaiveDateTime,
+        lookback_runs: i32,
+    ) -> Result<Vec<FeatureVolume>, anyhow::Error> {
+        let query = Queries::GetFeatureVolume.get_query();
+
+        let mut builder = SqlBuilder::new(&query.sql);
+        builder
+            .bind(features_to_monitor)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(source.data_type())
+            .bind(features_to_monitor)
+            .bind(previous_run)
+            .bind(previous_run)
+            .bind(lookback_runs)
+            .expr(source.table())
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version);
 
-        match result {
-            Ok(result) => {
-                // pretty print
-                Ok(result)
-            }
+        let volumes: Result<Vec<FeatureVolume>, sqlx::Error> = builder
+            .build()?
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await;
+
+        match volumes {
+            Ok(volumes) => Ok(volumes),
             Err(e) => {
-                error!("Failed to run query: {:?}", e);
-                Err(anyhow!("Failed to run query: {:?}", e))
+                error!("Failed to get feature volumes from database: {:?}", e);
+                Err(anyhow!(
+                    "Failed to get feature volumes from database: {:?}",
+                    e
+                ))
             }
         }
     }
 
-    pub async fn update_drift_profile_status(
+    // Aggregates the route metrics recorded for a service within a time window
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service to summarize route metrics for
+    // * `since` - Only metrics recorded after this timestamp are included
+    // * `until` - Only metrics recorded at or before this timestamp are included
+    //
+    // # Returns
+    //
+    // * Request and error counts with request-weighted p95/p99 latency per route
+    pub async fn get_route_summaries(
         &self,
-        params: &ProfileStatusRequest,
-    ) -> Result<(), anyhow::Error> {
-        let query = Queries::UpdateDriftProfileStatus.get_query();
+        service_info: &ServiceInfo,
+        since: NaiveDateTime,
+        until: NaiveDateTime,
+    ) -> Result<Vec<RouteSummary>, anyhow::Error> {
+        let query = Queries::GetObservabilityRouteSummary.get_query();
 
-        let query_result = sqlx::query(&query.sql)
-            .bind(params.active)
-            .bind(&params.name)
-            .bind(&params.repository)
-            .bind(&params.version)
-            .execute(&self.pool)
+        let summaries: Result<Vec<RouteSummary>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(since)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(until)
+            .fetch_all(&self.pool)
             .await;
 
-        match query_result {
-            Ok(_) => Ok(()),
+        match summaries {
+            Ok(mut summaries) => {
+                for summary in summaries.iter_mut() {
+                    if let Err(e) = summary.merge_sketches() {
+                        warn!(
+                            "Falling back to the highest reported percentiles for {}: {}",
+                            summary.route_name, e
+                        );
+                    }
+                }
+
+                Ok(summaries)
+            }
             Err(e) => {
-                error!("Failed to update drift profile status: {:?}", e);
-                Err(anyhow!("Failed to update drift profile status: {:?}", e))
+                error!("Failed to get route summaries from database: {:?}", e);
+                Err(anyhow!(
+                    "Failed to get route summaries from database: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
+    // Queries the database for observed psi bin counts since a given timestamp
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service to query bin counts for
+    // * `limit_datetime` - Only bin counts recorded after this timestamp are returned
+    // * `end_datetime` - Only bin counts recorded at or before this timestamp are returned (no upper bound if None)
+    // * `features_to_monitor` - Features to return bin counts for (all if empty)
+    //
+    // # Returns
+    //
+    // * A vector of summed bin counts per feature and bin
+    pub async fn get_observed_bin_counts(
+        &self,
+        service_info: &ServiceInfo,
+        limit_datetime: &NaiveDateTime,
+        end_datetime: Option<NaiveDateTime>,
+        features_to_monitor: &[String],
+    ) -> Result<Vec<FeatureBinCount>, anyhow::Error> {
+        let query = Queries::GetObservedBinCounts.get_query();
+
+        let mut builder = SqlBuilder::new(&query.sql);
+        builder
+            .bind(limit_datetime)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .filter_opt("created_at", Op::Lte, end_datetime)
+            .filter_any("feature", features_to_monitor);
+
+        let bin_counts: Result<Vec<FeatureBinCount>, sqlx::Error> = builder
+            .build()?
+            .build_query_as()
+            .fetch_all(&self.pool)
+            .await;
+
+        bin_counts.map_err(|e| {
+            error!("Failed to get observed bin counts from database: {:?}", e);
+            anyhow!("Failed to get observed bin counts from database: {:?}", e)
+        })
+    }
+
+    #[allow(dead_code)]
+    pub async fn raw_query(&self, query: &str) -> Result<Vec<PgRow>, anyhow::Error> {
+        let result = sqlx::raw_sql(query).fetch_all(&self.pool).await;
+
+        match result {
+            Ok(result) => {
+                // pretty print
+                Ok(result)
+            }
+            Err(e) => {
+                error!("Failed to run query: {:?}", e);
+                Err(anyhow!("Failed to run query: {:?}", e))
+            }
+        }
+    }
+
+    pub async fn update_drift_profile_status(
+        &self,
+        params: &ProfileStatusRequest,
+    ) -> Result<(), anyhow::Error> {
+        let query = Queries::UpdateDriftProfileStatus.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(params.active)
+            .bind(&params.name)
+            .bind(&params.repository)
+            .bind(&params.version)
+            .bind(params.drift_type.as_deref().map(str::to_uppercase))
+            .execute(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to update drift profile status: {:?}", e);
+                Err(anyhow!("Failed to update drift profile status: {:?}", e))
+            }
+        }
+    }
+
+    // Sets how the drift executor handles missed scheduled runs of a profile
+    //
+    // # Arguments
+    //
+    // * `params` - Profile and catch-up policy
+    //
+    // # Returns
+    //
+    // * `bool` - Whether a profile was updated
+    pub async fn update_drift_profile_catch_up(
+        &self,
+        params: &ProfileCatchUpRequest,
+    ) -> Result<bool, anyhow::Error> {
+        let query = Queries::UpdateDriftProfileCatchUp.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(params.catch_up.as_str())
+            .bind(&params.name)
+            .bind(&params.repository)
+            .bind(&params.version)
+            .bind(params.drift_type.as_deref().map(str::to_uppercase))
+            .execute(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(e) => {
+                error!("Failed to update drift profile catch-up policy: {:?}", e);
+                Err(anyhow!(
+                    "Failed to update drift profile catch-up policy: {:?}",
+                    e
+                ))
+            }
+        }
+    }
+
+    // Deletes a drift profile so it is no longer scheduled
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service whose profile should be deleted
+    // * `drift_type` - Drift type of the profile to delete, every profile of the service if None
+    //
+    // # Returns
+    //
+    // * `bool` - Whether a profile was deleted
+    pub async fn delete_drift_profile(
+        &self,
+        service_info: &ServiceInfo,
+        drift_type: Option<&str>,
+    ) -> Result<bool, anyhow::Error> {
+        let query = Queries::DeleteDriftProfile.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(drift_type.map(str::to_uppercase))
+            .fetch_all(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(!result.is_empty()),
+            Err(e) => {
+                error!("Failed to delete drift profile: {:?}", e);
+                Err(anyhow!("Failed to delete drift profile: {:?}", e))
+            }
+        }
+    }
+
+    pub async fn insert_purge_job(
+        &self,
+        service_info: &ServiceInfo,
+        requested_by: &str,
+    ) -> Result<PurgeJob, anyhow::Error> {
+        let query = Queries::InsertPurgeJob.get_query();
+
+        let query_result: Result<PurgeJob, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(requested_by)
+            .fetch_one(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to insert purge job: {:?}", e);
+                Err(anyhow!("Failed to insert purge job: {:?}", e))
+            }
+        }
+    }
+
+    // Records the outcome of a drift task run
+    //
+    // # Arguments
+    //
+    // * `run` - The completed run
+    pub async fn insert_drift_task_run(&self, run: &DriftTaskRun) -> Result<(), anyhow::Error> {
+        let query = Queries::InsertDriftTaskRun.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(&run.name)
+            .bind(&run.repository)
+            .bind(&run.version)
+            .bind(&run.drift_type)
+            .bind(run.window_start)
+            .bind(run.window_end)
+            .bind(run.duration_ms)
+            .bind(run.rows_read)
+            .bind(run.alerts_produced)
+            .bind(&run.status)
+            .bind(&run.error)
+            .bind(&run.catch_up)
+            .execute(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to insert drift task run: {:?}", e);
+                Err(anyhow!("Failed to insert drift task run: {:?}", e))
+            }
+        }
+    }
+
+    // Returns the most recent drift task runs for a service, newest first
+    //
+    // # Arguments
+    //
+    // * `service_info` - The service to get runs for
+    // * `limit` - Maximum number of runs to return
+    pub async fn get_drift_task_runs(
+        &self,
+        service_info: &ServiceInfo,
+        limit: i64,
+    ) -> Result<Vec<DriftTaskRun>, anyhow::Error> {
+        let query = Queries::GetDriftTaskRuns.get_query();
+
+        let query_result: Result<Vec<DriftTaskRun>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get drift task runs: {:?}", e);
+                Err(anyhow!("Failed to get drift task runs: {:?}", e))
+            }
+        }
+    }
+
+    pub async fn get_purge_job(&self, id: i32) -> Result<Option<PurgeJob>, anyhow::Error> {
+        let query = Queries::GetPurgeJob.get_query();
+
+        let query_result: Result<Option<PurgeJob>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get purge job: {:?}", e);
+                Err(anyhow!("Failed to get purge job: {:?}", e))
+            }
+        }
+    }
+
+    // Returns purge jobs that were pending or running, e.g. when the server stopped mid purge
+    pub async fn get_unfinished_purge_jobs(&self) -> Result<Vec<PurgeJob>, anyhow::Error> {
+        let query = Queries::GetUnfinishedPurgeJobs.get_query();
+
+        let query_result: Result<Vec<PurgeJob>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind([PurgeStatus::Pending.as_str(), PurgeStatus::Running.as_str()])
+            .fetch_all(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get unfinished purge jobs: {:?}", e);
+                Err(anyhow!("Failed to get unfinished purge jobs: {:?}", e))
+            }
+        }
+    }
+
+    // Marks a purge job as running so no other instance picks it up. Returns None if the
+    // job is finished or is still being run elsewhere
+    //
+    // # Arguments
+    //
+    // * `id` - Id of the purge job
+    // * `timeout_secs` - Seconds without progress after which a running job can be taken over
+    async fn claim_purge_job(
+        &self,
+        id: i32,
+        timeout_secs: i64,
+    ) -> Result<Option<PurgeJob>, anyhow::Error> {
+        let query = Queries::ClaimPurgeJob.get_query();
+
+        let query_result: Result<Option<PurgeJob>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(id)
+            .bind(timeout_secs)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to claim purge job: {:?}", e);
+                Err(anyhow!("Failed to claim purge job: {:?}", e))
+            }
+        }
+    }
+
+    async fn update_purge_job(
+        &self,
+        id: i32,
+        status: PurgeStatus,
+        rows_deleted: i64,
+        error: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let query = Queries::UpdatePurgeJob.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(id)
+            .bind(status.as_str())
+            .bind(rows_deleted)
+            .bind(error)
+            .execute(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                error!("Failed to update purge job: {:?}", e);
+                Err(anyhow!("Failed to update purge job: {:?}", e))
+            }
+        }
+    }
+
+    // Deletes up to `batch_size` rows for a service using one of the purge queries
+    async fn purge_batch(
+        &self,
+        query: &Queries,
+        service_info: &ServiceInfo,
+        batch_size: i64,
+    ) -> Result<i64, anyhow::Error> {
+        let query = query.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(&service_info.name)
+            .bind(&service_info.repository)
+            .bind(&service_info.version)
+            .bind(batch_size)
+            .fetch_one(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(row) => Ok(row.get("deleted")),
+            Err(e) => {
+                error!("Failed to purge service data: {:?}", e);
+                Err(anyhow!("Failed to purge service data: {:?}", e))
+            }
+        }
+    }
+
+    // Purges all drift, alert and observability data recorded for the job's service
+    //
+    // Rows are deleted in batches so large services don't hold long locks, and the
+    // job's rows_deleted is updated after every batch. Purging is idempotent, so an
+    // interrupted job can simply be run again. The job is claimed before it runs, so
+    // when several instances try to resume it only one does.
+    //
+    // # Arguments
+    //
+    // * `job` - The purge job to run
+    //
+    // # Returns
+    //
+    // * `Option<i64>` - Total number of rows deleted, None if the job was claimed elsewhere
+    pub async fn run_purge_job(&self, job: &PurgeJob) -> Result<Option<i64>, anyhow::Error> {
+        let batch_size = std::env::var("SCOUTER_PURGE_BATCH_SIZE")
+            .ok()
+            .and_then(|size| size.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_PURGE_BATCH_SIZE);
+        let timeout_secs = std::env::var("SCOUTER_PURGE_JOB_TIMEOUT_SECS")
+            .ok()
+            .and_then(|secs| secs.parse::<i64>().ok())
+            .unwrap_or(DEFAULT_PURGE_JOB_TIMEOUT_SECS);
+
+        let Some(job) = self.claim_purge_job(job.id, timeout_secs).await? else {
+            info!("Purge job {} is finished or running elsewhere", job.id);
+            return Ok(None);
+        };
+
+        let service_info = ServiceInfo {
+            name: job.name.clone(),
+            repository: job.repository.clone(),
+            version: job.version.clone(),
+        };
+
+        info!(
+            "Running purge job {} for {}/{}/{}",
+            job.id, job.repository, job.name, job.version
+        );
+
+        let mut rows_deleted = job.rows_deleted;
+
+        let queries = [
+            Queries::PurgeDriftAlerts,
+            Queries::PurgeDrift,
+            Queries::PurgeObservedBinCount,
+            Queries::PurgeObservabilityMetrics,
+            Queries::PurgeFeatures,
+            Queries::PurgeDriftTaskRuns,
+        ];
+
+        for query in queries.iter() {
+            loop {
+                let deleted = match self.purge_batch(query, &service_info, batch_size).await {
+                    Ok(deleted) => deleted,
+                    Err(e) => {
+                        self.update_purge_job(
+                            job.id,
+                            PurgeStatus::Failed,
+                            rows_deleted,
+                            Some(&e.to_string()),
+                        )
+                        .await?;
+                        return Err(e);
+                    }
+                };
+
+                rows_deleted += deleted;
+                self.update_purge_job(job.id, PurgeStatus::Running, rows_deleted, None)
+                    .await?;
+
+                if deleted < batch_size {
+                    break;
+                }
+            }
+        }
+
+        self.update_purge_job(job.id, PurgeStatus::Completed, rows_deleted, None)
+            .await?;
+
+        info!(
+            "Purge job {} completed, {} rows deleted",
+            job.id, rows_deleted
+        );
+
+        Ok(Some(rows_deleted))
+    }
+
+    // Inserts a hashed api key into the database
+    //
+    // # Arguments
+    //
+    // * `name` - Human readable name of the key
+    // * `key_prefix` - Non-secret prefix used to identify the key
+    // * `key_hash` - Sha256 hash of the full key
+    // * `scopes` - Scopes granted to the key
+    // * `repositories` - Optional list of repositories the key is restricted to
+    //
+    pub async fn insert_api_key(
+        &self,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        scopes: &[String],
+        repositories: Option<&[String]>,
+    ) -> Result<Option<ApiKeyRecord>, anyhow::Error> {
+        let query = Queries::InsertApiKey.get_query();
+
+        let query_result: Result<Option<ApiKeyRecord>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(name)
+            .bind(key_prefix)
+            .bind(key_hash)
+            .bind(scopes)
+            .bind(repositories)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to insert api key into database: {:?}", e);
+                Err(anyhow!("Failed to insert api key into database: {:?}", e))
+            }
+        }
+    }
+
+    pub async fn get_api_key(&self, key_hash: &str) -> Result<Option<ApiKeyRecord>, anyhow::Error> {
+        let query = Queries::GetApiKey.get_query();
+
+        let query_result: Result<Option<ApiKeyRecord>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get api key from database: {:?}", e);
+                Err(anyhow!("Failed to get api key from database: {:?}", e))
+            }
+        }
+    }
+
+    // Gets an active api key by id. Returns None if the key does not exist or was revoked
+    pub async fn get_api_key_by_id(&self, id: i32) -> Result<Option<ApiKeyRecord>, anyhow::Error> {
+        let query = Queries::GetApiKeyById.get_query();
+
+        let query_result: Result<Option<ApiKeyRecord>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get api key from database: {:?}", e);
+                Err(anyhow!("Failed to get api key from database: {:?}", e))
+            }
+        }
+    }
+
+    // Revokes an api key. Returns false if the key does not exist or was already revoked
+    pub async fn revoke_api_key(&self, id: i32) -> Result<bool, anyhow::Error> {
+        let query = Queries::RevokeApiKey.get_query();
+
+        let query_result = sqlx::query(&query.sql).bind(id).execute(&self.pool).await;
+
+        match query_result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(e) => {
+                error!("Failed to revoke api key: {:?}", e);
+                Err(anyhow!("Failed to revoke api key: {:?}", e))
+            }
+        }
+    }
+
+    // Assigns a role to a subject on a repository, replacing any existing role
+    //
+    // # Arguments
+    //
+    // * `subject` - Subject of the caller (e.g. api_key:1 or jwt:user@example.com)
+    // * `repository` - Repository the role applies to
+    // * `role` - Role to assign
+    //
+    pub async fn upsert_repository_role(
+        &self,
+        subject: &str,
+        repository: &str,
+        role: &str,
+    ) -> Result<RoleBinding, anyhow::Error> {
+        let query = Queries::UpsertRepositoryRole.get_query();
+
+        let query_result: Result<RoleBinding, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(subject)
+            .bind(repository)
+            .bind(role)
+            .fetch_one(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to upsert repository role: {:?}", e);
+                Err(anyhow!("Failed to upsert repository role: {:?}", e))
+            }
+        }
+    }
+
+    // Lists role bindings, optionally filtered by subject and/or repository
+    pub async fn get_repository_roles(
+        &self,
+        subject: Option<&str>,
+        repository: Option<&str>,
+    ) -> Result<Vec<RoleBinding>, anyhow::Error> {
+        let query = Queries::GetRepositoryRoles.get_query();
+
+        let query_result: Result<Vec<RoleBinding>, sqlx::Error> = sqlx::query_as(&query.sql)
+            .bind(subject)
+            .bind(repository)
+            .fetch_all(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                error!("Failed to get repository roles: {:?}", e);
+                Err(anyhow!("Failed to get repository roles: {:?}", e))
+            }
+        }
+    }
+
+    // Removes a role binding. Returns false if the binding does not exist
+    pub async fn delete_repository_role(
+        &self,
+        subject: &str,
+        repository: &str,
+    ) -> Result<bool, anyhow::Error> {
+        let query = Queries::DeleteRepositoryRole.get_query();
+
+        let query_result = sqlx::query(&query.sql)
+            .bind(subject)
+            .bind(repository)
+            .execute(&self.pool)
+            .await;
+
+        match query_result {
+            Ok(result) => Ok(result.rows_affected() > 0),
+            Err(e) => {
+                error!("Failed to delete repository role: {:?}", e);
+                Err(anyhow!("Failed to delete repository role: {:?}", e))
             }
         }
     }
@@ -690,16 +1917,67 @@ mod tests {
     }
 
     #[test]
-    fn test_time_interval() {
-        assert_eq!(TimeInterval::FiveMinutes.to_minutes(), 5);
-        assert_eq!(TimeInterval::FifteenMinutes.to_minutes(), 15);
-        assert_eq!(TimeInterval::ThirtyMinutes.to_minutes(), 30);
-        assert_eq!(TimeInterval::OneHour.to_minutes(), 60);
-        assert_eq!(TimeInterval::ThreeHours.to_minutes(), 180);
-        assert_eq!(TimeInterval::SixHours.to_minutes(), 360);
-        assert_eq!(TimeInterval::TwelveHours.to_minutes(), 720);
-        assert_eq!(TimeInterval::TwentyFourHours.to_minutes(), 1440);
-        assert_eq!(TimeInterval::TwoDays.to_minutes(), 2880);
-        assert_eq!(TimeInterval::FiveDays.to_minutes(), 7200);
+    fn test_dynamic_query_templates() {
+        // every filter set, so each template has to accept every builder call
+        let alert_request = DriftAlertRequest {
+            name: "test_app".to_string(),
+            repository: "test".to_string(),
+            version: "1.0.0".to_string(),
+            limit_timestamp: Some("2024-01-01T00:00:00".to_string()),
+            end_timestamp: Some("2024-01-02T00:00:00".to_string()),
+            active: Some(true),
+            limit: Some(10),
+            feature: Some("feature_1".to_string()),
+            status: Some("active".to_string()),
+            zone: Some("Zone 1".to_string()),
+            kind: Some("Consecutive".to_string()),
+            cursor: None,
+        };
+        let cursor = AlertCursor {
+            created_at: Utc::now().naive_utc(),
+            id: 1,
+        };
+
+        let mut builder =
+            PostgresClient::alert_query(Queries::GetDriftAlerts, &alert_request, Some(&cursor))
+                .unwrap();
+        builder.order_by("created_at DESC, id DESC").limit(Some(10));
+        assert!(builder.build().is_ok());
+
+        assert!(
+            PostgresClient::alert_query(Queries::CountDriftAlerts, &alert_request, None)
+                .unwrap()
+                .build()
+                .is_ok()
+        );
+
+        let profile_request = ProfileListRequest {
+            repository: Some("test".to_string()),
+            name: Some("test_".to_string()),
+            drift_type: Some("spc".to_string()),
+            active: Some(true),
+            limit: Some(10),
+            offset: Some(10),
+        };
+        let repositories = ["test".to_string()];
+
+        let mut builder = PostgresClient::profile_query(
+            Queries::ListDriftProfiles,
+            &profile_request,
+            Some(&repositories),
+        );
+        builder
+            .order_by("repository, name, version")
+            .limit(profile_request.limit)
+            .offset(profile_request.offset);
+        assert!(builder.build().is_ok());
+
+        assert!(PostgresClient::profile_query(
+            Queries::CountDriftProfiles,
+            &profile_request,
+            Some(&repositories)
+        )
+        .build()
+        .is_ok());
     }
 }
@@ -1,38 +1,112 @@
 //constants
 
-const INSERT_DRIFT_RECORD: &str = include_str!("scripts/insert_drift_record.sql");
+const INSERT_DRIFT_RECORDS: &str = include_str!("scripts/insert_drift_records.sql");
+const INSERT_PSI_DRIFT_RECORDS: &str = include_str!("scripts/insert_psi_drift_records.sql");
+const INSERT_OBSERVABILITY_RECORDS: &str = include_str!("scripts/insert_observability_records.sql");
 const GET_FEATURES: &str = include_str!("scripts/unique_features.sql");
+const GET_FEATURE_CATALOGUE: &str = include_str!("scripts/get_features.sql");
+const BACKFILL_FEATURES: &str = include_str!("scripts/backfill_features.sql");
+const GET_FEATURE_VOLUME: &str = include_str!("scripts/feature_volume.sql");
+const GET_OBSERVABILITY_ROUTE_SUMMARY: &str =
+    include_str!("scripts/observability_route_summary.sql");
 const GET_BINNED_FEATURE_VALUES: &str = include_str!("scripts/binned_feature_values.sql");
 const GET_FEATURE_VALUES: &str = include_str!("scripts/feature_values.sql");
+const GET_OBSERVED_BIN_COUNTS: &str = include_str!("scripts/observed_bin_counts.sql");
 const GET_BINNED_OBSERVABILITY_METRICS: &str =
     include_str!("scripts/binned_observability_metrics.sql");
 const INSERT_DRIFT_PROFILE: &str = include_str!("scripts/insert_drift_profile.sql");
 const INSERT_DRIFT_ALERT: &str = include_str!("scripts/insert_drift_alert.sql");
-const INSERT_OBSERVABILITY_RECORD: &str = include_str!("scripts/insert_observability_record.sql");
 const GET_DRIFT_TASK: &str = include_str!("scripts/poll_for_drift_task.sql");
+const GET_DRIFT_PROFILE_TASK: &str = include_str!("scripts/get_drift_profile_task.sql");
 const GET_DRIFT_ALERTS: &str = include_str!("scripts/get_drift_alerts.sql");
+const COUNT_DRIFT_ALERTS: &str = include_str!("scripts/count_drift_alerts.sql");
+const LIST_DRIFT_PROFILES: &str = include_str!("scripts/list_drift_profiles.sql");
+const COUNT_DRIFT_PROFILES: &str = include_str!("scripts/count_drift_profiles.sql");
+const DELETE_DRIFT_PROFILE: &str = include_str!("scripts/delete_drift_profile.sql");
+const INSERT_PURGE_JOB: &str = include_str!("scripts/insert_purge_job.sql");
+const GET_PURGE_JOB: &str = include_str!("scripts/get_purge_job.sql");
+const GET_UNFINISHED_PURGE_JOBS: &str = include_str!("scripts/get_unfinished_purge_jobs.sql");
+const UPDATE_PURGE_JOB: &str = include_str!("scripts/update_purge_job.sql");
+const CLAIM_PURGE_JOB: &str = include_str!("scripts/claim_purge_job.sql");
+const PURGE_DRIFT: &str = include_str!("scripts/purge_drift.sql");
+const PURGE_DRIFT_ALERTS: &str = include_str!("scripts/purge_drift_alerts.sql");
+const PURGE_OBSERVABILITY_METRICS: &str = include_str!("scripts/purge_observability_metrics.sql");
+const PURGE_FEATURES: &str = include_str!("scripts/purge_features.sql");
+const PURGE_DRIFT_TASK_RUNS: &str = include_str!("scripts/purge_drift_task_runs.sql");
+const INSERT_DRIFT_TASK_RUN: &str = include_str!("scripts/insert_drift_task_run.sql");
+const GET_DRIFT_TASK_RUNS: &str = include_str!("scripts/get_drift_task_runs.sql");
+const PURGE_OBSERVED_BIN_COUNT: &str = include_str!("scripts/purge_observed_bin_count.sql");
+const GET_DRIFT_ALERT: &str = include_str!("scripts/get_drift_alert.sql");
+const GET_DRIFT_ALERT_HISTORY: &str = include_str!("scripts/get_drift_alert_history.sql");
+const UPDATE_DRIFT_ALERT_STATUS: &str = include_str!("scripts/update_drift_alert_status.sql");
+const RESOLVE_DRIFT_ALERTS: &str = include_str!("scripts/resolve_drift_alerts.sql");
 const GET_DRIFT_PROFILE: &str = include_str!("scripts/get_drift_profile.sql");
 const UPDATE_DRIFT_PROFILE_RUN_DATES: &str =
     include_str!("scripts/update_drift_profile_run_dates.sql");
 const UPDATE_DRIFT_PROFILE_STATUS: &str = include_str!("scripts/update_drift_profile_status.sql");
+const UPDATE_DRIFT_PROFILE_CATCH_UP: &str =
+    include_str!("scripts/update_drift_profile_catch_up.sql");
 const UPDATE_DRIFT_PROFILE: &str = include_str!("scripts/update_drift_profile.sql");
+const INSERT_API_KEY: &str = include_str!("scripts/insert_api_key.sql");
+const GET_API_KEY: &str = include_str!("scripts/get_api_key.sql");
+const GET_API_KEY_BY_ID: &str = include_str!("scripts/get_api_key_by_id.sql");
+const REVOKE_API_KEY: &str = include_str!("scripts/revoke_api_key.sql");
+const UPSERT_REPOSITORY_ROLE: &str = include_str!("scripts/upsert_repository_role.sql");
+const GET_REPOSITORY_ROLES: &str = include_str!("scripts/get_repository_roles.sql");
+const DELETE_REPOSITORY_ROLE: &str = include_str!("scripts/delete_repository_role.sql");
 
 #[allow(dead_code)]
 pub enum Queries {
     GetFeatures,
-    InsertDriftRecord,
+    GetFeatureCatalogue,
+    BackfillFeatures,
+    GetFeatureVolume,
+    GetObservabilityRouteSummary,
+    InsertDriftRecords,
+    InsertPsiDriftRecords,
+    InsertObservabilityRecords,
     InsertDriftProfile,
     InsertDriftAlert,
-    InsertObservabilityRecord,
     GetDriftAlerts,
+    CountDriftAlerts,
+    GetDriftAlert,
+    GetDriftAlertHistory,
+    UpdateDriftAlertStatus,
+    ResolveDriftAlerts,
     GetBinnedFeatureValues,
     GetBinnedObservabilityMetrics,
     GetFeatureValues,
+    GetObservedBinCounts,
     GetDriftTask,
+    GetDriftProfileTask,
     GetDriftProfile,
+    ListDriftProfiles,
+    CountDriftProfiles,
+    DeleteDriftProfile,
+    InsertPurgeJob,
+    GetPurgeJob,
+    GetUnfinishedPurgeJobs,
+    UpdatePurgeJob,
+    ClaimPurgeJob,
+    PurgeDrift,
+    PurgeDriftAlerts,
+    PurgeObservabilityMetrics,
+    PurgeFeatures,
+    PurgeDriftTaskRuns,
+    InsertDriftTaskRun,
+    GetDriftTaskRuns,
+    PurgeObservedBinCount,
     UpdateDriftProfileRunDates,
     UpdateDriftProfileStatus,
+    UpdateDriftProfileCatchUp,
     UpdateDriftProfile,
+    InsertApiKey,
+    GetApiKey,
+    GetApiKeyById,
+    RevokeApiKey,
+    UpsertRepositoryRole,
+    GetRepositoryRoles,
+    DeleteRepositoryRole,
 }
 
 impl Queries {
@@ -40,21 +114,57 @@ impl Queries {
         match self {
             // load sql file from scripts/insert.sql
             Queries::GetFeatures => SqlQuery::new(GET_FEATURES),
-            Queries::InsertDriftRecord => SqlQuery::new(INSERT_DRIFT_RECORD),
+            Queries::GetFeatureCatalogue => SqlQuery::new(GET_FEATURE_CATALOGUE),
+            Queries::BackfillFeatures => SqlQuery::new(BACKFILL_FEATURES),
+            Queries::GetFeatureVolume => SqlQuery::new(GET_FEATURE_VOLUME),
+            Queries::GetObservabilityRouteSummary => SqlQuery::new(GET_OBSERVABILITY_ROUTE_SUMMARY),
+            Queries::InsertDriftRecords => SqlQuery::new(INSERT_DRIFT_RECORDS),
+            Queries::InsertPsiDriftRecords => SqlQuery::new(INSERT_PSI_DRIFT_RECORDS),
+            Queries::InsertObservabilityRecords => SqlQuery::new(INSERT_OBSERVABILITY_RECORDS),
             Queries::GetBinnedFeatureValues => SqlQuery::new(GET_BINNED_FEATURE_VALUES),
             Queries::GetBinnedObservabilityMetrics => {
                 SqlQuery::new(GET_BINNED_OBSERVABILITY_METRICS)
             }
             Queries::GetFeatureValues => SqlQuery::new(GET_FEATURE_VALUES),
+            Queries::GetObservedBinCounts => SqlQuery::new(GET_OBSERVED_BIN_COUNTS),
             Queries::InsertDriftProfile => SqlQuery::new(INSERT_DRIFT_PROFILE),
             Queries::InsertDriftAlert => SqlQuery::new(INSERT_DRIFT_ALERT),
-            Queries::InsertObservabilityRecord => SqlQuery::new(INSERT_OBSERVABILITY_RECORD),
             Queries::GetDriftAlerts => SqlQuery::new(GET_DRIFT_ALERTS),
+            Queries::CountDriftAlerts => SqlQuery::new(COUNT_DRIFT_ALERTS),
+            Queries::ListDriftProfiles => SqlQuery::new(LIST_DRIFT_PROFILES),
+            Queries::CountDriftProfiles => SqlQuery::new(COUNT_DRIFT_PROFILES),
+            Queries::DeleteDriftProfile => SqlQuery::new(DELETE_DRIFT_PROFILE),
+            Queries::InsertPurgeJob => SqlQuery::new(INSERT_PURGE_JOB),
+            Queries::GetPurgeJob => SqlQuery::new(GET_PURGE_JOB),
+            Queries::GetUnfinishedPurgeJobs => SqlQuery::new(GET_UNFINISHED_PURGE_JOBS),
+            Queries::UpdatePurgeJob => SqlQuery::new(UPDATE_PURGE_JOB),
+            Queries::ClaimPurgeJob => SqlQuery::new(CLAIM_PURGE_JOB),
+            Queries::PurgeDrift => SqlQuery::new(PURGE_DRIFT),
+            Queries::PurgeDriftAlerts => SqlQuery::new(PURGE_DRIFT_ALERTS),
+            Queries::PurgeObservabilityMetrics => SqlQuery::new(PURGE_OBSERVABILITY_METRICS),
+            Queries::PurgeFeatures => SqlQuery::new(PURGE_FEATURES),
+            Queries::PurgeDriftTaskRuns => SqlQuery::new(PURGE_DRIFT_TASK_RUNS),
+            Queries::InsertDriftTaskRun => SqlQuery::new(INSERT_DRIFT_TASK_RUN),
+            Queries::GetDriftTaskRuns => SqlQuery::new(GET_DRIFT_TASK_RUNS),
+            Queries::PurgeObservedBinCount => SqlQuery::new(PURGE_OBSERVED_BIN_COUNT),
+            Queries::GetDriftAlert => SqlQuery::new(GET_DRIFT_ALERT),
+            Queries::GetDriftAlertHistory => SqlQuery::new(GET_DRIFT_ALERT_HISTORY),
+            Queries::UpdateDriftAlertStatus => SqlQuery::new(UPDATE_DRIFT_ALERT_STATUS),
+            Queries::ResolveDriftAlerts => SqlQuery::new(RESOLVE_DRIFT_ALERTS),
             Queries::GetDriftTask => SqlQuery::new(GET_DRIFT_TASK),
+            Queries::GetDriftProfileTask => SqlQuery::new(GET_DRIFT_PROFILE_TASK),
             Queries::UpdateDriftProfileRunDates => SqlQuery::new(UPDATE_DRIFT_PROFILE_RUN_DATES),
             Queries::UpdateDriftProfileStatus => SqlQuery::new(UPDATE_DRIFT_PROFILE_STATUS),
+            Queries::UpdateDriftProfileCatchUp => SqlQuery::new(UPDATE_DRIFT_PROFILE_CATCH_UP),
             Queries::UpdateDriftProfile => SqlQuery::new(UPDATE_DRIFT_PROFILE),
             Queries::GetDriftProfile => SqlQuery::new(GET_DRIFT_PROFILE),
+            Queries::InsertApiKey => SqlQuery::new(INSERT_API_KEY),
+            Queries::GetApiKey => SqlQuery::new(GET_API_KEY),
+            Queries::GetApiKeyById => SqlQuery::new(GET_API_KEY_BY_ID),
+            Queries::RevokeApiKey => SqlQuery::new(REVOKE_API_KEY),
+            Queries::UpsertRepositoryRole => SqlQuery::new(UPSERT_REPOSITORY_ROLE),
+            Queries::GetRepositoryRoles => SqlQuery::new(GET_REPOSITORY_ROLES),
+            Queries::DeleteRepositoryRole => SqlQuery::new(DELETE_REPOSITORY_ROLE),
         }
     }
 }
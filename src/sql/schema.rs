@@ -1,30 +1,83 @@
+use crate::observe::sketch::LatencySketch;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgRow, Error, FromRow, Row};
 use std::collections::BTreeMap;
 use std::collections::HashMap;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct DriftRecord {
-    pub created_at: NaiveDateTime,
-    pub name: String,
-    pub repository: String,
-    pub version: String,
-    pub feature: String,
-    pub value: f64,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FeatureResult {
     pub created_at: Vec<chrono::NaiveDateTime>,
     pub values: Vec<f64>,
 }
 
+// Feature catalogue entry maintained at ingestion time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureRecord {
+    pub feature: String,
+    pub data_type: String,
+    pub first_seen: NaiveDateTime,
+    pub last_seen: NaiveDateTime,
+    pub record_count: i64,
+}
+
+impl<'r> FromRow<'r, PgRow> for FeatureRecord {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(FeatureRecord {
+            feature: row.try_get("feature")?,
+            data_type: row.try_get("data_type")?,
+            first_seen: row.try_get("first_seen")?,
+            last_seen: row.try_get("last_seen")?,
+            record_count: row.try_get("record_count")?,
+        })
+    }
+}
+
+// Records received for a feature since the previous drift run, and the
+// average received over the same length of time in preceding runs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureVolume {
+    pub feature: String,
+    pub current_count: i64,
+    pub trailing_average: f64,
+    // kinds of the feature's alerts that are still active or acknowledged
+    pub open_alerts: Vec<String>,
+}
+
+impl<'r> FromRow<'r, PgRow> for FeatureVolume {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(FeatureVolume {
+            feature: row.try_get("feature")?,
+            current_count: row.try_get("current_count")?,
+            trailing_average: row.try_get("trailing_average")?,
+            open_alerts: row.try_get("open_alerts")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueryResult {
     pub features: BTreeMap<String, FeatureResult>,
 }
 
+// Binned feature series. Values are null for bins without any records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinnedFeature {
+    pub created_at: Vec<chrono::NaiveDateTime>,
+    pub values: Vec<Option<f64>>,
+    pub sample_count: Vec<i64>,
+    // per bin min/max band, only returned when requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<Vec<Option<f64>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<Vec<Option<f64>>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinnedQueryResult {
+    pub features: BTreeMap<String, BinnedFeature>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpcFeatureResult {
     pub feature: String,
@@ -42,6 +95,52 @@ impl<'r> FromRow<'r, PgRow> for SpcFeatureResult {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BinnedFeatureResult {
+    pub feature: String,
+    pub created_at: Vec<chrono::NaiveDateTime>,
+    pub values: Vec<Option<f64>>,
+    pub min_values: Vec<Option<f64>>,
+    pub max_values: Vec<Option<f64>>,
+    pub sample_count: Vec<i64>,
+}
+
+impl<'r> FromRow<'r, PgRow> for BinnedFeatureResult {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(BinnedFeatureResult {
+            feature: row.try_get("feature")?,
+            created_at: row.try_get("created_at")?,
+            values: row.try_get("values")?,
+            min_values: row.try_get("min_values")?,
+            max_values: row.try_get("max_values")?,
+            sample_count: row.try_get("sample_count")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchInsertResult {
+    pub accepted: usize,
+    pub rejected: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureBinCount {
+    pub feature: String,
+    pub bin_id: i32,
+    pub bin_count: i64,
+}
+
+impl<'r> FromRow<'r, PgRow> for FeatureBinCount {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(FeatureBinCount {
+            feature: row.try_get("feature")?,
+            bin_id: row.try_get("bin_id")?,
+            bin_count: row.try_get("bin_count")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AlertResult {
     pub created_at: NaiveDateTime,
@@ -73,6 +172,74 @@ impl<'r> FromRow<'r, PgRow> for AlertResult {
     }
 }
 
+/// Keyset position in the alert list, handed to clients as an opaque string
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertCursor {
+    pub created_at: NaiveDateTime,
+    pub id: i32,
+}
+
+impl AlertCursor {
+    const TIMESTAMP_FORMAT: &'static str = "%Y-%m-%dT%H:%M:%S%.f";
+
+    pub fn encode(&self) -> String {
+        hex::encode(format!(
+            "{}|{}",
+            self.created_at.format(Self::TIMESTAMP_FORMAT),
+            self.id
+        ))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, anyhow::Error> {
+        let decoded = String::from_utf8(hex::decode(cursor)?)?;
+
+        let (created_at, id) = decoded
+            .split_once('|')
+            .ok_or_else(|| anyhow::anyhow!("Malformed cursor"))?;
+
+        Ok(AlertCursor {
+            created_at: NaiveDateTime::parse_from_str(created_at, Self::TIMESTAMP_FORMAT)?,
+            id: id.parse()?,
+        })
+    }
+}
+
+impl From<&AlertResult> for AlertCursor {
+    fn from(alert: &AlertResult) -> Self {
+        AlertCursor {
+            created_at: alert.created_at,
+            id: alert.id,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertHistoryRecord {
+    pub id: i32,
+    pub created_at: NaiveDateTime,
+    pub alert_id: i32,
+    pub previous_status: String,
+    pub status: String,
+    pub actor: String,
+    pub subject: String,
+    pub comment: Option<String>,
+}
+
+impl<'r> FromRow<'r, PgRow> for AlertHistoryRecord {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(AlertHistoryRecord {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            alert_id: row.try_get("alert_id")?,
+            previous_status: row.try_get("previous_status")?,
+            status: row.try_get("status")?,
+            actor: row.try_get("actor")?,
+            subject: row.try_get("subject")?,
+            comment: row.try_get("comment")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskRequest {
     pub name: String,
@@ -82,6 +249,8 @@ pub struct TaskRequest {
     pub drift_type: String,
     pub previous_run: NaiveDateTime,
     pub schedule: String,
+    // how missed scheduled runs are handled, see CatchUpPolicy
+    pub catch_up: String,
 }
 
 impl<'r> FromRow<'r, PgRow> for TaskRequest {
@@ -96,6 +265,188 @@ impl<'r> FromRow<'r, PgRow> for TaskRequest {
             drift_type: row.try_get("drift_type")?,
             previous_run: row.try_get("previous_run")?,
             schedule: row.try_get("schedule")?,
+            catch_up: row.try_get("catch_up")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileSummary {
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    pub drift_type: String,
+    pub active: bool,
+    pub schedule: String,
+    pub next_run: NaiveDateTime,
+    pub previous_run: NaiveDateTime,
+    pub scouter_version: String,
+    pub catch_up: String,
+    // outcome of the most recent drift task run, None if the profile never ran
+    pub last_run_status: Option<String>,
+    pub last_error: Option<String>,
+}
+
+impl<'r> FromRow<'r, PgRow> for ProfileSummary {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(ProfileSummary {
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            name: row.try_get("name")?,
+            repository: row.try_get("repository")?,
+            version: row.try_get("version")?,
+            drift_type: row.try_get("drift_type")?,
+            active: row.try_get("active")?,
+            schedule: row.try_get("schedule")?,
+            next_run: row.try_get("next_run")?,
+            previous_run: row.try_get("previous_run")?,
+            scouter_version: row.try_get("scouter_version")?,
+            catch_up: row.try_get("catch_up")?,
+            last_run_status: row.try_get("last_run_status")?,
+            last_error: row.try_get("last_error")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskRunStatus {
+    Success,
+    Failed,
+    Skipped,
+}
+
+impl TaskRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskRunStatus::Success => "success",
+            TaskRunStatus::Failed => "failed",
+            TaskRunStatus::Skipped => "skipped",
+        }
+    }
+}
+
+// A single execution of a scheduled drift task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftTaskRun {
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    pub drift_type: String,
+    pub window_start: NaiveDateTime,
+    pub window_end: NaiveDateTime,
+    pub duration_ms: i64,
+    pub rows_read: i64,
+    pub alerts_produced: i32,
+    pub status: String,
+    pub error: Option<String>,
+    // catch-up policy applied when the run covered missed schedule intervals
+    pub catch_up: Option<String>,
+}
+
+impl DriftTaskRun {
+    pub fn new(
+        name: &str,
+        repository: &str,
+        version: &str,
+        drift_type: &str,
+        window_start: NaiveDateTime,
+        window_end: NaiveDateTime,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            repository: repository.to_string(),
+            version: version.to_string(),
+            drift_type: drift_type.to_string(),
+            window_start,
+            window_end,
+            duration_ms: 0,
+            rows_read: 0,
+            alerts_produced: 0,
+            status: TaskRunStatus::Success.as_str().to_string(),
+            error: None,
+            catch_up: None,
+        }
+    }
+
+    /// Mark the run as a window that was deliberately not evaluated
+    pub fn skip(&mut self) {
+        self.status = TaskRunStatus::Skipped.as_str().to_string();
+    }
+
+    /// Mark the run as failed with the error that stopped it
+    pub fn fail(&mut self, error: &anyhow::Error) {
+        self.status = TaskRunStatus::Failed.as_str().to_string();
+        self.error = Some(format!("{:#}", error));
+    }
+}
+
+impl<'r> FromRow<'r, PgRow> for DriftTaskRun {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(DriftTaskRun {
+            name: row.try_get("name")?,
+            repository: row.try_get("repository")?,
+            version: row.try_get("version")?,
+            drift_type: row.try_get("drift_type")?,
+            window_start: row.try_get("window_start")?,
+            window_end: row.try_get("window_end")?,
+            duration_ms: row.try_get("duration_ms")?,
+            rows_read: row.try_get("rows_read")?,
+            alerts_produced: row.try_get("alerts_produced")?,
+            status: row.try_get("status")?,
+            error: row.try_get("error")?,
+            catch_up: row.try_get("catch_up")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PurgeStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl PurgeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PurgeStatus::Pending => "pending",
+            PurgeStatus::Running => "running",
+            PurgeStatus::Completed => "completed",
+            PurgeStatus::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeJob {
+    pub id: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub name: String,
+    pub repository: String,
+    pub version: String,
+    pub requested_by: String,
+    pub status: String,
+    pub rows_deleted: i64,
+    pub error: Option<String>,
+}
+
+impl<'r> FromRow<'r, PgRow> for PurgeJob {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(PurgeJob {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            name: row.try_get("name")?,
+            repository: row.try_get("repository")?,
+            version: row.try_get("version")?,
+            requested_by: row.try_get("requested_by")?,
+            status: row.try_get("status")?,
+            rows_deleted: row.try_get("rows_deleted")?,
+            error: row.try_get("error")?,
         })
     }
 }
@@ -104,15 +455,20 @@ impl<'r> FromRow<'r, PgRow> for TaskRequest {
 pub struct ObservabilityResult {
     pub route_name: String,
     pub created_at: Vec<chrono::NaiveDateTime>,
-    pub p5: Vec<f64>,
-    pub p25: Vec<f64>,
-    pub p50: Vec<f64>,
-    pub p95: Vec<f64>,
-    pub p99: Vec<f64>,
+    // latencies are null for bins without any metrics
+    pub p5: Vec<Option<f64>>,
+    pub p25: Vec<Option<f64>>,
+    pub p50: Vec<Option<f64>>,
+    pub p95: Vec<Option<f64>>,
+    pub p99: Vec<Option<f64>>,
     pub total_request_count: Vec<i64>,
     pub total_error_count: Vec<i64>,
-    pub error_latency: Vec<f64>,
+    pub error_latency: Vec<Option<f64>>,
     pub status_counts: Vec<HashMap<String, i64>>,
+    pub sample_count: Vec<i64>,
+    // sketches reported in each bin, merged by the query layer
+    #[serde(skip)]
+    pub latency_sketches: Vec<Vec<LatencySketch>>,
 }
 
 impl<'r> FromRow<'r, PgRow> for ObservabilityResult {
@@ -126,6 +482,12 @@ impl<'r> FromRow<'r, PgRow> for ObservabilityResult {
             .map(|value| serde_json::from_value(value).unwrap_or_default())
             .collect();
 
+        let latency_sketches: Vec<serde_json::Value> = row.try_get("latency_sketches")?;
+        let latency_sketches = latency_sketches
+            .into_iter()
+            .map(|sketches| decode_sketches("latency_sketches", sketches))
+            .collect::<Result<Vec<_>, Error>>()?;
+
         Ok(ObservabilityResult {
             route_name: row.try_get("route_name")?,
             created_at: row.try_get("created_at")?,
@@ -138,6 +500,146 @@ impl<'r> FromRow<'r, PgRow> for ObservabilityResult {
             total_error_count: row.try_get("total_error_count")?,
             error_latency: row.try_get("error_latency")?,
             status_counts,
+            sample_count: row.try_get("sample_count")?,
+            latency_sketches,
+        })
+    }
+}
+
+impl ObservabilityResult {
+    // Replace the averaged percentiles of each bin with percentiles of the merged sketches.
+    // Only applied to bins where every record reported a sketch, a partial merge
+    // would drop the traffic of the other records
+    //
+    // # Returns
+    //
+    // * Bins whose sketches could not be merged and the reason, these keep the averaged percentiles
+    pub fn merge_sketches(&mut self) -> Vec<(usize, anyhow::Error)> {
+        let mut failures = Vec::new();
+
+        for (bin, sketches) in self.latency_sketches.iter().enumerate() {
+            if sketches.is_empty() || self.sample_count.get(bin) != Some(&(sketches.len() as i64)) {
+                continue;
+            }
+
+            match LatencySketch::merged_percentiles(sketches) {
+                Ok(Some([p5, p25, p50, p95, p99])) => {
+                    self.p5[bin] = Some(p5);
+                    self.p25[bin] = Some(p25);
+                    self.p50[bin] = Some(p50);
+                    self.p95[bin] = Some(p95);
+                    self.p99[bin] = Some(p99);
+                }
+                Ok(None) => {}
+                Err(e) => failures.push((bin, e)),
+            }
+        }
+
+        failures
+    }
+}
+
+// Decode a jsonb array of latency sketches
+fn decode_sketches(column: &str, value: serde_json::Value) -> Result<Vec<LatencySketch>, Error> {
+    serde_json::from_value(value).map_err(|e| Error::ColumnDecode {
+        index: column.to_string(),
+        source: Box::new(e),
+    })
+}
+
+// Request metrics for a route aggregated over an alerting window
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RouteSummary {
+    pub route_name: String,
+    pub request_count: i64,
+    pub error_count: i64,
+    // highest percentile reported in the window until replaced by the merged sketches
+    pub p95: Option<f64>,
+    pub p99: Option<f64>,
+    pub sample_count: i64,
+    #[serde(skip)]
+    pub latency_sketches: Vec<LatencySketch>,
+}
+
+impl<'r> FromRow<'r, PgRow> for RouteSummary {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(RouteSummary {
+            route_name: row.try_get("route_name")?,
+            request_count: row.try_get("request_count")?,
+            error_count: row.try_get("error_count")?,
+            p95: row.try_get("p95")?,
+            p99: row.try_get("p99")?,
+            sample_count: row.try_get("sample_count")?,
+            latency_sketches: decode_sketches(
+                "latency_sketches",
+                row.try_get("latency_sketches")?,
+            )?,
+        })
+    }
+}
+
+impl RouteSummary {
+    // Replace the p95 and p99 with percentiles of the merged sketches. Only applied when
+    // every record in the window reported a sketch, a partial merge would drop the
+    // traffic of the other records
+    pub fn merge_sketches(&mut self) -> Result<(), anyhow::Error> {
+        if self.latency_sketches.is_empty()
+            || self.sample_count != self.latency_sketches.len() as i64
+        {
+            return Ok(());
+        }
+
+        if let Some([_, _, _, p95, p99]) =
+            LatencySketch::merged_percentiles(&self.latency_sketches)?
+        {
+            self.p95 = Some(p95);
+            self.p99 = Some(p99);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub id: i32,
+    pub created_at: NaiveDateTime,
+    pub name: String,
+    pub key_prefix: String,
+    pub scopes: Vec<String>,
+    pub repositories: Option<Vec<String>>,
+}
+
+impl<'r> FromRow<'r, PgRow> for ApiKeyRecord {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(ApiKeyRecord {
+            id: row.try_get("id")?,
+            created_at: row.try_get("created_at")?,
+            name: row.try_get("name")?,
+            key_prefix: row.try_get("key_prefix")?,
+            scopes: row.try_get("scopes")?,
+            repositories: row.try_get("repositories")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleBinding {
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub subject: String,
+    pub repository: String,
+    pub role: String,
+}
+
+impl<'r> FromRow<'r, PgRow> for RoleBinding {
+    fn from_row(row: &'r PgRow) -> Result<Self, Error> {
+        Ok(RoleBinding {
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+            subject: row.try_get("subject")?,
+            repository: row.try_get("repository")?,
+            role: row.try_get("role")?,
         })
     }
 }
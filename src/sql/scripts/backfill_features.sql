@@ -0,0 +1,41 @@
+WITH spc AS (
+    INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+    SELECT name, repository, version, feature, 'spc', min(created_at), max(created_at), count(*)
+    FROM scouter.drift
+    WHERE name = $1
+      AND repository = $2
+      AND version = $3
+    GROUP BY name, repository, version, feature
+    ORDER BY name, repository, version, feature
+    ON CONFLICT DO NOTHING
+    RETURNING feature
+),
+
+psi AS (
+    INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+    SELECT name, repository, version, feature, 'psi', min(created_at), max(created_at), count(*)
+    FROM scouter.observed_bin_count
+    WHERE name = $1
+      AND repository = $2
+      AND version = $3
+    GROUP BY name, repository, version, feature
+    ORDER BY name, repository, version, feature
+    ON CONFLICT DO NOTHING
+    RETURNING feature
+),
+
+routes AS (
+    INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+    SELECT name, repository, version, route_metric->>'route_name', 'route', min(created_at), max(created_at), count(*)
+    FROM scouter.observability_metrics, jsonb_array_elements(route_metrics) as route_metric
+    WHERE name = $1
+      AND repository = $2
+      AND version = $3
+    GROUP BY name, repository, version, route_metric->>'route_name'
+    ORDER BY name, repository, version, route_metric->>'route_name'
+    ON CONFLICT DO NOTHING
+    RETURNING feature
+)
+
+SELECT
+    (SELECT count(*) FROM spc) + (SELECT count(*) FROM psi) + (SELECT count(*) FROM routes) AS inserted;
\ No newline at end of file
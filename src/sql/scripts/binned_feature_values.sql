@@ -1,6 +1,6 @@
 WITH subquery1 AS (
     SELECT
-        date_bin('$1 minutes', created_at, TIMESTAMP '1970-01-01') as created_at,
+        date_bin(make_interval(secs => {bind}), created_at, TIMESTAMP '1970-01-01') as created_at,
         name,
         repository,
         feature,
@@ -8,11 +8,12 @@ WITH subquery1 AS (
         value
     FROM scouter.drift
     WHERE 
-        created_at > timezone('utc', now()) - interval '$2 minute'
-        AND name = $3
-        AND repository = $4
-        AND version = $5
-        AND feature = $6
+        created_at >= {bind}
+        AND created_at <= {bind}
+        AND name = {bind}
+        AND repository = {bind}
+        AND version = {bind}
+        {filters}
 ),
 
 subquery2 AS (
@@ -22,7 +23,10 @@ subquery2 AS (
         repository,
         feature,
         version,
-        avg(value) as value
+        {expr} as value,
+        min(value) as min_value,
+        max(value) as max_value,
+        count(value) as sample_count
     FROM subquery1
     GROUP BY 
         created_at,
@@ -30,12 +34,59 @@ subquery2 AS (
         repository,
         feature,
         version
+),
+
+-- every bin in the range, only generated when gaps are filled
+bins AS (
+    SELECT generate_series(
+        date_bin(make_interval(secs => {bind}), {bind}, TIMESTAMP '1970-01-01'),
+        {bind},
+        make_interval(secs => {bind})
+    ) as created_at
+    WHERE {bind}
+),
+
+-- every catalogued feature of the service, so features without records in the range still get a series
+series AS (
+    SELECT bins.created_at, features.feature
+    FROM bins
+    CROSS JOIN (
+        SELECT feature
+        FROM scouter.features
+        WHERE
+            name = {bind}
+            AND repository = {bind}
+            AND version = {bind}
+            AND data_type = 'spc'
+            AND first_seen <= {bind}
+            {filters}
+    ) as features
+    UNION
+    SELECT created_at, feature
+    FROM subquery2
+),
+
+subquery3 AS (
+    SELECT
+        series.created_at,
+        series.feature,
+        subquery2.value,
+        subquery2.min_value,
+        subquery2.max_value,
+        coalesce(subquery2.sample_count, 0) as sample_count
+    FROM series
+    LEFT JOIN subquery2
+        ON subquery2.created_at = series.created_at
+        AND subquery2.feature = series.feature
 )
 
 SELECT
     feature,
     array_agg(created_at ORDER BY created_at DESC) as created_at,
-    array_agg(value ORDER BY created_at DESC) as values
-FROM subquery2
+    array_agg(value ORDER BY created_at DESC) as values,
+    array_agg(min_value ORDER BY created_at DESC) as min_values,
+    array_agg(max_value ORDER BY created_at DESC) as max_values,
+    array_agg(sample_count ORDER BY created_at DESC) as sample_count
+FROM subquery3
 GROUP BY 
     feature;
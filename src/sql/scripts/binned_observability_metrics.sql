@@ -1,13 +1,15 @@
 WITH subquery1 AS (
     SELECT
-        date_bin('$1 minutes', created_at, TIMESTAMP '1970-01-01') as created_at,
+        date_bin(make_interval(secs => {bind}), created_at, TIMESTAMP '1970-01-01') as created_at,
         jsonb_array_elements(route_metrics) as route_metric
     FROM scouter.observability_metrics
     WHERE 
-        created_at > timezone('utc', now()) - interval '$2 minute'
-        AND name = $3
-        AND repository = $4
-        AND version = $5
+        created_at >= {bind}
+        AND created_at <= {bind}
+        AND name = {bind}
+        AND repository = {bind}
+        AND version = {bind}
+        {filters}
 ),
 
 subquery2 AS (
@@ -22,7 +24,8 @@ subquery2 AS (
         (route_metric->>'request_count')::int as request_count,
         (route_metric->>'error_count')::int as error_count,
         (route_metric->>'error_latency')::float as error_latency,
-        route_metric->'status_codes' as status_codes
+        route_metric->'status_codes' as status_codes,
+        route_metric->'latency_sketch' as latency_sketch
     FROM subquery1
 ),
 
@@ -68,7 +71,10 @@ subquery3 AS (
         avg(p99) as avg_p99,
         sum(request_count) as total_request_count,
         sum(error_count) as total_error_count,
-        avg(error_latency) as avg_error_latency
+        avg(error_latency) as avg_error_latency,
+        count(*) as sample_count,
+        -- sketches are merged by the server to compute exact bin percentiles
+        coalesce(jsonb_agg(latency_sketch) FILTER (WHERE latency_sketch IS NOT NULL), '[]'::jsonb) as latency_sketches
     FROM (
         SELECT
             created_at,
@@ -80,7 +86,8 @@ subquery3 AS (
             p99,
             request_count,
             error_count,
-            error_latency
+            error_latency,
+            latency_sketch
         FROM subquery2
     ) as flattened
     GROUP BY 
@@ -101,11 +108,63 @@ select
  a.total_request_count,
  a.total_error_count,
  a.avg_error_latency,
+ a.sample_count,
+ a.latency_sketches,
  b.aggregated_map as status_counts
 from subquery3 as a
 left join expanded_status_codes as b
 	on a.created_at = b.created_at
 	and a.route_name = b.route_name
+),
+
+-- every bin in the range, only generated when gaps are filled
+bins AS (
+    SELECT generate_series(
+        date_bin(make_interval(secs => {bind}), {bind}, TIMESTAMP '1970-01-01'),
+        {bind},
+        make_interval(secs => {bind})
+    ) as created_at
+    WHERE {bind}
+),
+
+-- every catalogued route of the service, so routes without requests in the range still get a series
+series AS (
+    SELECT bins.created_at, routes.feature as route_name
+    FROM bins
+    CROSS JOIN (
+        SELECT feature
+        FROM scouter.features
+        WHERE
+            name = {bind}
+            AND repository = {bind}
+            AND version = {bind}
+            AND data_type = 'route'
+            AND first_seen <= {bind}
+    ) as routes
+    UNION
+    SELECT created_at, route_name
+    FROM joined
+),
+
+filled AS (
+    SELECT
+        series.created_at,
+        series.route_name,
+        joined.avg_p5,
+        joined.avg_p25,
+        joined.avg_p50,
+        joined.avg_p95,
+        joined.avg_p99,
+        coalesce(joined.total_request_count, 0) as total_request_count,
+        coalesce(joined.total_error_count, 0) as total_error_count,
+        joined.avg_error_latency,
+        coalesce(joined.sample_count, 0) as sample_count,
+        coalesce(joined.status_counts, '{}'::jsonb) as status_counts,
+        coalesce(joined.latency_sketches, '[]'::jsonb) as latency_sketches
+    FROM series
+    LEFT JOIN joined
+        ON joined.created_at = series.created_at
+        AND joined.route_name = series.route_name
 )
 
 SELECT
@@ -119,7 +178,9 @@ SELECT
     array_agg(total_request_count ORDER BY created_at DESC) as total_request_count,
     array_agg(total_error_count ORDER BY created_at DESC) as total_error_count,
     array_agg(avg_error_latency ORDER BY created_at DESC) as error_latency,
-    array_agg(status_counts ORDER BY created_at DESC) as status_counts
-FROM joined
+    array_agg(status_counts ORDER BY created_at DESC) as status_counts,
+    array_agg(sample_count ORDER BY created_at DESC) as sample_count,
+    array_agg(latency_sketches ORDER BY created_at DESC) as latency_sketches
+FROM filled
 GROUP BY 
     route_name;
\ No newline at end of file
@@ -0,0 +1,10 @@
+UPDATE scouter.purge_jobs
+SET
+  status = 'running',
+  updated_at = timezone('utc', now())
+WHERE id = $1
+  AND (
+    status = 'pending'
+    OR (status = 'running' AND updated_at < timezone('utc', now()) - make_interval(secs => $2))
+  )
+RETURNING id, created_at, updated_at, name, repository, version, requested_by, status, rows_deleted, error;
\ No newline at end of file
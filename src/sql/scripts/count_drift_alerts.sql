@@ -0,0 +1,7 @@
+SELECT count(*) AS total
+FROM scouter.drift_alerts
+WHERE
+    version = {bind}
+    AND name = {bind}
+    AND repository = {bind}
+    {filters}
\ No newline at end of file
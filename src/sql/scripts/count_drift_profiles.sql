@@ -0,0 +1,3 @@
+SELECT count(*) AS total
+FROM scouter.drift_profile
+{where}
\ No newline at end of file
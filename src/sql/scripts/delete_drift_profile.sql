@@ -0,0 +1,6 @@
+DELETE FROM scouter.drift_profile
+WHERE name = $1
+  and repository = $2
+  and version = $3
+  and ($4::varchar IS NULL OR drift_type = $4)
+RETURNING name;
\ No newline at end of file
@@ -0,0 +1,3 @@
+DELETE FROM scouter.repository_roles
+WHERE subject = $1
+  and repository = $2;
\ No newline at end of file
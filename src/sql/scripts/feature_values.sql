@@ -5,11 +5,11 @@ feature,
 value
 FROM scouter.drift
 WHERE
-    created_at > $1::timestamp
-    AND name = $2
-    AND repository = $3
-    AND version = $4
-    AND feature = $5
+    created_at > {bind}::timestamp
+    AND name = {bind}
+    AND repository = {bind}
+    AND version = {bind}
+    {filters}
 )
 
 SELECT
@@ -0,0 +1,58 @@
+-- record counts per monitored feature since the previous run, compared with
+-- the average over the same length of time across the preceding runs
+WITH monitored AS (
+    SELECT unnest({bind}::varchar[]) AS feature
+    UNION
+    SELECT feature
+    FROM scouter.features
+    WHERE name = {bind}
+      AND repository = {bind}
+      AND version = {bind}
+      AND data_type = {bind}
+      AND cardinality({bind}::varchar[]) = 0
+),
+
+bounds AS (
+    SELECT
+        {bind}::timestamp AS previous_run,
+        timezone('utc', now()) - {bind}::timestamp AS run_interval,
+        {bind}::integer AS lookback_runs
+),
+
+counts AS (
+    SELECT
+        feature,
+        count(*) FILTER (WHERE created_at > bounds.previous_run) AS current_count,
+        count(*) FILTER (WHERE created_at <= bounds.previous_run) AS trailing_count
+    FROM {expr}, bounds
+    WHERE name = {bind}
+      AND repository = {bind}
+      AND version = {bind}
+      AND feature IN (SELECT feature FROM monitored)
+      AND created_at > bounds.previous_run - bounds.run_interval * bounds.lookback_runs
+    GROUP BY feature
+),
+
+-- kinds of the alerts still open for each feature, so they are not raised again
+open_alerts AS (
+    SELECT feature, array_agg(DISTINCT alert->>'kind') AS kinds
+    FROM scouter.drift_alerts
+    WHERE name = {bind}
+      AND repository = {bind}
+      AND version = {bind}
+      AND status IN ('active', 'acknowledged')
+    GROUP BY feature
+)
+
+SELECT
+    monitored.feature,
+    coalesce(counts.current_count, 0) AS current_count,
+    coalesce(counts.trailing_count, 0)::double precision / bounds.lookback_runs AS trailing_average,
+    coalesce(open_alerts.kinds, ARRAY[]::text[]) AS open_alerts
+FROM monitored
+CROSS JOIN bounds
+LEFT JOIN counts
+    ON counts.feature = monitored.feature
+LEFT JOIN open_alerts
+    ON open_alerts.feature = monitored.feature
+ORDER BY monitored.feature;
\ No newline at end of file
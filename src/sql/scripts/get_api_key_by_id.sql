@@ -0,0 +1,4 @@
+SELECT id, created_at, name, key_prefix, scopes, repositories
+FROM scouter.api_keys
+WHERE id = $1
+  and revoked_at IS NULL;
\ No newline at end of file
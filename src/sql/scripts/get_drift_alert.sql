@@ -0,0 +1,11 @@
+SELECT
+created_at,
+name,
+repository,
+version,
+feature,
+alert,
+id,
+status
+FROM scouter.drift_alerts
+WHERE id = $1;
\ No newline at end of file
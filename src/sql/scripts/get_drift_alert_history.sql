@@ -0,0 +1,12 @@
+SELECT
+id,
+created_at,
+alert_id,
+previous_status,
+status,
+actor,
+subject,
+comment
+FROM scouter.drift_alert_history
+WHERE alert_id = $1
+ORDER BY created_at, id;
\ No newline at end of file
@@ -9,6 +9,7 @@ id,
 status
 FROM scouter.drift_alerts
 WHERE
-    version = $1
-    AND name = $2
-    AND repository = $3
\ No newline at end of file
+    version = {bind}
+    AND name = {bind}
+    AND repository = {bind}
+    {filters}
\ No newline at end of file
@@ -2,4 +2,8 @@ SELECT profile
 FROM scouter.drift_profile
 WHERE name = $1
   and repository = $2
-  and version = $3;
\ No newline at end of file
+  and version = $3
+  and ($4::varchar IS NULL OR drift_type = $4)
+-- without a drift type the drift profile is returned ahead of the observability profile
+ORDER BY drift_type = 'OBSERVABILITY', drift_type
+LIMIT 1;
@@ -0,0 +1,7 @@
+SELECT name, repository, version, drift_type, window_start, window_end, duration_ms, rows_read, alerts_produced, status, error, catch_up
+FROM scouter.drift_task_runs
+WHERE name = $1
+  AND repository = $2
+  AND version = $3
+ORDER BY id DESC
+LIMIT $4;
\ No newline at end of file
@@ -0,0 +1,12 @@
+SELECT
+    feature,
+    data_type,
+    first_seen,
+    last_seen,
+    record_count
+FROM scouter.features
+WHERE
+    name = $1
+    AND repository = $2
+    AND version = $3
+ORDER BY feature, data_type;
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT id, created_at, updated_at, name, repository, version, requested_by, status, rows_deleted, error
+FROM scouter.purge_jobs
+WHERE id = $1;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT created_at, updated_at, subject, repository, role
+FROM scouter.repository_roles
+WHERE ($1::varchar IS NULL OR subject = $1)
+  and ($2::varchar IS NULL OR repository = $2)
+ORDER BY repository, subject;
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT id, created_at, updated_at, name, repository, version, requested_by, status, rows_deleted, error
+FROM scouter.purge_jobs
+WHERE status = ANY($1)
+ORDER BY id;
\ No newline at end of file
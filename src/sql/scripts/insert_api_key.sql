@@ -0,0 +1,4 @@
+INSERT INTO scouter.api_keys (name, key_prefix, key_hash, scopes, repositories)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT (key_hash) DO NOTHING
+RETURNING id, created_at, name, key_prefix, scopes, repositories;
\ No newline at end of file
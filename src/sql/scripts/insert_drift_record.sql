@@ -1,3 +0,0 @@
-INSERT INTO scouter.drift (created_at, name, repository, version, feature, value) 
-VALUES ($1, $2, $3, $4, $5, $6)
-ON CONFLICT DO NOTHING;
\ No newline at end of file
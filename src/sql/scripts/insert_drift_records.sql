@@ -0,0 +1,30 @@
+WITH inserted AS (
+    INSERT INTO scouter.drift (created_at, name, repository, version, feature, value) 
+    SELECT * FROM UNNEST(
+        $1::timestamp[],
+        $2::varchar[],
+        $3::varchar[],
+        $4::varchar[],
+        $5::varchar[],
+        $6::double precision[]
+    )
+    ON CONFLICT DO NOTHING
+    RETURNING created_at, name, repository, version, feature
+),
+
+catalogue AS (
+    INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+    SELECT name, repository, version, feature, 'spc', min(created_at), max(created_at), count(*)
+    FROM inserted
+    GROUP BY name, repository, version, feature
+    -- rows are locked in key order so concurrent batches can't deadlock
+    ORDER BY name, repository, version, feature
+    ON CONFLICT (name, repository, version, feature, data_type) DO UPDATE
+    SET
+        first_seen = least(scouter.features.first_seen, excluded.first_seen),
+        last_seen = greatest(scouter.features.last_seen, excluded.last_seen),
+        record_count = scouter.features.record_count + excluded.record_count
+)
+
+SELECT count(*) AS inserted
+FROM inserted;
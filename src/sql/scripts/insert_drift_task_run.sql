@@ -0,0 +1,2 @@
+INSERT INTO scouter.drift_task_runs (name, repository, version, drift_type, window_start, window_end, duration_ms, rows_read, alerts_produced, status, error, catch_up)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12);
\ No newline at end of file
@@ -0,0 +1,39 @@
+WITH inserted AS (
+    INSERT INTO scouter.observability_metrics (created_at, repository, name, version, request_count, error_count, route_metrics) 
+    SELECT
+        created_at,
+        repository,
+        name,
+        version,
+        request_count,
+        error_count,
+        route_metrics
+    FROM UNNEST(
+        $1::varchar[],
+        $2::varchar[],
+        $3::varchar[],
+        $4::integer[],
+        $5::integer[],
+        $6::jsonb[],
+        $7::timestamp[]
+    ) AS records(repository, name, version, request_count, error_count, route_metrics, created_at)
+    RETURNING created_at, name, repository, version, route_metrics
+),
+
+-- routes are catalogued alongside features so they can be listed and gap filled
+catalogue AS (
+    INSERT INTO scouter.features (name, repository, version, feature, data_type, first_seen, last_seen, record_count)
+    SELECT name, repository, version, route_metric->>'route_name', 'route', min(created_at), max(created_at), count(*)
+    FROM inserted, jsonb_array_elements(route_metrics) as route_metric
+    GROUP BY name, repository, version, route_metric->>'route_name'
+    -- rows are locked in key order so concurrent batches can't deadlock
+    ORDER BY name, repository, version, route_metric->>'route_name'
+    ON CONFLICT (name, repository, version, feature, data_type) DO UPDATE
+    SET
+        first_seen = least(scouter.features.first_seen, excluded.first_seen),
+        last_seen = greatest(scouter.features.last_seen, excluded.last_seen),
+        record_count = scouter.features.record_count + excluded.record_count
+)
+
+SELECT count(*) AS inserted
+FROM inserted;
\ No newline at end of file
@@ -0,0 +1,3 @@
+INSERT INTO scouter.purge_jobs (name, repository, version, requested_by)
+VALUES ($1, $2, $3, $4)
+RETURNING id, created_at, updated_at, name, repository, version, requested_by, status, rows_deleted, error;
\ No newline at end of file
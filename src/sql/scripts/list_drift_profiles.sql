@@ -0,0 +1,27 @@
+SELECT
+    created_at,
+    updated_at,
+    name,
+    repository,
+    version,
+    drift_type,
+    active,
+    schedule,
+    next_run,
+    previous_run,
+    scouter_version,
+    catch_up,
+    last_run.status as last_run_status,
+    last_run.error as last_error
+FROM scouter.drift_profile
+LEFT JOIN LATERAL (
+    SELECT runs.status, runs.error
+    FROM scouter.drift_task_runs as runs
+    WHERE runs.name = drift_profile.name
+      AND runs.repository = drift_profile.repository
+      AND runs.version = drift_profile.version
+      AND runs.drift_type = drift_profile.drift_type
+    ORDER BY runs.id DESC
+    LIMIT 1
+) as last_run ON true
+{where}
\ No newline at end of file
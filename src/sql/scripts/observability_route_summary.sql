@@ -0,0 +1,36 @@
+WITH subquery1 AS (
+    SELECT
+        jsonb_array_elements(route_metrics) as route_metric
+    FROM scouter.observability_metrics
+    WHERE 
+        created_at > $1
+        AND created_at <= $5
+        AND name = $2
+        AND repository = $3
+        AND version = $4
+),
+
+subquery2 AS (
+    SELECT
+        route_metric->>'route_name' as route_name,
+        (route_metric->'metrics'->>'p95')::float as p95,
+        (route_metric->'metrics'->>'p99')::float as p99,
+        (route_metric->>'request_count')::bigint as request_count,
+        (route_metric->>'error_count')::bigint as error_count,
+        route_metric->'latency_sketch' as latency_sketch
+    FROM subquery1
+)
+
+-- percentiles can't be averaged across records. The sketches are merged by the server and,
+-- for records without one, the highest reported percentile bounds the window's percentile
+SELECT
+    route_name,
+    sum(request_count)::bigint as request_count,
+    sum(error_count)::bigint as error_count,
+    max(p95) as p95,
+    max(p99) as p99,
+    count(*) as sample_count,
+    coalesce(jsonb_agg(latency_sketch) FILTER (WHERE latency_sketch IS NOT NULL), '[]'::jsonb) as latency_sketches
+FROM subquery2
+GROUP BY 
+    route_name;
\ No newline at end of file
@@ -0,0 +1,14 @@
+SELECT
+    feature,
+    bin_id,
+    sum(bin_count)::bigint as bin_count
+FROM scouter.observed_bin_count
+WHERE
+    created_at > {bind}::timestamp
+    AND name = {bind}
+    AND repository = {bind}
+    AND version = {bind}
+    {filters}
+GROUP BY 
+    feature,
+    bin_id;
\ No newline at end of file
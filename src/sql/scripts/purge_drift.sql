@@ -0,0 +1,17 @@
+WITH batch AS (
+    SELECT tableoid, ctid
+    FROM scouter.drift
+    WHERE name = $1
+      AND repository = $2
+      AND version = $3
+    LIMIT $4
+),
+
+deleted AS (
+    DELETE FROM scouter.drift
+    WHERE (tableoid, ctid) IN (SELECT tableoid, ctid FROM batch)
+    RETURNING 1
+)
+
+SELECT count(*) AS deleted
+FROM deleted;
\ No newline at end of file
@@ -0,0 +1,22 @@
+WITH batch AS (
+    SELECT tableoid, ctid
+    FROM scouter.drift_alerts
+    WHERE name = $1
+      AND repository = $2
+      AND version = $3
+    LIMIT $4
+),
+
+deleted AS (
+    DELETE FROM scouter.drift_alerts
+    WHERE (tableoid, ctid) IN (SELECT tableoid, ctid FROM batch)
+    RETURNING id
+),
+
+history AS (
+    DELETE FROM scouter.drift_alert_history
+    WHERE alert_id IN (SELECT id FROM deleted)
+)
+
+SELECT count(*) AS deleted
+FROM deleted;
\ No newline at end of file
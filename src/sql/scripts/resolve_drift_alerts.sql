@@ -0,0 +1,26 @@
+-- resolves every open alert for the given features of a service
+WITH previous AS (
+    SELECT id, created_at, status
+    FROM scouter.drift_alerts
+    WHERE name = $1
+      AND repository = $2
+      AND version = $3
+      AND feature = ANY($4)
+      AND status IN ('active', 'acknowledged')
+    FOR UPDATE
+),
+updated AS (
+    UPDATE scouter.drift_alerts AS alerts
+    SET status = 'resolved'
+    FROM previous
+    WHERE alerts.id = previous.id
+      AND alerts.created_at = previous.created_at
+    RETURNING alerts.id, alerts.status, previous.status AS previous_status
+),
+history AS (
+    INSERT INTO scouter.drift_alert_history (alert_id, previous_status, status, actor, subject, comment)
+    SELECT id, previous_status, status, $5, $5, $6
+    FROM updated
+)
+SELECT count(*) AS resolved
+FROM updated;
\ No newline at end of file
@@ -0,0 +1,4 @@
+UPDATE scouter.api_keys
+SET revoked_at = timezone('utc', now())
+WHERE id = $1
+  and revoked_at IS NULL;
\ No newline at end of file
@@ -1,7 +1,9 @@
-SELECT
-DISTINCT feature
-FROM scouter.drift
+SELECT DISTINCT
+feature
+FROM scouter.features
 WHERE
    name = $1
    AND repository = $2
-   AND version = $3;
\ No newline at end of file
+   AND version = $3
+   AND data_type != 'route'
+ORDER BY feature;
\ No newline at end of file
@@ -0,0 +1,40 @@
+-- moves a single alert to a new status if it is currently in one of the allowed statuses
+WITH previous AS (
+    SELECT id, created_at, status
+    FROM scouter.drift_alerts
+    WHERE id = $1
+      AND status = ANY($3)
+    FOR UPDATE
+),
+updated AS (
+    UPDATE scouter.drift_alerts AS alerts
+    SET status = $2
+    FROM previous
+    WHERE alerts.id = previous.id
+      AND alerts.created_at = previous.created_at
+    RETURNING
+        alerts.created_at,
+        alerts.name,
+        alerts.repository,
+        alerts.version,
+        alerts.feature,
+        alerts.alert,
+        alerts.id,
+        alerts.status,
+        previous.status AS previous_status
+),
+history AS (
+    INSERT INTO scouter.drift_alert_history (alert_id, previous_status, status, actor, subject, comment)
+    SELECT id, previous_status, status, $4, $5, $6
+    FROM updated
+)
+SELECT
+created_at,
+name,
+repository,
+version,
+feature,
+alert,
+id,
+status
+FROM updated;
\ No newline at end of file
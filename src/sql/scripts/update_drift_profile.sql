@@ -1,8 +1,8 @@
--- update drift profile given name, repository and version
+-- update drift profile given name, repository, version and drift type
 
 UPDATE scouter.drift_profile
-SET profile = $1,
-    drift_type = $2
-WHERE name = $3
-  and repository = $4
-  and version = $5;
\ No newline at end of file
+SET profile = $1
+WHERE name = $2
+  and repository = $3
+  and version = $4
+  and drift_type = $5;
\ No newline at end of file
@@ -0,0 +1,6 @@
+UPDATE scouter.drift_profile
+SET catch_up = $1
+WHERE name = $2
+  and repository = $3
+  and version = $4
+  and ($5::varchar IS NULL OR drift_type = $5);
\ No newline at end of file
@@ -1,7 +1,8 @@
 UPDATE scouter.drift_profile
-SET previous_run = next_run,
+SET previous_run = coalesce($5, next_run),
     next_run     = $1,
     updated_at   = timezone('utc', now())
 WHERE name = $2
   and repository = $3
-  and version = $4;
\ No newline at end of file
+  and version = $4
+  and drift_type = $6;
\ No newline at end of file
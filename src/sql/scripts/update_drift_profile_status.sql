@@ -2,4 +2,5 @@ UPDATE scouter.drift_profile
 SET active = $1
 WHERE name = $2
   and repository = $3
-  and version = $4;
\ No newline at end of file
+  and version = $4
+  and ($5::varchar IS NULL OR drift_type = $5);
\ No newline at end of file
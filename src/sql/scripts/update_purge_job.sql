@@ -0,0 +1,7 @@
+UPDATE scouter.purge_jobs
+SET 
+  status = $2,
+  rows_deleted = $3,
+  error = $4,
+  updated_at = timezone('utc', now())
+WHERE id = $1;
\ No newline at end of file
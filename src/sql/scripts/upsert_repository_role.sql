@@ -0,0 +1,6 @@
+INSERT INTO scouter.repository_roles (subject, repository, role)
+VALUES ($1, $2, $3)
+ON CONFLICT (subject, repository) DO UPDATE
+SET role = excluded.role,
+    updated_at = timezone('utc', now())
+RETURNING created_at, updated_at, subject, repository, role;
\ No newline at end of file
@@ -0,0 +1,77 @@
+use anyhow::anyhow;
+use std::str::FromStr;
+
+pub enum TimeInterval {
+    FiveMinutes,
+    FifteenMinutes,
+    ThirtyMinutes,
+    OneHour,
+    ThreeHours,
+    SixHours,
+    TwelveHours,
+    TwentyFourHours,
+    TwoDays,
+    FiveDays,
+}
+
+impl TimeInterval {
+    pub fn to_minutes(&self) -> i32 {
+        match self {
+            TimeInterval::FiveMinutes => 5,
+            TimeInterval::FifteenMinutes => 15,
+            TimeInterval::ThirtyMinutes => 30,
+            TimeInterval::OneHour => 60,
+            TimeInterval::ThreeHours => 180,
+            TimeInterval::SixHours => 360,
+            TimeInterval::TwelveHours => 720,
+            TimeInterval::TwentyFourHours => 1440,
+            TimeInterval::TwoDays => 2880,
+            TimeInterval::FiveDays => 7200,
+        }
+    }
+}
+
+impl FromStr for TimeInterval {
+    type Err = anyhow::Error;
+
+    fn from_str(time_window: &str) -> Result<Self, Self::Err> {
+        match time_window {
+            "5minute" => Ok(TimeInterval::FiveMinutes),
+            "15minute" => Ok(TimeInterval::FifteenMinutes),
+            "30minute" => Ok(TimeInterval::ThirtyMinutes),
+            "1hour" => Ok(TimeInterval::OneHour),
+            "3hour" => Ok(TimeInterval::ThreeHours),
+            "6hour" => Ok(TimeInterval::SixHours),
+            "12hour" => Ok(TimeInterval::TwelveHours),
+            "24hour" => Ok(TimeInterval::TwentyFourHours),
+            "2day" => Ok(TimeInterval::TwoDays),
+            "5day" => Ok(TimeInterval::FiveDays),
+            _ => Err(anyhow!(
+                "Unknown time window: {}. Expected one of 5minute, 15minute, 30minute, 1hour, 3hour, 6hour, 12hour, 24hour, 2day, 5day",
+                time_window
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_interval() {
+        assert_eq!(TimeInterval::FiveMinutes.to_minutes(), 5);
+        assert_eq!(TimeInterval::FifteenMinutes.to_minutes(), 15);
+        assert_eq!(TimeInterval::ThirtyMinutes.to_minutes(), 30);
+        assert_eq!(TimeInterval::OneHour.to_minutes(), 60);
+        assert_eq!(TimeInterval::ThreeHours.to_minutes(), 180);
+        assert_eq!(TimeInterval::SixHours.to_minutes(), 360);
+        assert_eq!(TimeInterval::TwelveHours.to_minutes(), 720);
+        assert_eq!(TimeInterval::TwentyFourHours.to_minutes(), 1440);
+        assert_eq!(TimeInterval::TwoDays.to_minutes(), 2880);
+        assert_eq!(TimeInterval::FiveDays.to_minutes(), 7200);
+
+        assert_eq!(TimeInterval::from_str("24hour").unwrap().to_minutes(), 1440);
+        assert!(TimeInterval::from_str("24hours").is_err());
+    }
+}
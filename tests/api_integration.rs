@@ -1,22 +1,36 @@
 use axum::{
     body::Body,
     http::{self, Request, StatusCode},
+    Router,
 };
+use chrono::NaiveDateTime;
 use http_body_util::BodyExt;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use scouter::core::drift::base::ServerRecords;
-use scouter::core::drift::base::{DriftType, ServerRecord};
+use scouter::core::drift::base::{DriftType, RecordType, ServerRecord};
 use scouter::core::drift::spc::types::{
     SpcAlertConfig, SpcAlertRule, SpcDriftConfig, SpcDriftProfile, SpcFeatureDriftProfile,
 };
+use scouter::core::observe::observer::{LatencyMetrics, ObservabilityMetrics, RouteMetrics};
 use scouter::core::{dispatch::types::AlertDispatchType, drift::spc::types::SpcServerRecord};
-use scouter_server::api::schema::{ProfileRequest, ProfileStatusRequest};
-use scouter_server::sql::schema::{ObservabilityResult, QueryResult};
-use serde_json::Value;
-use std::collections::HashMap;
+use scouter_server::alerts::psi::types::PsiServerRecord;
+use scouter_server::api::auth::{generate_api_key, hash_api_key, Role, Scope};
+use scouter_server::api::jwt::{JwksSource, JwtConfig, JwtValidator};
+use scouter_server::api::schema::{
+    AlertAction, AlertStatusRequest, ApiKeyRequest, DriftAlertRequest, ProfileRequest,
+    ProfileStatusRequest, RoleBindingRequest, ServiceInfo,
+};
+use scouter_server::sql::schema::{
+    AlertHistoryRecord, ApiKeyRecord, BatchInsertResult, BinnedQueryResult, FeatureRecord,
+    ObservabilityResult, RoleBinding,
+};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
 use tower::Service;
 use tower::ServiceExt; // for `call`, `oneshot`, and `ready`
 mod test_utils;
 use scouter_server::alerts::base::DriftExecutor;
+use scouter_server::observe::sketch::LatencySketch;
 use scouter_server::sql::postgres::PostgresClient;
 use scouter_server::sql::schema::AlertResult;
 use sqlx::Row;
@@ -74,7 +88,7 @@ async fn test_api_drift() {
     let body: Value = serde_json::from_slice(&body).unwrap();
 
     let data = body.get("data");
-    let data: QueryResult = serde_json::from_value(data.unwrap().clone()).unwrap();
+    let data: BinnedQueryResult = serde_json::from_value(data.unwrap().clone()).unwrap();
 
     assert_eq!(data.features.len(), 3);
 
@@ -126,7 +140,7 @@ async fn test_api_drift() {
     let body: Value = serde_json::from_slice(&body).unwrap();
 
     let data = body.get("data");
-    let data: QueryResult = serde_json::from_value(data.unwrap().clone()).unwrap();
+    let data: BinnedQueryResult = serde_json::from_value(data.unwrap().clone()).unwrap();
 
     assert_eq!(data.features.len(), 1);
 
@@ -135,6 +149,384 @@ async fn test_api_drift() {
     // test api
 }
 
+// Post spc records for test_app/test/1.0.0 through the drift route
+async fn post_spc_records(app: &mut Router, records: Vec<(&str, f64, NaiveDateTime)>) {
+    let server_records = ServerRecords {
+        record_type: RecordType::SPC,
+        records: records
+            .into_iter()
+            .map(|(feature, value, created_at)| ServerRecord::SPC {
+                record: SpcServerRecord {
+                    created_at,
+                    name: "test_app".to_string(),
+                    repository: "test".to_string(),
+                    feature: feature.to_string(),
+                    value,
+                    version: "1.0.0".to_string(),
+                },
+            })
+            .collect(),
+    };
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/drift")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .method("POST")
+                .body(Body::from(serde_json::to_string(&server_records).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+// Query binned drift for test_app/test/1.0.0 over the last 5 minutes
+async fn get_binned_drift(app: &mut Router, query: &str) -> (StatusCode, Value) {
+    let response = app
+        .call(
+            Request::builder()
+                .uri(format!(
+                    "/scouter/drift?name=test_app&repository=test&version=1.0.0&time_window=5minute{}",
+                    query
+                ))
+                .method("GET")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+#[tokio::test]
+async fn test_api_drift_aggregation() {
+    let mut app = test_utils::setup_api(true).await.unwrap();
+    let now = chrono::Utc::now().naive_utc();
+
+    post_spc_records(
+        &mut app,
+        vec![
+            ("feature0", 1.0, now),
+            ("feature1", 2.0, now),
+            ("feature1", 4.0, now),
+        ],
+    )
+    .await;
+
+    // bands are only returned when requested
+    let (status, body) = get_binned_drift(&mut app, "&max_data_points=1000").await;
+    assert_eq!(status, StatusCode::OK);
+    let data: BinnedQueryResult = serde_json::from_value(body["data"].clone()).unwrap();
+    assert!(data.features["feature1"].min.is_none());
+
+    let (status, body) =
+        get_binned_drift(&mut app, "&max_data_points=1&aggregation=count&bands=true").await;
+    assert_eq!(status, StatusCode::OK);
+    let data: BinnedQueryResult = serde_json::from_value(body["data"].clone()).unwrap();
+
+    let feature = &data.features["feature1"];
+    assert_eq!(feature.values, vec![Some(2.0)]);
+    assert_eq!(feature.sample_count, vec![2]);
+    assert_eq!(feature.min, Some(vec![Some(2.0)]));
+    assert_eq!(feature.max, Some(vec![Some(4.0)]));
+
+    // unknown aggregations are rejected
+    let (status, _) = get_binned_drift(&mut app, "&max_data_points=1000&aggregation=mode").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_drift_fill_gaps() {
+    let mut app = test_utils::setup_api(true).await.unwrap();
+    let now = chrono::Utc::now().naive_utc();
+
+    // feature_stale stopped reporting before the queried window
+    post_spc_records(
+        &mut app,
+        vec![
+            ("feature0", 2.0, now),
+            ("feature_stale", 1.0, now - chrono::Duration::hours(1)),
+        ],
+    )
+    .await;
+
+    let (status, body) = get_binned_drift(&mut app, "&max_data_points=10&fill_gaps=true").await;
+    assert_eq!(status, StatusCode::OK);
+    let data: BinnedQueryResult = serde_json::from_value(body["data"].clone()).unwrap();
+
+    // every bin is returned, with nulls where there are no records
+    let feature = &data.features["feature0"];
+    assert!(feature.created_at.len() >= 10);
+    assert_eq!(feature.values.len(), feature.created_at.len());
+    assert_eq!(feature.sample_count.iter().sum::<i64>(), 1);
+    assert_eq!(
+        feature.values.iter().flatten().collect::<Vec<_>>(),
+        vec![&2.0]
+    );
+    assert!(feature.created_at.windows(2).all(|w| w[0] > w[1]));
+
+    // catalogued features without records in the window still get an empty series
+    let stale = &data.features["feature_stale"];
+    assert_eq!(stale.created_at, feature.created_at);
+    assert!(stale.values.iter().all(Option::is_none));
+    assert_eq!(stale.sample_count.iter().sum::<i64>(), 0);
+
+    // without gap filling only bins with records are returned
+    let (_, body) = get_binned_drift(&mut app, "&max_data_points=10").await;
+    let data: BinnedQueryResult = serde_json::from_value(body["data"].clone()).unwrap();
+    assert_eq!(data.features.len(), 1);
+    assert_eq!(data.features["feature0"].created_at.len(), 1);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_drift_features() {
+    let mut app = test_utils::setup_api(true).await.unwrap();
+    let now = chrono::Utc::now().naive_utc();
+
+    post_spc_records(
+        &mut app,
+        vec![
+            ("feature0", 0.0, now),
+            ("feature1", 1.0, now),
+            ("feature2", 2.0, now),
+        ],
+    )
+    .await;
+
+    // only the requested features are binned
+    let (status, body) =
+        get_binned_drift(&mut app, "&max_data_points=1000&features=feature0,feature2").await;
+    assert_eq!(status, StatusCode::OK);
+    let data: BinnedQueryResult = serde_json::from_value(body["data"].clone()).unwrap();
+
+    assert_eq!(
+        data.features.keys().collect::<Vec<_>>(),
+        vec!["feature0", "feature2"]
+    );
+
+    // the feature filter also applies to gap filled series
+    let (_, body) = get_binned_drift(
+        &mut app,
+        "&max_data_points=10&fill_gaps=true&features=feature1",
+    )
+    .await;
+    let data: BinnedQueryResult = serde_json::from_value(body["data"].clone()).unwrap();
+    assert_eq!(data.features.keys().collect::<Vec<_>>(), vec!["feature1"]);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_drift_batch() {
+    let mut app = test_utils::setup_api(true).await.unwrap();
+
+    // a batch of spc records plus one record that doesn't match the batch type
+    let mut records = (0..10)
+        .map(|i| ServerRecord::SPC {
+            record: SpcServerRecord {
+                created_at: chrono::Utc::now().naive_utc(),
+                name: "test_app".to_string(),
+                repository: "test".to_string(),
+                feature: format!("feature{}", i % 2),
+                value: i as f64,
+                version: "1.0.0".to_string(),
+            },
+        })
+        .collect::<Vec<_>>();
+
+    let mut status_codes = HashMap::new();
+    status_codes.insert(200_usize, 10_i64);
+    let observability_record = ObservabilityMetrics {
+        name: "test_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+        request_count: 10,
+        error_count: 0,
+        route_metrics: vec![RouteMetrics {
+            route_name: "test_route".to_string(),
+            metrics: LatencyMetrics {
+                p5: 0_f64,
+                p25: 0_f64,
+                p50: 0.25_f64,
+                p95: 0.25_f64,
+                p99: 0.25_f64,
+            },
+            request_count: 10,
+            error_count: 0,
+            error_latency: 0_f64,
+            status_codes,
+        }],
+    };
+
+    records.push(ServerRecord::OBSERVABILITY {
+        record: observability_record.clone(),
+    });
+
+    let server_records = ServerRecords {
+        record_type: RecordType::SPC,
+        records,
+    };
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/drift")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .method("POST")
+                .body(Body::from(serde_json::to_string(&server_records).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let data: BatchInsertResult =
+        serde_json::from_value(body.get("data").unwrap().clone()).unwrap();
+
+    assert_eq!(data.accepted, 10);
+    assert_eq!(data.rejected, 1);
+
+    // observability records are accepted on the same route
+    let server_records = ServerRecords {
+        record_type: RecordType::OBSERVABILITY,
+        records: vec![ServerRecord::OBSERVABILITY {
+            record: observability_record,
+        }],
+    };
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/drift")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .method("POST")
+                .body(Body::from(serde_json::to_string(&server_records).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let data: BatchInsertResult =
+        serde_json::from_value(body.get("data").unwrap().clone()).unwrap();
+
+    assert_eq!(data.accepted, 1);
+    assert_eq!(data.rejected, 0);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_features() {
+    let mut app = test_utils::setup_api(true).await.unwrap();
+    let created_at = chrono::Utc::now().naive_utc();
+
+    let spc_records = ServerRecords {
+        record_type: RecordType::SPC,
+        records: (0..4)
+            .map(|i| ServerRecord::SPC {
+                record: SpcServerRecord {
+                    created_at: created_at - chrono::Duration::minutes(i),
+                    name: "test_app".to_string(),
+                    repository: "test".to_string(),
+                    feature: format!("feature{}", i % 2),
+                    value: i as f64,
+                    version: "1.0.0".to_string(),
+                },
+            })
+            .collect(),
+    };
+
+    // the pinned scouter release has no psi server record, so the batch is built as sent over the wire
+    let psi_records = json!({
+        "record_type": "PSI",
+        "records": (0..3)
+            .map(|bin| json!({"PSI": {"record": PsiServerRecord {
+                created_at,
+                name: "test_app".to_string(),
+                repository: "test".to_string(),
+                version: "1.0.0".to_string(),
+                feature: "feature_psi".to_string(),
+                bin_id: bin,
+                bin_count: bin + 1,
+                record_type: RecordType::PSI,
+            }}}))
+            .collect::<Vec<_>>()
+    });
+    let spc_records = serde_json::to_value(&spc_records).unwrap();
+
+    // the spc batch is sent twice, duplicates must not be counted again
+    for records in [&spc_records, &spc_records, &psi_records] {
+        let response = app
+            .call(
+                Request::builder()
+                    .uri("/scouter/drift")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .method("POST")
+                    .body(Body::from(serde_json::to_string(records).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/features?name=test_app&repository=test&version=1.0.0")
+                .method("GET")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let features: Vec<FeatureRecord> = serde_json::from_value(body["data"].clone()).unwrap();
+
+    assert_eq!(
+        features
+            .iter()
+            .map(|f| (f.feature.as_str(), f.data_type.as_str(), f.record_count))
+            .collect::<Vec<_>>(),
+        vec![
+            ("feature0", "spc", 2),
+            ("feature1", "spc", 2),
+            ("feature_psi", "psi", 3)
+        ]
+    );
+
+    // feature0 was recorded at 0 and 2 minutes ago
+    let feature0 = &features[0];
+    assert_eq!(
+        feature0.last_seen - feature0.first_seen,
+        chrono::Duration::minutes(2)
+    );
+
+    test_utils::teardown().await.unwrap();
+}
+
 #[tokio::test]
 async fn test_api_profile() {
     let app = test_utils::setup_api(true).await.unwrap();
@@ -237,6 +629,7 @@ async fn test_api_profile_update() {
         repository: "mathworld".to_string(),
         version: "0.1.0".to_string(),
         active: true,
+        drift_type: None,
     };
 
     let body = serde_json::to_string(&body).unwrap();
@@ -439,6 +832,7 @@ async fn test_observability_metrics() {
     sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
 
     let response = app
+        .clone()
         .oneshot(
             Request::builder()
                 .uri("/scouter/observability/metrics?name=example-service-1&repository=example-repo-1&version=1.0.0&time_window=5minute&max_data_points=1000")
@@ -458,5 +852,1856 @@ async fn test_observability_metrics() {
     assert!(!metrics.is_empty());
     assert_eq!(metrics.len(), 2);
 
+    let get_metrics = |query: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri(format!(
+                            "/scouter/observability/metrics?name=example-service-1&repository=example-repo-1&version=1.0.0&max_data_points=100{}",
+                            query
+                        ))
+                        .method("GET")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    // explicit iso-8601 range covering the populated metrics
+    let now = chrono::Utc::now();
+    let (status, body) = get_metrics(format!(
+        "&start={}&end={}",
+        (now - chrono::Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ"),
+        (now + chrono::Duration::minutes(1)).format("%Y-%m-%dT%H:%M:%SZ"),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let metrics = serde_json::from_value::<Vec<ObservabilityResult>>(body["data"].clone()).unwrap();
+    assert_eq!(metrics.len(), 2);
+
+    // gap filled series have a bin for every interval in the range
+    let (status, body) = get_metrics(format!(
+        "&start={}&end={}&fill_gaps=true",
+        (now - chrono::Duration::hours(1)).format("%Y-%m-%dT%H:%M:%SZ"),
+        (now + chrono::Duration::minutes(1)).format("%Y-%m-%dT%H:%M:%SZ"),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let metrics = serde_json::from_value::<Vec<ObservabilityResult>>(body["data"].clone()).unwrap();
+    assert_eq!(metrics.len(), 2);
+    for metric in metrics {
+        assert!(metric.created_at.len() >= 100);
+        assert_eq!(metric.sample_count.len(), metric.created_at.len());
+        for (i, count) in metric.sample_count.iter().enumerate() {
+            assert_eq!(*count == 0, metric.p50[i].is_none());
+            if *count == 0 {
+                assert_eq!(metric.total_request_count[i], 0);
+                assert!(metric.status_counts[i].is_empty());
+            }
+        }
+    }
+
+    // a range from last week has no data
+    let (status, body) = get_metrics(format!(
+        "&start={}&end={}",
+        (now - chrono::Duration::days(8)).format("%Y-%m-%dT%H:%M:%S"),
+        (now - chrono::Duration::days(7)).format("%Y-%m-%dT%H:%M:%S"),
+    ))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["data"].as_array().unwrap().is_empty());
+
+    // invalid windows and ranges are rejected instead of falling back to a default
+    let (status, body) = get_metrics("&time_window=5minutes".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("Unknown time window"));
+
+    let (status, _) =
+        get_metrics("&start=2024-11-02T00:00:00&end=2024-11-01T00:00:00".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = get_metrics("&start=yesterday".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_keys() {
+    let (mut app, db_client) = test_utils::setup_auth_api(true, None).await.unwrap();
+
+    // seed an admin key directly in the database
+    let (admin_key, admin_prefix) = generate_api_key();
+    db_client
+        .insert_api_key(
+            "admin",
+            &admin_prefix,
+            &hash_api_key(&admin_key),
+            &["admin".to_string()],
+            None,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    let drift_uri = |repository: &str| {
+        format!("/scouter/drift?name=test_app&repository={}&version=1.0.0&time_window=5minute&max_data_points=1000", repository)
+    };
+
+    // healthcheck stays public
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/healthcheck")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // missing key
+    let response = app
+        .call(
+            Request::builder()
+                .uri(drift_uri("test"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    // create a read key restricted to the test repository
+    let request = ApiKeyRequest {
+        name: "reader".to_string(),
+        scopes: vec![Scope::Read],
+        repositories: Some(vec!["test".to_string()]),
+    };
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/auth/keys")
+                .method("POST")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", admin_key))
+                .body(Body::from(serde_json::to_string(&request).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let read_key = body["data"]["key"].as_str().unwrap().to_string();
+    let record: ApiKeyRecord = serde_json::from_value(body["data"]["record"].clone()).unwrap();
+    assert_eq!(record.scopes, vec!["read".to_string()]);
+
+    // read key can query its repository but nothing else
+    let response = app
+        .call(
+            Request::builder()
+                .uri(drift_uri("test"))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", read_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri(drift_uri("other"))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", read_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // read key cannot ingest
+    let server_records = ServerRecords {
+        record_type: RecordType::SPC,
+        records: vec![],
+    };
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/drift")
+                .method("POST")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", read_key))
+                .body(Body::from(serde_json::to_string(&server_records).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // admins restricted to another repository cannot revoke the read key or the admin key
+    let (other_key, other_prefix) = generate_api_key();
+    db_client
+        .insert_api_key(
+            "other-admin",
+            &other_prefix,
+            &hash_api_key(&other_key),
+            &["admin".to_string()],
+            Some(&["other".to_string()]),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    let admin_record = db_client
+        .get_api_key(&hash_api_key(&admin_key))
+        .await
+        .unwrap()
+        .unwrap();
+
+    for id in [record.id, admin_record.id] {
+        let response = app
+            .call(
+                Request::builder()
+                    .uri(format!("/scouter/auth/keys/{}", id))
+                    .method("DELETE")
+                    .header(http::header::AUTHORIZATION, format!("Bearer {}", other_key))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    // revoke the read key
+    let response = app
+        .call(
+            Request::builder()
+                .uri(format!("/scouter/auth/keys/{}", record.id))
+                .method("DELETE")
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", admin_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri(drift_uri("test"))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", read_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_jwt() {
+    let config = JwtConfig::new(JwksSource::File(
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jwt/jwks.json").into(),
+    ));
+    let validator = JwtValidator::new(config).await.unwrap();
+    let (mut app, _) = test_utils::setup_auth_api(true, Some(validator))
+        .await
+        .unwrap();
+
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some("scouter-test".to_string());
+    let token = encode(
+        &header,
+        &serde_json::json!({
+            "sub": "user@example.com",
+            "exp": chrono::Utc::now().timestamp() + 600,
+            "scope": "read",
+            "repositories": ["test"],
+        }),
+        &EncodingKey::from_rsa_pem(include_bytes!("fixtures/jwt/private_key.pem")).unwrap(),
+    )
+    .unwrap();
+
+    let profile_uri = |repository: &str| {
+        format!(
+            "/scouter/profile?name=test_app&repository={}&version=1.0.0",
+            repository
+        )
+    };
+
+    // token is accepted, profile simply does not exist
+    let response = app
+        .call(
+            Request::builder()
+                .uri(profile_uri("test"))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    // repository not listed in the token claims
+    let response = app
+        .call(
+            Request::builder()
+                .uri(profile_uri("other"))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // read scope cannot toggle profile status
+    let request = ProfileStatusRequest {
+        name: "test_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+        active: false,
+        drift_type: None,
+    };
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/profile/status")
+                .method("PUT")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", token))
+                .body(Body::from(serde_json::to_string(&request).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // invalid signature
+    let response = app
+        .call(
+            Request::builder()
+                .uri(profile_uri("test"))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}x", token))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_repository_roles() {
+    let (mut app, db_client) = test_utils::setup_auth_api(true, None).await.unwrap();
+
+    let (admin_key, admin_prefix) = generate_api_key();
+    db_client
+        .insert_api_key(
+            "admin",
+            &admin_prefix,
+            &hash_api_key(&admin_key),
+            &["admin".to_string()],
+            None,
+        )
+        .await
+        .unwrap();
+
+    // unrestricted read key with no role bindings yet
+    let (team_key, team_prefix) = generate_api_key();
+    let team_record = db_client
+        .insert_api_key(
+            "team-a",
+            &team_prefix,
+            &hash_api_key(&team_key),
+            &["read".to_string()],
+            None,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+    let subject = format!("api_key:{}", team_record.id);
+
+    let status_request = |repository: &str| {
+        let request = ProfileStatusRequest {
+            name: "test_app".to_string(),
+            repository: repository.to_string(),
+            version: "1.0.0".to_string(),
+            active: false,
+            drift_type: None,
+        };
+
+        Request::builder()
+            .uri("/scouter/profile/status")
+            .method("PUT")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::AUTHORIZATION, format!("Bearer {}", team_key))
+            .body(Body::from(serde_json::to_string(&request).unwrap()))
+            .unwrap()
+    };
+
+    let alerts_request = |repository: &str| {
+        Request::builder()
+            .uri(format!(
+                "/scouter/alerts?name=test_app&repository={}&version=1.0.0",
+                repository
+            ))
+            .header(http::header::AUTHORIZATION, format!("Bearer {}", team_key))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let bind_request = |method: &str, role: Option<Role>| {
+        let request = RoleBindingRequest {
+            subject: subject.clone(),
+            repository: "team-a".to_string(),
+            role,
+        };
+
+        Request::builder()
+            .uri("/scouter/auth/roles")
+            .method(method)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .header(http::header::AUTHORIZATION, format!("Bearer {}", admin_key))
+            .body(Body::from(serde_json::to_string(&request).unwrap()))
+            .unwrap()
+    };
+
+    // no binding, the read scope makes the key a viewer everywhere
+    let response = app.call(alerts_request("team-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(status_request("team-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // viewer can read but not deactivate monitors
+    let response = app
+        .call(bind_request("PUT", Some(Role::Viewer)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(alerts_request("team-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(status_request("team-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // editors still cannot change monitor status
+    let response = app
+        .call(bind_request("PUT", Some(Role::Editor)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(status_request("team-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // owners can, but only on their own repository
+    let response = app
+        .call(bind_request("PUT", Some(Role::Owner)))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(status_request("team-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(status_request("team-b")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // list bindings
+    let response = app
+        .call(
+            Request::builder()
+                .uri(format!("/scouter/auth/roles?subject={}", subject))
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", admin_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let bindings: Vec<RoleBinding> = serde_json::from_value(body["data"].clone()).unwrap();
+    assert_eq!(bindings.len(), 1);
+    assert_eq!(bindings[0].role, "owner");
+
+    // admins restricted to another repository do not see the binding
+    let (other_key, other_prefix) = generate_api_key();
+    db_client
+        .insert_api_key(
+            "team-b-admin",
+            &other_prefix,
+            &hash_api_key(&other_key),
+            &["admin".to_string()],
+            Some(&["team-b".to_string()]),
+        )
+        .await
+        .unwrap();
+
+    let roles_request = |uri: String| {
+        Request::builder()
+            .uri(uri)
+            .header(http::header::AUTHORIZATION, format!("Bearer {}", other_key))
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let response = app
+        .call(roles_request(format!(
+            "/scouter/auth/roles?subject={}",
+            subject
+        )))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let bindings: Vec<RoleBinding> = serde_json::from_value(body["data"].clone()).unwrap();
+    assert!(bindings.is_empty());
+
+    let response = app
+        .call(roles_request(
+            "/scouter/auth/roles?repository=team-a".to_string(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // team keys cannot manage bindings themselves
+    let response = app
+        .call(
+            Request::builder()
+                .uri("/scouter/auth/roles")
+                .header(http::header::AUTHORIZATION, format!("Bearer {}", team_key))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    // remove binding
+    let response = app.call(bind_request("DELETE", None)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.call(status_request("team-a")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_alert_lifecycle() {
+    let mut app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    // populate the database and generate alerts
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+    let mut drift_executor = DriftExecutor::new(db_client.clone());
+    drift_executor.poll_for_tasks().await.unwrap();
+
+    let alerts = db_client
+        .get_drift_alerts(&DriftAlertRequest {
+            name: "test_app".to_string(),
+            repository: "statworld".to_string(),
+            version: "0.1.0".to_string(),
+            limit_timestamp: None,
+            active: Some(true),
+            limit: None,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let id = alerts[0].id;
+
+    let status_request = |id: i32, action: AlertAction| {
+        let request = AlertStatusRequest {
+            action,
+            actor: Some("on-call".to_string()),
+            comment: Some(format!("{} from test", action.as_str())),
+        };
+
+        Request::builder()
+            .uri(format!("/scouter/alerts/{}", id))
+            .method("PUT")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(serde_json::to_string(&request).unwrap()))
+            .unwrap()
+    };
+
+    let response = app
+        .call(status_request(id, AlertAction::Acknowledge))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let alert: AlertResult = serde_json::from_value(body["data"].clone()).unwrap();
+    assert_eq!(alert.status, "acknowledged");
+
+    // acknowledging twice is not a valid transition
+    let response = app
+        .call(status_request(id, AlertAction::Acknowledge))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+
+    let response = app
+        .call(status_request(id, AlertAction::Resolve))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(status_request(id, AlertAction::Reopen))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .call(status_request(i32::MAX, AlertAction::Resolve))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .call(
+            Request::builder()
+                .uri(format!("/scouter/alerts/{}/history", id))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let history: Vec<AlertHistoryRecord> = serde_json::from_value(body["data"].clone()).unwrap();
+
+    let transitions = history
+        .iter()
+        .map(|record| (record.previous_status.as_str(), record.status.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        transitions,
+        vec![
+            ("active", "acknowledged"),
+            ("acknowledged", "resolved"),
+            ("resolved", "active")
+        ]
+    );
+    assert!(history.iter().all(|record| record.actor == "on-call"));
+    assert!(history.iter().all(|record| record.subject == "anonymous"));
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_alert_pagination() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let service_info = ServiceInfo {
+        name: "test_app".to_string(),
+        repository: "statworld".to_string(),
+        version: "0.1.0".to_string(),
+    };
+
+    for i in 0..5 {
+        let feature = format!("feature_{}", i);
+        let mut alert = BTreeMap::new();
+        alert.insert("feature".to_string(), feature.clone());
+        alert.insert("kind".to_string(), "Consecutive".to_string());
+        alert.insert("zone".to_string(), format!("Zone {}", i % 2 + 1));
+
+        db_client
+            .insert_drift_alert(&service_info, &feature, &alert)
+            .await
+            .unwrap();
+    }
+
+    let get_alerts = |query: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri(format!(
+                            "/scouter/alerts?name=test_app&repository=statworld&version=0.1.0{}",
+                            query
+                        ))
+                        .method("GET")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    // walk every page following next_cursor
+    let mut ids = Vec::new();
+    let mut query = "&limit=2".to_string();
+    loop {
+        let (status, body) = get_alerts(query).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["total"], 5);
+
+        let data: Vec<AlertResult> = serde_json::from_value(body["data"].clone()).unwrap();
+        assert!(data.len() <= 2);
+        ids.extend(data.iter().map(|alert| alert.id));
+
+        match body["next_cursor"].as_str() {
+            Some(cursor) => query = format!("&limit=2&cursor={}", cursor),
+            None => break,
+        }
+    }
+
+    let mut unique_ids = ids.clone();
+    unique_ids.sort();
+    unique_ids.dedup();
+    assert_eq!(ids.len(), 5);
+    assert_eq!(unique_ids.len(), 5);
+
+    // newest first
+    assert!(ids.windows(2).all(|pair| pair[0] > pair[1]));
+
+    // filters on the alert payload and feature
+    let (_, body) = get_alerts("&zone=Zone%201".to_string()).await;
+    assert_eq!(body["total"], 3);
+
+    let (_, body) = get_alerts("&feature=feature_2&kind=Consecutive".to_string()).await;
+    let data: Vec<AlertResult> = serde_json::from_value(body["data"].clone()).unwrap();
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0].feature, "feature_2");
+
+    let (_, body) = get_alerts("&status=resolved".to_string()).await;
+    assert_eq!(body["total"], 0);
+
+    // upper time bound in the past excludes everything
+    let (_, body) = get_alerts("&end_timestamp=2020-01-01T00:00:00".to_string()).await;
+    assert_eq!(body["total"], 0);
+
+    let (status, _) = get_alerts("&end_timestamp=yesterday".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = get_alerts("&cursor=not-a-cursor".to_string()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_list_profiles() {
+    let (app, db_client) = test_utils::setup_auth_api(true, None).await.unwrap();
+
+    db_client
+        .raw_query(
+            r#"
+            INSERT INTO scouter.drift_profile (name, repository, version, profile, drift_type, active, schedule, next_run, previous_run)
+            VALUES
+                ('model_a', 'statworld', '1.0.0', '{}', 'SPC', true, '0 0 * * * *', now(), now()),
+                ('model_b', 'statworld', '1.0.0', '{}', 'PSI', false, '0 0 * * * *', now(), now()),
+                ('modelx%', 'statworld', '1.0.0', '{}', 'SPC', true, '0 0 * * * *', now(), now()),
+                ('other', 'mathworld', '1.0.0', '{}', 'PSI', true, '0 0 * * * *', now(), now())
+            "#,
+        )
+        .await
+        .unwrap();
+
+    let (admin_key, admin_prefix) = generate_api_key();
+    db_client
+        .insert_api_key(
+            "admin",
+            &admin_prefix,
+            &hash_api_key(&admin_key),
+            &["admin".to_string()],
+            None,
+        )
+        .await
+        .unwrap();
+
+    let (reader_key, reader_prefix) = generate_api_key();
+    db_client
+        .insert_api_key(
+            "reader",
+            &reader_prefix,
+            &hash_api_key(&reader_key),
+            &["read".to_string()],
+            Some(&["statworld".to_string()]),
+        )
+        .await
+        .unwrap();
+
+    let list_profiles = |key: String, query: &str| {
+        let app = app.clone();
+        let request = Request::builder()
+            .uri(format!("/scouter/profiles{}", query))
+            .method("GET")
+            .header(http::header::AUTHORIZATION, format!("Bearer {}", key))
+            .body(Body::empty())
+            .unwrap();
+
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    let names = |body: &Value| {
+        body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|profile| profile["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let (status, body) = list_profiles(admin_key.clone(), "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 4);
+    assert_eq!(body["data"][0]["repository"], "mathworld");
+    assert_eq!(body["data"][0]["drift_type"], "PSI");
+
+    let (_, body) = list_profiles(admin_key.clone(), "?repository=statworld&name=model_").await;
+    assert_eq!(names(&body), vec!["model_a", "model_b"]);
+
+    // wildcards in the name prefix are matched literally
+    let (_, body) = list_profiles(admin_key.clone(), "?name=modelx%25").await;
+    assert_eq!(names(&body), vec!["modelx%"]);
+
+    let (_, body) = list_profiles(admin_key.clone(), "?drift_type=psi&active=true").await;
+    assert_eq!(names(&body), vec!["other"]);
+
+    let (_, body) = list_profiles(admin_key.clone(), "?limit=2&offset=1").await;
+    assert_eq!(names(&body), vec!["model_a", "model_b"]);
+    assert_eq!(body["next_offset"], 3);
+
+    // restricted callers only see their repositories
+    let (status, body) = list_profiles(reader_key.clone(), "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total"], 3);
+    assert!(body["next_offset"].is_null());
+
+    let (status, _) = list_profiles(reader_key.clone(), "?repository=mathworld").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_delete_profile() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(true).await.unwrap();
+
+    // populate the database
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+
+    let delete_profile = |repository: &str, purge: bool| {
+        let app = app.clone();
+        let body = json!({
+            "name": "test_app",
+            "repository": repository,
+            "version": "0.1.0",
+            "purge": purge
+        });
+        let request = Request::builder()
+            .uri("/scouter/profile")
+            .method("DELETE")
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    let count_rows = |query: &'static str| {
+        let pool = pool.clone();
+        async move { sqlx::raw_sql(query).fetch_all(&pool).await.unwrap().len() }
+    };
+
+    // deleting without purge keeps the recorded data
+    let (status, _) = delete_profile("mathworld", false).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        count_rows("SELECT * FROM scouter.drift_profile WHERE repository = 'mathworld'").await,
+        0
+    );
+    assert!(count_rows("SELECT * FROM scouter.drift WHERE repository = 'mathworld'").await > 0);
+
+    let (status, _) = delete_profile("mathworld", false).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // purging runs as a background job
+    let (status, body) = delete_profile("statworld", true).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    let job_id = body["data"]["id"].as_i64().unwrap();
+
+    let mut job = Value::Null;
+    for _ in 0..50 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/scouter/profile/purge/{}", job_id))
+                    .method("GET")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body: Value = serde_json::from_slice(&body).unwrap();
+        job = body["data"].clone();
+
+        if job["status"] == "completed" {
+            break;
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
+    }
+
+    assert_eq!(job["status"], "completed");
+    assert!(job["rows_deleted"].as_i64().unwrap() > 0);
+    assert_eq!(
+        count_rows("SELECT * FROM scouter.drift WHERE repository = 'statworld'").await,
+        0
+    );
+    assert!(count_rows("SELECT * FROM scouter.drift WHERE repository = 'mathworld'").await > 0);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_observability_alerts() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let post_profile = |body: Value| {
+        let app = app.clone();
+        async move {
+            app.oneshot(
+                Request::builder()
+                    .uri("/scouter/observability/profile")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .method("POST")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+        }
+    };
+
+    // every route needs at least one threshold
+    let status = post_profile(json!({
+        "name": "test_app",
+        "repository": "observe",
+        "version": "1.0.0",
+        "schedule": "0 0 0 * * *",
+        "routes": [{"route": "test_route"}]
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let status = post_profile(json!({
+        "name": "test_app",
+        "repository": "observe",
+        "version": "1.0.0",
+        "schedule": "0 0 0 * * *",
+        "routes": [
+            {"route": "test_route", "p95_ceiling": 0.1, "error_rate_ceiling": 0.5},
+            {"route": "missing_route", "request_count_floor": 1}
+        ]
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut status_codes = HashMap::new();
+    status_codes.insert(200_usize, 10_i64);
+    let server_records = ServerRecords {
+        record_type: RecordType::OBSERVABILITY,
+        records: vec![ServerRecord::OBSERVABILITY {
+            record: ObservabilityMetrics {
+                name: "test_app".to_string(),
+                repository: "observe".to_string(),
+                version: "1.0.0".to_string(),
+                request_count: 10,
+                error_count: 0,
+                route_metrics: vec![RouteMetrics {
+                    route_name: "test_route".to_string(),
+                    metrics: LatencyMetrics {
+                        p5: 0_f64,
+                        p25: 0_f64,
+                        p50: 0.25_f64,
+                        p95: 0.25_f64,
+                        p99: 0.25_f64,
+                    },
+                    request_count: 10,
+                    error_count: 0,
+                    error_latency: 0_f64,
+                    status_codes,
+                }],
+            },
+        }],
+    };
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/scouter/drift")
+                .header(http::header::CONTENT_TYPE, "application/json")
+                .method("POST")
+                .body(Body::from(serde_json::to_string(&server_records).unwrap()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // schedule the profile so the executor picks it up
+    sqlx::raw_sql(
+        r#"
+        UPDATE scouter.drift_profile
+        SET active = true,
+            next_run = CURRENT_TIMESTAMP - interval '1 minute',
+            previous_run = CURRENT_TIMESTAMP - interval '1 hour'
+        WHERE name = 'test_app'
+        AND repository = 'observe'
+        AND drift_type = 'OBSERVABILITY'
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let mut drift_executor = DriftExecutor::new(db_client.clone());
+    drift_executor.poll_for_tasks().await.unwrap();
+
+    let alerts = sqlx::raw_sql(
+        r#"
+        SELECT feature, alert
+        FROM scouter.drift_alerts
+        WHERE name = 'test_app'
+        AND repository = 'observe'
+        ORDER BY feature
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap()
+    .iter()
+    .map(|row| {
+        let alert: BTreeMap<String, String> = serde_json::from_value(row.get("alert")).unwrap();
+        (row.get::<String, _>("feature"), alert)
+    })
+    .collect::<Vec<_>>();
+
+    assert_eq!(alerts.len(), 2);
+    assert_eq!(alerts[0].0, "missing_route");
+    assert_eq!(alerts[0].1["kind"], "request_count");
+    assert_eq!(alerts[0].1["value"], "0");
+    assert_eq!(alerts[1].0, "test_route");
+    assert_eq!(alerts[1].1["kind"], "p95_latency");
+    assert_eq!(alerts[1].1["threshold"], "0.1");
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_observability_profile_key() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+
+    let send = |uri: String, method: &'static str, body: Option<Value>| {
+        let app = app.clone();
+        async move {
+            let body = match body {
+                Some(body) => Body::from(body.to_string()),
+                None => Body::empty(),
+            };
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri(uri)
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .method(method)
+                        .body(body)
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap_or_default();
+            (status, body)
+        }
+    };
+
+    let observability_profile = |p95_ceiling: f64| {
+        json!({
+            "name": "test_app",
+            "repository": "statworld",
+            "version": "0.1.0",
+            "schedule": "0 0 0 * * *",
+            "routes": [{"route": "test_route", "p95_ceiling": p95_ceiling}]
+        })
+    };
+
+    // the service already has an spc profile, the observability profile is stored next to it
+    let uri = "/scouter/observability/profile".to_string();
+    let (status, _) = send(uri.clone(), "POST", Some(observability_profile(0.1))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(uri.clone(), "POST", Some(observability_profile(0.2))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send(uri.clone(), "PUT", Some(observability_profile(0.3))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut missing = observability_profile(0.3);
+    missing["version"] = json!("9.9.9");
+    let (status, _) = send(uri, "PUT", Some(missing)).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let rows = sqlx::raw_sql(
+        r#"
+        SELECT drift_type, profile
+        FROM scouter.drift_profile
+        WHERE name = 'test_app'
+        AND repository = 'statworld'
+        ORDER BY drift_type
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].get::<String, _>("drift_type"), "OBSERVABILITY");
+    assert_eq!(rows[1].get::<String, _>("drift_type"), "SPC");
+    let spc_profile: Value = rows[1].get("profile");
+
+    // inserting the spc profile again doesn't silently succeed
+    let (status, _) = send(
+        "/scouter/profile".to_string(),
+        "POST",
+        Some(json!({"drift_type": "SPC", "profile": spc_profile})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // the drift profile is returned unless the observability profile is asked for
+    let uri = "/scouter/profile?name=test_app&repository=statworld&version=0.1.0";
+    let (_, body) = send(uri.to_string(), "GET", None).await;
+    assert_eq!(body["data"], spc_profile);
+
+    let (_, body) = send(format!("{}&drift_type=observability", uri), "GET", None).await;
+    assert_eq!(body["data"]["routes"][0]["p95_ceiling"], 0.3);
+
+    test_utils::teardown().await.unwrap();
+}
+
+// Set created_at on every observability record in a serialized batch
+fn with_created_at(value: &mut Value, created_at: &str) {
+    match value {
+        Value::Object(object) if object.contains_key("route_metrics") => {
+            object.insert("created_at".to_string(), json!(created_at));
+        }
+        Value::Object(object) => object
+            .values_mut()
+            .for_each(|value| with_created_at(value, created_at)),
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| with_created_at(value, created_at)),
+        _ => {}
+    }
+}
+
+#[tokio::test]
+async fn test_api_insert_observability_metrics() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+
+    let post_records = |uri: &'static str, body: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri(uri)
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .method("POST")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    let record = |request_count: i64| ServerRecord::OBSERVABILITY {
+        record: ObservabilityMetrics {
+            name: "test_app".to_string(),
+            repository: "observe".to_string(),
+            version: "1.0.0".to_string(),
+            request_count,
+            error_count: 0,
+            route_metrics: vec![RouteMetrics {
+                route_name: "test_route".to_string(),
+                metrics: LatencyMetrics {
+                    p5: 0_f64,
+                    p25: 0_f64,
+                    p50: 0.25_f64,
+                    p95: 0.25_f64,
+                    p99: 0.25_f64,
+                },
+                request_count,
+                error_count: 0,
+                error_latency: 0_f64,
+                status_codes: HashMap::new(),
+            }],
+        },
+    };
+
+    // a batch recorded by the client an hour ago
+    let mut batch = serde_json::to_value(ServerRecords {
+        record_type: RecordType::OBSERVABILITY,
+        records: vec![record(10)],
+    })
+    .unwrap();
+    let created_at = (chrono::Utc::now() - chrono::Duration::hours(1)).naive_utc();
+    with_created_at(
+        &mut batch,
+        &created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    );
+
+    let (status, body) = post_records("/scouter/observability/metrics", batch).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["accepted"], 1);
+
+    // a single record without a timestamp
+    let (status, body) = post_records(
+        "/scouter/observability/metrics",
+        serde_json::to_value(record(5)).unwrap(),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["accepted"], 1);
+
+    let rows = sqlx::raw_sql(
+        r#"
+        SELECT created_at, request_count
+        FROM scouter.observability_metrics
+        WHERE name = 'test_app'
+        AND repository = 'observe'
+        ORDER BY created_at
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(rows.len(), 2);
+    let stored: chrono::NaiveDateTime = rows[0].get("created_at");
+    assert_eq!(
+        stored.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        created_at.format("%Y-%m-%dT%H:%M:%S").to_string()
+    );
+    assert_eq!(rows[0].get::<i32, _>("request_count"), 10);
+    assert_eq!(rows[1].get::<i32, _>("request_count"), 5);
+
+    // other record types belong on /scouter/drift
+    let spc_records = serde_json::to_value(ServerRecords {
+        record_type: RecordType::SPC,
+        records: vec![ServerRecord::SPC {
+            record: SpcServerRecord {
+                created_at: chrono::Utc::now().naive_utc(),
+                name: "test_app".to_string(),
+                repository: "observe".to_string(),
+                feature: "feature0".to_string(),
+                value: 1.0,
+                version: "1.0.0".to_string(),
+            },
+        }],
+    })
+    .unwrap();
+    let (status, _) = post_records("/scouter/observability/metrics", spc_records).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // /scouter/drift keeps the client timestamp and sketches as well
+    let mut batch = serde_json::to_value(ServerRecords {
+        record_type: RecordType::OBSERVABILITY,
+        records: vec![record(3)],
+    })
+    .unwrap();
+    let created_at = (chrono::Utc::now() - chrono::Duration::hours(2)).naive_utc();
+    with_created_at(
+        &mut batch,
+        &created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+    );
+    let mut sketch = LatencySketch::default();
+    sketch.add(0.5, 3);
+    batch["records"][0]["OBSERVABILITY"]["record"]["route_metrics"][0]["latency_sketch"] =
+        serde_json::to_value(&sketch).unwrap();
+
+    let (status, body) = post_records("/scouter/drift", batch).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["accepted"], 1);
+
+    let rows = sqlx::raw_sql(
+        r#"
+        SELECT created_at, route_metrics
+        FROM scouter.observability_metrics
+        WHERE name = 'test_app'
+        AND repository = 'observe'
+        ORDER BY created_at
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    assert_eq!(rows.len(), 3);
+    let stored: chrono::NaiveDateTime = rows[0].get("created_at");
+    assert_eq!(
+        stored.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        created_at.format("%Y-%m-%dT%H:%M:%S").to_string()
+    );
+    let route_metrics: Value = rows[0].get("route_metrics");
+    let stored_sketch: LatencySketch =
+        serde_json::from_value(route_metrics[0]["latency_sketch"].clone()).unwrap();
+    assert_eq!(stored_sketch, sketch);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_profile_runs() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    // a stored profile that can't be parsed fails on every run
+    db_client
+        .raw_query(
+            r#"
+            INSERT INTO scouter.drift_profile (name, repository, version, profile, drift_type, active, schedule, next_run, previous_run)
+            VALUES ('broken', 'statworld', '1.0.0', '{}', 'SPC', true, '0 0 * * * *', now() - interval '1 hour', now() - interval '2 hour')
+            "#,
+        )
+        .await
+        .unwrap();
+
+    let mut drift_executor = DriftExecutor::new(db_client.clone());
+    drift_executor.poll_for_tasks().await.unwrap();
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/scouter/profile/runs?name=broken&repository=statworld&version=1.0.0")
+                .method("GET")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let runs = body["data"].as_array().unwrap();
+
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0]["status"], "failed");
+    assert_eq!(runs[0]["drift_type"], "SPC");
+    assert!(runs[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("Error converting drift profile"));
+
+    // the failure is surfaced on the profile summary
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/scouter/profiles?repository=statworld")
+                .method("GET")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    let profile = &body["data"][0];
+
+    assert_eq!(profile["name"], "broken");
+    assert_eq!(profile["last_run_status"], "failed");
+    assert!(profile["last_error"].is_string());
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_run_profile() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+
+    let run_profile = |body: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/scouter/profile/run")
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .method("POST")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    let count_alerts = || async {
+        sqlx::raw_sql("SELECT * FROM scouter.drift_alerts WHERE repository = 'statworld'")
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+            .len()
+    };
+
+    let (status, _) = run_profile(json!({
+        "name": "missing",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "time_window": "24hour"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = run_profile(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "start": "2024-01-02T00:00:00Z",
+        "end": "2024-01-01T00:00:00Z"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // a dry run returns the drift and alerts without inserting them
+    let (status, body) = run_profile(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "time_window": "24hour",
+        "dry_run": true
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["data"]["alerts"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"]["drift"]["col_3"].as_array().unwrap().len(), 10);
+    assert_eq!(count_alerts().await, 0);
+
+    let (status, body) = run_profile(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "time_window": "24hour"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["alerts"].as_array().unwrap().len(), 2);
+    assert_eq!(count_alerts().await, 2);
+
+    // running outside of the schedule leaves the run dates alone
+    let result = sqlx::raw_sql(
+        r#"
+        SELECT *
+        FROM scouter.drift_profile
+        WHERE name = 'test_app'
+        AND repository = 'statworld'
+        AND next_run < CURRENT_TIMESTAMP
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(result.len(), 1);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_backtest_profile() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+
+    let backtest = |body: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/scouter/profile/backtest")
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .method("POST")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    let (status, body) = backtest(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "time_window": "3hour",
+        "window": "1hour"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // all of the populated records fall in the most recent window
+    let windows = body["data"].as_array().unwrap();
+    assert_eq!(windows.len(), 3);
+    assert_eq!(windows[0]["rows_read"], 0);
+    assert_eq!(windows[2]["alerts"].as_array().unwrap().len(), 2);
+    assert_eq!(body["total_alerts"], 2);
+
+    // too many windows for the range
+    let (status, _) = backtest(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "start": "2024-01-01T00:00:00Z",
+        "end": "2024-03-01T00:00:00Z",
+        "window": "5minute"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // a replacement profile has to be valid for the stored drift type
+    let (status, _) = backtest(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "time_window": "3hour",
+        "window": "1hour",
+        "profile": {}
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let result = sqlx::raw_sql(
+        "SELECT profile FROM scouter.drift_profile WHERE name = 'test_app' AND repository = 'statworld'",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let profile: Value = result[0].get("profile");
+
+    // an override of the same profile is replayed
+    let (status, body) = backtest(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "time_window": "3hour",
+        "window": "1hour",
+        "profile": profile
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["total_alerts"], 2);
+
+    // but never another service's profile
+    let mut other = profile.clone();
+    other["config"]["repository"] = json!("other");
+    let (status, _) = backtest(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "time_window": "3hour",
+        "window": "1hour",
+        "profile": other
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    // spc profiles query every monitored feature in each window
+    let mut wide = profile.clone();
+    wide["config"]["alert_config"]["features_to_monitor"] =
+        json!((0..20).map(|i| format!("col_{}", i)).collect::<Vec<_>>());
+    let (status, body) = backtest(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "start": "2024-01-01T00:00:00Z",
+        "end": "2024-01-14T00:00:00Z",
+        "window": "1hour",
+        "profile": wide
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body["message"].as_str().unwrap().contains("queries"));
+
+    // replays never insert alerts
+    let result = sqlx::raw_sql("SELECT * FROM scouter.drift_alerts")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert!(result.is_empty());
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_check_drift_sample() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+
+    let count_records = || async {
+        sqlx::raw_sql("SELECT * FROM scouter.drift")
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+            .len()
+    };
+    let record_count = count_records().await;
+
+    let check_sample = |body: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/scouter/drift/check")
+                        .header(http::header::CONTENT_TYPE, "application/json")
+                        .method("POST")
+                        .body(Body::from(body.to_string()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            let status = response.status();
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            (status, body)
+        }
+    };
+
+    // col_1 stays on its baseline while col_3 sits far above its control limits
+    let (status, body) = check_sample(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "features": {
+            "col_1": vec![-4.0; 250],
+            "col_3": vec![3.0; 250]
+        }
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["data"]["drift"]["col_3"].as_array().unwrap().len(), 10);
+
+    let alerts = body["data"]["alerts"].as_array().unwrap();
+    assert!(!alerts.is_empty());
+    assert!(alerts.iter().all(|alert| alert["feature"] == "col_3"));
+
+    let (status, _) = check_sample(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "features": {
+            "col_1": vec![-4.0; 250],
+            "col_3": vec![3.0; 200]
+        }
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = check_sample(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "features": {
+            "col_9": vec![3.0; 250]
+        }
+    }))
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = check_sample(json!({
+        "name": "missing",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "features": {
+            "col_1": vec![-4.0; 250]
+        }
+    }))
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // the sample is never stored
+    assert_eq!(count_records().await, record_count);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_profile_catch_up() {
+    let app = test_utils::setup_api(true).await.unwrap();
+    let pool = test_utils::setup_db(false).await.unwrap();
+
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+
+    let update_catch_up = |body: Value| {
+        let app = app.clone();
+        async move {
+            app.oneshot(
+                Request::builder()
+                    .uri("/scouter/profile/catch_up")
+                    .header(http::header::CONTENT_TYPE, "application/json")
+                    .method("PUT")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+        }
+    };
+
+    let status = update_catch_up(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "catch_up": "run_each"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let result = sqlx::raw_sql(
+        r#"
+        SELECT catch_up
+        FROM scouter.drift_profile
+        WHERE name = 'test_app'
+        AND repository = 'statworld'
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(result[0].get::<String, _>("catch_up"), "run_each");
+
+    // unknown policy
+    let status = update_catch_up(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "0.1.0",
+        "catch_up": "backfill"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // missing profile
+    let status = update_catch_up(json!({
+        "name": "test_app",
+        "repository": "statworld",
+        "version": "9.9.9",
+        "catch_up": "skip"
+    }))
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
     test_utils::teardown().await.unwrap();
 }
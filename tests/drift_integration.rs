@@ -1,9 +1,10 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDateTime, Utc};
 use scouter::core::dispatch::dispatcher::dispatcher_logic::{ConsoleAlertDispatcher, Dispatch};
 
 use scouter::core::drift::spc::types::SpcDriftProfile;
 use scouter_server::alerts::base::DriftExecutor;
 use scouter_server::alerts::spc::drift::SpcDrifter;
+use scouter_server::api::schema::ServiceInfo;
 use scouter_server::sql::postgres::PostgresClient;
 use sqlx::{Postgres, Row};
 mod test_utils;
@@ -40,11 +41,23 @@ async fn test_drift_executor_separate() {
 
     let drifter = SpcDrifter::new(drift_profile.clone());
 
-    let (drift_array, keys) = drifter
-        .compute_drift(&previous_run, &db_client)
+    let service_info = ServiceInfo {
+        name: name.clone(),
+        repository: repository.clone(),
+        version: version.clone(),
+    };
+    let records = db_client
+        .get_drift_records(
+            &service_info,
+            &previous_run.to_string(),
+            None,
+            &drift_profile.config.alert_config.features_to_monitor,
+        )
         .await
         .unwrap();
 
+    let (drift_array, keys) = drifter.compute_drift_from_records(&records).unwrap();
+
     assert_eq!(drift_array.shape(), [10, 3] as [usize; 2]);
 
     let alerts = drifter
@@ -108,8 +121,12 @@ async fn test_drift_executor() {
 
     let previous_run: NaiveDateTime = result[0].get("previous_run");
 
-    // assert next run from before computing drift is now the previous run
-    assert_eq!(previous_run, curr_next_run);
+    // the fixture missed yesterday's run, so the previous run is the latest scheduled time (today's midnight)
+    assert!(previous_run > curr_next_run);
+    assert_eq!(
+        previous_run,
+        Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap()
+    );
 
     let result = sqlx::raw_sql(
         r#"
@@ -123,5 +140,133 @@ async fn test_drift_executor() {
 
     assert_eq!(result.len(), 2);
 
+    let result = sqlx::raw_sql(
+        r#"
+        SELECT status, alerts_produced, rows_read
+        FROM scouter.drift_task_runs
+        WHERE name = 'test_app'
+        AND repository = 'statworld'
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    // every run is recorded, along with what it read and raised
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].get::<String, _>("status"), "success");
+    assert_eq!(result[0].get::<i32, _>("alerts_produced"), 2);
+    assert!(result[0].get::<i64, _>("rows_read") > 0);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_drift_executor_missing_data() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    // populate the database, then remove one of the monitored features
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+    sqlx::raw_sql(
+        r#"
+        DELETE
+        FROM scouter.drift
+        WHERE repository = 'statworld'
+        AND feature = 'col_3'
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let mut drift_executor = DriftExecutor::new(db_client.clone());
+    drift_executor.poll_for_tasks().await.unwrap();
+
+    let result = sqlx::raw_sql(
+        r#"
+        SELECT feature, alert->>'kind' AS kind
+        FROM scouter.drift_alerts
+        WHERE repository = 'statworld'
+        AND alert->>'kind' = 'missing_data'
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    // col_2 isn't monitored and col_1 still reports
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].get::<String, _>("feature"), "col_3");
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_drift_executor_catch_up() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    // both active fixture profiles have missed yesterday's run
+    let populate_script = include_str!("scripts/populate.sql");
+    sqlx::raw_sql(populate_script).execute(&pool).await.unwrap();
+    sqlx::raw_sql(
+        r#"
+        UPDATE scouter.drift_profile
+        SET catch_up = CASE WHEN repository = 'statworld' THEN 'run_each' ELSE 'skip' END
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let mut drift_executor = DriftExecutor::new(db_client.clone());
+    for _ in 0..2 {
+        drift_executor.poll_for_tasks().await.unwrap();
+    }
+
+    let result = sqlx::raw_sql(
+        r#"
+        SELECT status, alerts_produced, rows_read, catch_up
+        FROM scouter.drift_task_runs
+        WHERE repository = 'statworld'
+        ORDER BY window_start
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    // the missed interval is evaluated on its own, all of the records fall in the latest one
+    assert_eq!(result.len(), 2);
+    assert!(result
+        .iter()
+        .all(|run| run.get::<String, _>("status") == "success"
+            && run.get::<String, _>("catch_up") == "run_each"));
+    assert_eq!(result[0].get::<i64, _>("rows_read"), 0);
+    assert_eq!(result[1].get::<i32, _>("alerts_produced"), 2);
+
+    let result = sqlx::raw_sql(
+        r#"
+        SELECT status
+        FROM scouter.drift_task_runs
+        WHERE catch_up = 'skip'
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+
+    // the missed interval is recorded as skipped and only the latest one is evaluated
+    assert_eq!(result.len(), 2);
+    assert_eq!(
+        result
+            .iter()
+            .filter(|run| run.get::<String, _>("status") == "skipped")
+            .count(),
+        1
+    );
+
     test_utils::teardown().await.unwrap();
 }
@@ -1,11 +1,36 @@
 use sqlx::Row;
 
-use scouter_server::api::schema::{DriftAlertRequest, DriftRequest};
+use scouter_server::api::schema::{DriftAlertRequest, DriftRequest, ObservabilityMetricRequest};
+use scouter_server::consumer::base::{IngestRecords, MessageHandler};
+#[cfg(any(feature = "kafka", feature = "rabbitmq"))]
+use scouter_server::consumer::bulk::{BulkWriter, BulkWriterConfig};
+use scouter_server::observe::record::ObservabilityRecord;
+use scouter_server::observe::sketch::LatencySketch;
 use scouter_server::sql::postgres::PostgresClient;
 mod test_utils;
+use scouter::core::drift::base::{RecordType, ServerRecord, ServerRecords};
 use scouter::core::drift::spc::types::SpcServerRecord;
+use scouter::core::observe::observer::{LatencyMetrics, ObservabilityMetrics, RouteMetrics};
+use scouter_server::alerts::psi::types::PsiServerRecord;
 use scouter_server::api::schema::ServiceInfo;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
+
+// Server records as received over the wire
+fn ingest_records(records: ServerRecords) -> IngestRecords {
+    serde_json::from_value(serde_json::to_value(records).unwrap()).unwrap()
+}
+
+// Psi records as received over the wire, the pinned scouter release has no psi server record
+fn psi_ingest_records(records: Vec<PsiServerRecord>) -> IngestRecords {
+    serde_json::from_value(serde_json::json!({
+        "record_type": "PSI",
+        "records": records
+            .into_iter()
+            .map(|record| serde_json::json!({"PSI": {"record": record}}))
+            .collect::<Vec<_>>()
+    }))
+    .unwrap()
+}
 
 #[tokio::test]
 async fn test_postgres_client() {
@@ -22,7 +47,15 @@ async fn test_postgres_client() {
         version: "1.0.0".to_string(),
     };
 
-    db_client.insert_spc_drift_record(&record).await.unwrap();
+    MessageHandler::Postgres(db_client.clone())
+        .insert_server_records(&ingest_records(ServerRecords {
+            record_type: RecordType::SPC,
+            records: vec![ServerRecord::SPC {
+                record: record.clone(),
+            }],
+        }))
+        .await
+        .unwrap();
 
     let result = db_client
         .raw_query(
@@ -64,7 +97,12 @@ async fn test_postgres_client() {
     };
 
     let result = db_client
-        .get_drift_records(&service_info, limit_timestamp.to_string().as_str(), &[])
+        .get_drift_records(
+            &service_info,
+            limit_timestamp.to_string().as_str(),
+            None,
+            &[],
+        )
         .await
         .unwrap();
 
@@ -92,6 +130,7 @@ async fn test_postgres_client() {
         limit_timestamp: None,
         active: Some(true),
         limit: None,
+        ..Default::default()
     };
     let result = db_client
         .get_drift_alerts(&drift_alert_request)
@@ -107,6 +146,7 @@ async fn test_postgres_client() {
         limit_timestamp: Some(result[0].created_at.to_string()),
         active: Some(true),
         limit: Some(50),
+        ..Default::default()
     };
     let result = db_client
         .get_drift_alerts(&drift_alert_request)
@@ -115,17 +155,51 @@ async fn test_postgres_client() {
 
     assert_eq!(result.len(), 1);
 
+    // timestamps are parsed before they reach the query
+    let drift_alert_request = DriftAlertRequest {
+        name: record.name.clone(),
+        repository: record.repository.clone(),
+        version: record.version.clone(),
+        limit_timestamp: Some("2024-01-01'; DELETE FROM scouter.drift_alerts; --".to_string()),
+        ..Default::default()
+    };
+    assert!(db_client
+        .get_drift_alerts(&drift_alert_request)
+        .await
+        .is_err());
+
+    let drift_alert_request = DriftAlertRequest {
+        name: record.name.clone(),
+        repository: record.repository.clone(),
+        version: record.version.clone(),
+        ..Default::default()
+    };
+    assert_eq!(
+        db_client
+            .get_drift_alerts(&drift_alert_request)
+            .await
+            .unwrap()
+            .len(),
+        3
+    );
+
     let drift_request = DriftRequest {
         name: record.name.clone(),
         repository: record.repository.clone(),
         version: record.version.clone(),
-        time_window: "30minute".to_string(),
+        time_window: Some("30minute".to_string()),
+        start: None,
+        end: None,
         max_data_points: 1000,
+        aggregation: None,
+        bands: None,
+        fill_gaps: None,
+        features: None,
     };
 
     //test get_binned_drift_records
     let result = db_client
-        .get_binned_drift_records(&drift_request)
+        .get_binned_drift_records(&drift_request, &drift_request.time_range().unwrap())
         .await
         .unwrap();
 
@@ -133,3 +207,617 @@ async fn test_postgres_client() {
 
     test_utils::teardown().await.unwrap();
 }
+
+#[tokio::test]
+async fn test_postgres_psi_records() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let created_at = chrono::Utc::now().naive_utc();
+    let message_handler = MessageHandler::Postgres(db_client.clone());
+
+    // two features, three bins each
+    let records = (0..2)
+        .flat_map(|feature| {
+            (0..3).map(move |bin| PsiServerRecord {
+                created_at,
+                name: "test_app".to_string(),
+                repository: "test".to_string(),
+                version: "1.0.0".to_string(),
+                feature: format!("feature_{}", feature),
+                bin_id: bin,
+                bin_count: bin + 1,
+                record_type: RecordType::PSI,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    message_handler
+        .insert_server_records(&psi_ingest_records(records))
+        .await
+        .unwrap();
+
+    let service_info = ServiceInfo {
+        name: "test_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+    };
+
+    let limit_datetime = created_at - chrono::Duration::minutes(1);
+
+    let result = db_client
+        .get_observed_bin_counts(&service_info, &limit_datetime, None, &[])
+        .await
+        .unwrap();
+
+    assert_eq!(result.len(), 6);
+
+    let result = db_client
+        .get_observed_bin_counts(
+            &service_info,
+            &limit_datetime,
+            None,
+            &["feature_1".to_string()],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result.len(), 3);
+    assert_eq!(result.iter().map(|count| count.bin_count).sum::<i64>(), 6);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_postgres_feature_catalogue() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let service_info = ServiceInfo {
+        name: "catalogue_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+    };
+
+    // records written without going through ingestion are catalogued on first read
+    sqlx::raw_sql(
+        r#"
+        INSERT INTO scouter.drift (created_at, name, repository, feature, value, version)
+        VALUES
+            (timezone('utc', now()), 'catalogue_app', 'test', 'feature_0', 1.0, '1.0.0'),
+            (timezone('utc', now()), 'catalogue_app', 'test', 'feature_1', 1.0, '1.0.0');
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let features = db_client
+        .get_feature_catalogue(&service_info)
+        .await
+        .unwrap();
+    assert_eq!(
+        features
+            .iter()
+            .map(|f| (f.feature.as_str(), f.data_type.as_str(), f.record_count))
+            .collect::<Vec<_>>(),
+        vec![("feature_0", "spc", 1), ("feature_1", "spc", 1)]
+    );
+
+    // a psi series with the same name is catalogued next to the spc one
+    MessageHandler::Postgres(db_client.clone())
+        .insert_server_records(&psi_ingest_records(vec![PsiServerRecord {
+            created_at: chrono::Utc::now().naive_utc(),
+            name: "catalogue_app".to_string(),
+            repository: "test".to_string(),
+            version: "1.0.0".to_string(),
+            feature: "feature_0".to_string(),
+            bin_id: 0,
+            bin_count: 1,
+            record_type: RecordType::PSI,
+        }]))
+        .await
+        .unwrap();
+
+    let features = db_client
+        .get_feature_catalogue(&service_info)
+        .await
+        .unwrap();
+    assert_eq!(
+        features
+            .iter()
+            .map(|f| (f.feature.as_str(), f.data_type.as_str(), f.record_count))
+            .collect::<Vec<_>>(),
+        vec![
+            ("feature_0", "psi", 1),
+            ("feature_0", "spc", 1),
+            ("feature_1", "spc", 1)
+        ]
+    );
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_postgres_observability_batch() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    // several instances of one service reporting in the same batch, without client timestamps
+    let records = (0..5)
+        .map(|i| {
+            let metrics = ObservabilityMetrics {
+                name: "batch_app".to_string(),
+                repository: "test".to_string(),
+                version: "1.0.0".to_string(),
+                request_count: i + 1,
+                error_count: 0,
+                route_metrics: Vec::new(),
+            };
+            serde_json::from_value::<ObservabilityRecord>(serde_json::to_value(metrics).unwrap())
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let mut transaction = pool.begin().await.unwrap();
+    let inserted = PostgresClient::insert_observability_records(&mut transaction, &records)
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+    assert_eq!(inserted, 5);
+
+    let rows = db_client
+        .raw_query("SELECT * FROM scouter.observability_metrics WHERE name = 'batch_app'")
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 5);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[cfg(any(feature = "kafka", feature = "rabbitmq"))]
+#[tokio::test]
+async fn test_bulk_writer() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let bulk_writer = BulkWriter::new(
+        db_client.clone(),
+        BulkWriterConfig {
+            batch_size: 5,
+            flush_interval: std::time::Duration::from_secs(60),
+        },
+    );
+
+    let server_records = |start: usize, count: usize| ServerRecords {
+        record_type: RecordType::SPC,
+        records: (start..start + count)
+            .map(|i| ServerRecord::SPC {
+                record: SpcServerRecord {
+                    created_at: chrono::Utc::now().naive_utc(),
+                    name: "bulk_app".to_string(),
+                    repository: "test".to_string(),
+                    feature: format!("feature_{}", i),
+                    value: i as f64,
+                    version: "1.0.0".to_string(),
+                },
+            })
+            .collect(),
+    };
+
+    let count_records = || async {
+        db_client
+            .raw_query("SELECT * FROM scouter.drift WHERE name = 'bulk_app'")
+            .await
+            .unwrap()
+            .len()
+    };
+
+    let flushed = bulk_writer.subscribe();
+
+    // below the batch size records stay buffered
+    let (result, first) = bulk_writer
+        .write(&ingest_records(server_records(0, 3)))
+        .await
+        .unwrap();
+    assert_eq!(result.accepted, 3);
+    assert_eq!(count_records().await, 0);
+    assert!(*flushed.borrow() < first.0);
+
+    // reaching the batch size writes the buffer
+    let (_, second) = bulk_writer
+        .write(&ingest_records(server_records(3, 3)))
+        .await
+        .unwrap();
+    assert_eq!(count_records().await, 6);
+    assert_eq!(first, second);
+    assert!(*flushed.borrow() >= second.0);
+
+    // flush writes whatever is left
+    let (_, third) = bulk_writer
+        .write(&ingest_records(server_records(6, 2)))
+        .await
+        .unwrap();
+    assert!(*flushed.borrow() < third.0);
+    assert_eq!(bulk_writer.flush().await.unwrap(), 2);
+    assert_eq!(count_records().await, 8);
+    assert!(*flushed.borrow() >= third.0);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_postgres_resolve_alerts() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let service_info = ServiceInfo {
+        name: "test_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+    };
+
+    for feature in ["feature_a", "feature_b"] {
+        let mut alert = BTreeMap::new();
+        alert.insert("feature".to_string(), feature.to_string());
+        alert.insert("kind".to_string(), "Consecutive".to_string());
+        alert.insert("zone".to_string(), "Zone 1".to_string());
+
+        db_client
+            .insert_drift_alert(&service_info, feature, &alert)
+            .await
+            .unwrap();
+    }
+
+    // a clean run for feature_a resolves only its alert
+    let resolved = db_client
+        .resolve_drift_alerts(
+            &service_info,
+            &["feature_a".to_string()],
+            "scouter",
+            "clean run",
+        )
+        .await
+        .unwrap();
+    assert_eq!(resolved, 1);
+
+    let alerts = db_client
+        .get_drift_alerts(&DriftAlertRequest {
+            name: service_info.name.clone(),
+            repository: service_info.repository.clone(),
+            version: service_info.version.clone(),
+            limit_timestamp: None,
+            active: None,
+            limit: None,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let resolved_alert = alerts.iter().find(|a| a.feature == "feature_a").unwrap();
+    let open_alert = alerts.iter().find(|a| a.feature == "feature_b").unwrap();
+    assert_eq!(resolved_alert.status, "resolved");
+    assert_eq!(open_alert.status, "active");
+
+    let history = db_client
+        .get_drift_alert_history(resolved_alert.id)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].previous_status, "active");
+    assert_eq!(history[0].status, "resolved");
+    assert_eq!(history[0].actor, "scouter");
+
+    // already resolved alerts are left alone
+    let resolved = db_client
+        .resolve_drift_alerts(
+            &service_info,
+            &["feature_a".to_string()],
+            "scouter",
+            "clean run",
+        )
+        .await
+        .unwrap();
+    assert_eq!(resolved, 0);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_postgres_purge_job() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    // small batches so every table needs more than one delete
+    unsafe {
+        std::env::set_var("SCOUTER_PURGE_BATCH_SIZE", "2");
+    }
+
+    let service_info = |name: &str| ServiceInfo {
+        name: name.to_string(),
+        repository: "purge".to_string(),
+        version: "1.0.0".to_string(),
+    };
+
+    for name in ["purged_app", "kept_app"] {
+        let records = (0..5)
+            .map(|i| ServerRecord::SPC {
+                record: SpcServerRecord {
+                    created_at: chrono::Utc::now().naive_utc(),
+                    name: name.to_string(),
+                    repository: "purge".to_string(),
+                    feature: format!("feature_{}", i),
+                    value: i as f64,
+                    version: "1.0.0".to_string(),
+                },
+            })
+            .collect::<Vec<_>>();
+        MessageHandler::Postgres(db_client.clone())
+            .insert_server_records(&ingest_records(ServerRecords {
+                record_type: RecordType::SPC,
+                records,
+            }))
+            .await
+            .unwrap();
+
+        for i in 0..3 {
+            let mut alert = BTreeMap::new();
+            alert.insert("kind".to_string(), "Consecutive".to_string());
+            db_client
+                .insert_drift_alert(&service_info(name), &format!("feature_{}", i), &alert)
+                .await
+                .unwrap();
+        }
+    }
+
+    let job = db_client
+        .insert_purge_job(&service_info("purged_app"), "test")
+        .await
+        .unwrap();
+    assert_eq!(job.status, "pending");
+
+    let rows_deleted = db_client.run_purge_job(&job).await.unwrap();
+    assert_eq!(rows_deleted, Some(8));
+
+    // finished jobs cannot be claimed again
+    assert_eq!(db_client.run_purge_job(&job).await.unwrap(), None);
+
+    let job = db_client.get_purge_job(job.id).await.unwrap().unwrap();
+    assert_eq!(job.status, "completed");
+    assert_eq!(job.rows_deleted, 8);
+    assert!(db_client
+        .get_unfinished_purge_jobs()
+        .await
+        .unwrap()
+        .is_empty());
+
+    // jobs another instance is still running are left alone
+    let running_job = db_client
+        .insert_purge_job(&service_info("kept_app"), "test")
+        .await
+        .unwrap();
+    db_client
+        .raw_query(&format!(
+            "UPDATE scouter.purge_jobs SET status = 'running' WHERE id = {}",
+            running_job.id
+        ))
+        .await
+        .unwrap();
+    assert_eq!(db_client.run_purge_job(&running_job).await.unwrap(), None);
+
+    let count_rows = |table: &str, name: &str| {
+        let query = format!(
+            "SELECT * FROM scouter.{} WHERE repository = 'purge' AND name = '{}'",
+            table, name
+        );
+        let db_client = db_client.clone();
+        async move { db_client.raw_query(&query).await.unwrap().len() }
+    };
+
+    assert_eq!(count_rows("drift", "purged_app").await, 0);
+    assert_eq!(count_rows("drift_alerts", "purged_app").await, 0);
+    assert_eq!(count_rows("drift", "kept_app").await, 5);
+    assert_eq!(count_rows("drift_alerts", "kept_app").await, 3);
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_postgres_latency_sketches() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let record = |p99: f64, latency: f64, count: usize| {
+        let mut sketch = LatencySketch::default();
+        sketch.add(latency, count as u64);
+
+        ObservabilityRecord {
+            metrics: ObservabilityMetrics {
+                name: "sketch_app".to_string(),
+                repository: "test".to_string(),
+                version: "1.0.0".to_string(),
+                request_count: count as i64,
+                error_count: 0,
+                route_metrics: vec![RouteMetrics {
+                    route_name: "/predict".to_string(),
+                    metrics: LatencyMetrics {
+                        p5: latency,
+                        p25: latency,
+                        p50: latency,
+                        p95: latency,
+                        p99,
+                    },
+                    request_count: count as i64,
+                    error_count: 0,
+                    error_latency: 0.0,
+                    status_codes: HashMap::new(),
+                }],
+            },
+            created_at: chrono::Utc::now().naive_utc(),
+            latency_sketches: BTreeMap::from([("/predict".to_string(), sketch)]),
+        }
+    };
+
+    // a busy fast instance and a quiet slow one
+    let records = vec![record(0.01, 0.01, 980), record(2.0, 2.0, 20)];
+
+    let mut transaction = pool.begin().await.unwrap();
+    let inserted = PostgresClient::insert_observability_records(&mut transaction, &records)
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+    assert_eq!(inserted, 2);
+
+    let params = ObservabilityMetricRequest {
+        name: "sketch_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+        time_window: Some("5minute".to_string()),
+        start: None,
+        end: None,
+        max_data_points: 1,
+        fill_gaps: None,
+    };
+
+    let metrics = db_client
+        .get_binned_observability_metrics(&params, &params.time_range().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].sample_count, vec![2]);
+
+    // averaging the p99s would report ~1s, the merged sketch reports the slow tail
+    let p99 = metrics[0].p99[0].unwrap();
+    assert!((p99 - 2.0).abs() < 0.02);
+    let p50 = metrics[0].p50[0].unwrap();
+    assert!((p50 - 0.01).abs() < 0.001);
+
+    // alerting windows merge the sketches too
+    let service_info = ServiceInfo {
+        name: "sketch_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+    };
+    let now = chrono::Utc::now().naive_utc();
+    let summaries = db_client
+        .get_route_summaries(
+            &service_info,
+            now - chrono::Duration::hours(1),
+            now + chrono::Duration::minutes(1),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].request_count, 1000);
+    assert!((summaries[0].p99.unwrap() - 2.0).abs() < 0.02);
+    assert!((summaries[0].p95.unwrap() - 0.01).abs() < 0.001);
+
+    // without a sketch for every record the highest reported percentile is used
+    let mut record = record(3.0, 0.01, 10);
+    record.latency_sketches.clear();
+
+    let mut transaction = pool.begin().await.unwrap();
+    PostgresClient::insert_observability_records(&mut transaction, &[record])
+        .await
+        .unwrap();
+    transaction.commit().await.unwrap();
+
+    let summaries = db_client
+        .get_route_summaries(
+            &service_info,
+            now - chrono::Duration::hours(1),
+            now + chrono::Duration::minutes(1),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(summaries[0].sample_count, 3);
+    assert_eq!(summaries[0].p99, Some(3.0));
+
+    test_utils::teardown().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_postgres_ingest_latency_sketches() {
+    let pool = test_utils::setup_db(true).await.unwrap();
+    let db_client = PostgresClient::new(pool.clone()).unwrap();
+
+    let metrics = |latency: f64, count: i64| ObservabilityMetrics {
+        name: "ingest_sketch_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+        request_count: count,
+        error_count: 0,
+        route_metrics: vec![RouteMetrics {
+            route_name: "/predict".to_string(),
+            metrics: LatencyMetrics {
+                p5: latency,
+                p25: latency,
+                p50: latency,
+                p95: latency,
+                p99: latency,
+            },
+            request_count: count,
+            error_count: 0,
+            error_latency: 0.0,
+            status_codes: HashMap::new(),
+        }],
+    };
+
+    // a busy fast instance that reports a sketch and a quiet slow one that only reports percentiles
+    let mut body = serde_json::to_value(ServerRecords {
+        record_type: RecordType::OBSERVABILITY,
+        records: vec![
+            ServerRecord::OBSERVABILITY {
+                record: metrics(0.5, 980),
+            },
+            ServerRecord::OBSERVABILITY {
+                record: metrics(2.0, 20),
+            },
+        ],
+    })
+    .unwrap();
+
+    let mut sketch = LatencySketch::default();
+    sketch.add(0.01, 980);
+    body["records"][0]["OBSERVABILITY"]["record"]["route_metrics"][0]["latency_sketch"] =
+        serde_json::to_value(&sketch).unwrap();
+
+    let records: IngestRecords = serde_json::from_value(body).unwrap();
+    let result = MessageHandler::Postgres(db_client.clone())
+        .insert_server_records(&records)
+        .await
+        .unwrap();
+    assert_eq!(result.accepted, 2);
+
+    let params = ObservabilityMetricRequest {
+        name: "ingest_sketch_app".to_string(),
+        repository: "test".to_string(),
+        version: "1.0.0".to_string(),
+        time_window: Some("5minute".to_string()),
+        start: None,
+        end: None,
+        max_data_points: 1,
+        fill_gaps: None,
+    };
+
+    let metrics = db_client
+        .get_binned_observability_metrics(&params, &params.time_range().unwrap())
+        .await
+        .unwrap();
+
+    assert_eq!(metrics.len(), 1);
+    assert_eq!(metrics[0].sample_count, vec![2]);
+
+    // the reported sketch wins over the fixed percentiles of the same record
+    let p50 = metrics[0].p50[0].unwrap();
+    assert!((p50 - 0.01).abs() < 0.001);
+
+    // the slow tail comes from the sketch approximated at ingestion
+    let p99 = metrics[0].p99[0].unwrap();
+    assert!((p99 - 2.0).abs() < 0.02);
+
+    test_utils::teardown().await.unwrap();
+}
@@ -1,6 +1,8 @@
 use anyhow::Error;
 use axum::Router;
 
+use scouter_server::api::auth::AuthConfig;
+use scouter_server::api::jwt::JwtValidator;
 use scouter_server::api::route::create_router;
 use scouter_server::api::route::AppState;
 use scouter_server::api::setup::create_db_pool;
@@ -53,11 +55,30 @@ pub async fn setup_api(clean_db: bool) -> Result<Router, Error> {
     let pool = setup_db(clean_db).await.unwrap();
 
     let db_client = PostgresClient::new(pool).unwrap();
-    let router = create_router(Arc::new(AppState { db: db_client }));
+    let router = create_router(Arc::new(AppState {
+        db: db_client,
+        auth: AuthConfig::default(),
+    }));
 
     Ok(router)
 }
 
+#[allow(dead_code)]
+pub async fn setup_auth_api(
+    clean_db: bool,
+    jwt: Option<JwtValidator>,
+) -> Result<(Router, PostgresClient), Error> {
+    let pool = setup_db(clean_db).await.unwrap();
+
+    let db_client = PostgresClient::new(pool).unwrap();
+    let router = create_router(Arc::new(AppState {
+        db: db_client.clone(),
+        auth: AuthConfig { enabled: true, jwt },
+    }));
+
+    Ok((router, db_client))
+}
+
 #[allow(dead_code)]
 pub async fn teardown() -> Result<(), Error> {
     // clear the database
@@ -74,6 +95,30 @@ pub async fn teardown() -> Result<(), Error> {
 
             DELETE
             FROM scouter.drift_alerts;
+
+            DELETE
+            FROM scouter.drift_alert_history;
+
+            DELETE
+            FROM scouter.observed_bin_count;
+
+            DELETE
+            FROM scouter.observability_metrics;
+
+            DELETE
+            FROM scouter.api_keys;
+
+            DELETE
+            FROM scouter.repository_roles;
+
+            DELETE
+            FROM scouter.purge_jobs;
+
+            DELETE
+            FROM scouter.drift_task_runs;
+
+            DELETE
+            FROM scouter.features;
             "#,
     )
     .fetch_all(&pool)